@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::core::entity::user;
 use crate::server::AppState;
+use crate::service::quota::QuotaUsage;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserResponse {
@@ -17,7 +18,11 @@ pub struct UserResponse {
     pub is_admin: bool,
     pub can_stream: bool,
     pub is_disabled: bool,
+    pub is_trusted: bool,
     pub created_at: String,
+    pub quota_bytes: Option<i64>,
+    pub quota_clips: Option<i64>,
+    pub quota_daily_uploads: Option<i64>,
 }
 
 impl From<user::Model> for UserResponse {
@@ -29,7 +34,11 @@ impl From<user::Model> for UserResponse {
             is_admin: user.is_admin,
             can_stream: user.can_stream,
             is_disabled: user.is_disabled,
+            is_trusted: user.is_trusted,
             created_at: user.created_at.to_string(),
+            quota_bytes: user.quota_bytes,
+            quota_clips: user.quota_clips,
+            quota_daily_uploads: user.quota_daily_uploads,
         }
     }
 }
@@ -39,6 +48,30 @@ pub struct UpdateUserPermissionsRequest {
     pub is_admin: Option<bool>,
     pub can_stream: Option<bool>,
     pub is_disabled: Option<bool>,
+    pub is_trusted: Option<bool>,
+}
+
+/// 用户单独的配额，为空时使用角色配额，负数表示不限
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateUserQuotaRequest {
+    pub quota_bytes: Option<i64>,
+    pub quota_clips: Option<i64>,
+    pub quota_daily_uploads: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaResponse {
+    pub max_bytes: Option<u64>,
+    pub max_clips: Option<u64>,
+    pub max_daily_uploads: Option<u64>,
+    pub usage: QuotaUsage,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CurrentUserResponse {
+    #[serde(flatten)]
+    pub user: UserResponse,
+    pub quota: QuotaResponse,
 }
 
 // 管理员获取所有用户列表
@@ -93,6 +126,7 @@ pub async fn update_user_permissions(
             request.is_admin,
             request.can_stream,
             request.is_disabled,
+            request.is_trusted,
         )
         .await
     {
@@ -104,6 +138,46 @@ pub async fn update_user_permissions(
     }
 }
 
-pub async fn get_current_user(Extension(user): Extension<user::Model>) -> impl IntoResponse {
-    Json(UserResponse::from(user))
+// 管理员设置用户配额
+pub async fn update_user_quota(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<i64>,
+    Json(request): Json<UpdateUserQuotaRequest>,
+) -> impl IntoResponse {
+    match state
+        .user_svc
+        .update_user_quota(
+            user_id,
+            request.quota_bytes,
+            request.quota_clips,
+            request.quota_daily_uploads,
+        )
+        .await
+    {
+        Ok(updated_user) => Ok(Json(UserResponse::from(updated_user))),
+        Err(e) => {
+            tracing::error!("Failed to update user quota: {}", e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+        }
+    }
+}
+
+pub async fn get_current_user(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+) -> impl IntoResponse {
+    let quota = state.clip_svc.quota(&user);
+    let usage = state.clip_svc.quota_usage(&user).await.map_err(|e| {
+        tracing::error!("Failed to get quota usage: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    })?;
+    Ok::<_, (StatusCode, String)>(Json(CurrentUserResponse {
+        user: UserResponse::from(user),
+        quota: QuotaResponse {
+            max_bytes: quota.max_bytes.map(|b| b.as_u64()),
+            max_clips: quota.max_clips,
+            max_daily_uploads: quota.max_daily_uploads,
+            usage,
+        },
+    }))
 }
@@ -0,0 +1,143 @@
+use std::sync::Arc;
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
+
+use crate::data::{CatalogEntry, CatalogKind};
+use crate::server::AppState;
+use crate::service::catalog::CatalogUpdate;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CatalogRequest {
+    pub name: String,
+    // vup 的头像或歌曲的封面
+    pub image_url: Option<String>,
+    pub link: Option<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MergeCatalogRequest {
+    pub into: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogResponse {
+    pub id: i64,
+    pub name: String,
+    pub image_url: Option<String>,
+    pub link: Option<String>,
+    pub aliases: Vec<String>,
+    pub clip_count: i64,
+}
+
+impl From<CatalogEntry> for CatalogResponse {
+    fn from(entry: CatalogEntry) -> Self {
+        CatalogResponse {
+            id: entry.id,
+            name: entry.name,
+            image_url: entry.image_url,
+            link: entry.link,
+            aliases: entry.aliases,
+            clip_count: entry.clip_count,
+        }
+    }
+}
+
+async fn list(
+    state: &AppState,
+    kind: CatalogKind,
+) -> Result<Json<Vec<CatalogResponse>>, (StatusCode, String)> {
+    match state.catalog_svc.list(kind).await {
+        Ok(entries) => Ok(Json(
+            entries
+                .into_iter()
+                .map(CatalogResponse::from)
+                .collect::<Vec<_>>(),
+        )),
+        Err(e) => {
+            tracing::error!("Failed to list {:?} catalog: {}", kind, e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+        }
+    }
+}
+
+async fn update(
+    state: &AppState,
+    kind: CatalogKind,
+    id: i64,
+    req: CatalogRequest,
+) -> Result<Json<CatalogResponse>, (StatusCode, String)> {
+    let update = CatalogUpdate {
+        name: req.name,
+        image_url: req.image_url,
+        link: req.link,
+        aliases: req.aliases,
+    };
+    match state.catalog_svc.update(kind, id, update).await {
+        Ok(entry) => Ok(Json(CatalogResponse::from(entry))),
+        Err(e) => {
+            tracing::error!("Failed to update {:?} {}: {}", kind, id, e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+async fn merge(
+    state: &AppState,
+    kind: CatalogKind,
+    from: i64,
+    into: i64,
+) -> Result<Json<CatalogResponse>, (StatusCode, String)> {
+    match state.catalog_svc.merge(kind, from, into).await {
+        Ok(entry) => Ok(Json(CatalogResponse::from(entry))),
+        Err(e) => {
+            tracing::error!("Failed to merge {:?} {} into {}: {}", kind, from, into, e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+pub async fn list_vups(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    list(&state, CatalogKind::Vup).await
+}
+
+pub async fn update_vup(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<CatalogRequest>,
+) -> impl IntoResponse {
+    update(&state, CatalogKind::Vup, id, req).await
+}
+
+pub async fn merge_vup(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<MergeCatalogRequest>,
+) -> impl IntoResponse {
+    merge(&state, CatalogKind::Vup, id, req.into).await
+}
+
+pub async fn list_songs(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    list(&state, CatalogKind::Song).await
+}
+
+pub async fn update_song(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<CatalogRequest>,
+) -> impl IntoResponse {
+    update(&state, CatalogKind::Song, id, req).await
+}
+
+pub async fn merge_song(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<MergeCatalogRequest>,
+) -> impl IntoResponse {
+    merge(&state, CatalogKind::Song, id, req.into).await
+}
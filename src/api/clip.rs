@@ -10,13 +10,19 @@ use axum::{Extension, Json};
 use futures_util::TryStreamExt;
 use sea_orm::ActiveEnum;
 use serde::{Deserialize, Serialize};
+use tokio::io::AsyncReadExt;
 use tokio_util::io::ReaderStream;
 use tokio_util::io::StreamReader;
 
-use crate::core::entity::{clip, user};
+use crate::api::tag::TagResponse;
+use crate::core::entity::{clip, clip_review, clip_version, tag, user};
 use crate::core::jwt;
 use crate::core::jwt::DEFAULT_SECRET_KEY;
 use crate::server::AppState;
+use crate::service::clip::{
+    ClipEdit, ClipFilter, ClipSort, DEFAULT_PAGE_SIZE, FileNamePattern, SPRITE_VTT_IMAGE,
+    SUPPORTED_EXTENSIONS, Thumbnail, UploadSession,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClipRequest {
@@ -44,8 +50,40 @@ pub struct ClipResponse {
     pub title: String,
     pub vup: String,
     pub song: String,
+    pub vup_id: Option<i64>,
+    pub song_id: Option<i64>,
     pub upload_time: u64,
     pub status: String,
+    pub process_stage: Option<clip::ProcessStage>,
+    pub process_progress: Option<i32>,
+    pub duration_ms: Option<i64>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub frame_rate: Option<f64>,
+    pub video_codec: Option<String>,
+    pub audio_codec: Option<String>,
+    pub bit_rate: Option<i64>,
+    pub file_size: Option<i64>,
+    pub loudness: Option<f64>,
+    pub volume_db: f64,
+    pub suggested_in_ms: Option<i64>,
+    pub suggested_out_ms: Option<i64>,
+    pub duplicate_of: Option<String>,
+    pub review_note: Option<String>,
+    // 移入回收站的时间，Unix 时间戳（秒）
+    pub deleted_at: Option<u64>,
+    // 只在列表和修改标签的响应中返回
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<TagResponse>>,
+}
+
+impl ClipResponse {
+    pub fn with_tags(self, tags: Vec<tag::Model>) -> Self {
+        ClipResponse {
+            tags: Some(tags.into_iter().map(TagResponse::from).collect()),
+            ..self
+        }
+    }
 }
 
 impl From<clip::Model> for ClipResponse {
@@ -55,19 +93,150 @@ impl From<clip::Model> for ClipResponse {
             title: clip.title,
             vup: clip.vup,
             song: clip.song,
+            vup_id: clip.vup_id,
+            song_id: clip.song_id,
             upload_time: clip.upload_time.timestamp() as u64,
             status: clip.status.to_value(),
+            process_stage: clip.process_stage,
+            process_progress: clip.process_progress,
+            duration_ms: clip.duration_ms,
+            width: clip.width,
+            height: clip.height,
+            frame_rate: clip.frame_rate,
+            video_codec: clip.video_codec,
+            audio_codec: clip.audio_codec,
+            bit_rate: clip.bit_rate,
+            file_size: clip.file_size,
+            loudness: clip.loudness,
+            volume_db: clip.volume_db,
+            suggested_in_ms: clip.suggested_in_ms,
+            suggested_out_ms: clip.suggested_out_ms,
+            duplicate_of: clip.duplicate_of.map(|uuid| uuid.to_string()),
+            review_note: clip.review_note,
+            deleted_at: clip.deleted_at.map(|t| t.timestamp() as u64),
+            tags: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipVersionResponse {
+    pub id: i64,
+    pub user_id: i64,
+    pub duration_ms: Option<i64>,
+    pub file_size: Option<i64>,
+    pub created_at: u64,
+}
+
+impl From<clip_version::Model> for ClipVersionResponse {
+    fn from(version: clip_version::Model) -> Self {
+        ClipVersionResponse {
+            id: version.id,
+            user_id: version.user_id,
+            duration_ms: version.duration_ms,
+            file_size: version.file_size,
+            created_at: version.created_at.timestamp() as u64,
         }
     }
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReviewRequest {
+    pub decision: clip_review::Decision,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResubmitRequest {
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipReviewResponse {
+    pub id: i64,
+    pub user_id: i64,
+    pub user_name: Option<String>,
+    pub decision: clip_review::Decision,
+    pub note: Option<String>,
+    pub created_at: u64,
+}
+
+impl From<(clip_review::Model, Option<user::Model>)> for ClipReviewResponse {
+    fn from((review, user): (clip_review::Model, Option<user::Model>)) -> Self {
+        ClipReviewResponse {
+            id: review.id,
+            user_id: review.user_id,
+            user_name: user.map(|u| u.uname),
+            decision: review.decision,
+            note: review.note,
+            created_at: review.created_at.timestamp() as u64,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PageQuery {
+    pub page: Option<u64>,
+    pub page_size: Option<u64>,
+}
+
+fn file_ext(file_name: &str) -> Result<String, (StatusCode, String)> {
+    let file_ext = file_name.split('.').last().unwrap_or("").to_string();
+    if !SUPPORTED_EXTENSIONS.contains(&file_ext.as_str()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Unsupported file type: {file_ext}"),
+        ));
+    }
+    Ok(file_ext)
+}
+
+/// 请求体的长度，用于在读取前检查配额
+fn incoming_length(
+    state: &AppState,
+    user: &user::Model,
+    headers: &HeaderMap,
+) -> Result<u64, (StatusCode, String)> {
+    let content_length = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok());
+    match content_length {
+        Some(len) => Ok(len),
+        // 没有长度时无法在接收前检查存储配额
+        None if state.clip_svc.quota(user).max_bytes.is_some() => Err((
+            StatusCode::LENGTH_REQUIRED,
+            "Content-Length is required when a storage quota applies".into(),
+        )),
+        None => Ok(0),
+    }
+}
+
+/// 在读取请求体之前按 Content-Length 检查配额
+async fn check_upload_quota(
+    state: &AppState,
+    user: &user::Model,
+    headers: &HeaderMap,
+) -> Result<(), (StatusCode, String)> {
+    let incoming = incoming_length(state, user, headers)?;
+    state
+        .clip_svc
+        .check_quota(user, incoming)
+        .await
+        .map_err(|e| (super::error_status(&e), e.to_string()))
+}
+
 pub async fn upload(
     State(state): State<Arc<AppState>>,
     Extension(user): Extension<user::Model>,
+    headers: HeaderMap,
     mut multipart: Multipart,
 ) -> impl IntoResponse {
+    check_upload_quota(&state, &user, &headers).await?;
+
     let mut uuid = None;
     let mut uploaded_file = None;
+    let mut content_hash = None;
     let mut req = None;
 
     while let Some(field) = multipart
@@ -77,17 +246,7 @@ pub async fn upload(
     {
         match field.name().unwrap_or("") {
             "file" => {
-                let file_ext = field
-                    .file_name()
-                    .and_then(|name| name.split('.').last())
-                    .unwrap_or("")
-                    .to_string();
-                if !["mp4", "mkv", "avi", "mov", "flv"].contains(&file_ext.as_str()) {
-                    return Err((
-                        StatusCode::BAD_REQUEST,
-                        format!("Unsupported file type: {file_ext}").into(),
-                    ));
-                }
+                let file_ext = file_ext(field.file_name().unwrap_or(""))?;
 
                 let body_with_io_error = field.map_err(io::Error::other);
                 let mut uploaded_file_reader = pin!(StreamReader::new(body_with_io_error));
@@ -103,6 +262,7 @@ pub async fn upload(
 
                 uuid = Some(file_path.0);
                 uploaded_file = Some(file_path.1);
+                content_hash = Some(file_path.2);
             }
             "metadata" => {
                 let json = field.text().await.map_err(|e| {
@@ -134,40 +294,500 @@ pub async fn upload(
         vup: req.vup,
         song: req.song,
         upload_time: chrono::Utc::now(),
+        content_hash,
         ..Default::default()
     };
 
     let clip = state
         .clip_svc
-        .create_clip(user.id, clip, uploaded_file)
+        .create_clip(&user, clip, uploaded_file)
         .await
         .map_err(|e| {
             tracing::error!("Failed to create clip: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            (super::error_status(&e), e.to_string())
         })?;
 
     Ok(Json(ClipResponse::from(clip)))
 }
 
-pub async fn list_clip(
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchClipRequest {
+    /// 对应的文件名，省略时按文件顺序对应
+    pub file_name: Option<String>,
+    #[serde(flatten)]
+    pub clip: ClipRequest,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchUploadResult {
+    pub file_name: String,
+    pub clip: Option<ClipResponse>,
+    pub error: Option<String>,
+}
+
+/// 批量上传：多个 `file` 字段，可选的 `metadata`（数组）和 `pattern`（文件名模板）。
+/// 单个文件失败不影响其他文件，结果按文件逐个返回。
+pub async fn batch_upload(
     State(state): State<Arc<AppState>>,
     Extension(user): Extension<user::Model>,
+    headers: HeaderMap,
+    mut multipart: Multipart,
 ) -> impl IntoResponse {
-    match state.clip_svc.list_clips_by_user(&user).await {
-        Ok(clips) => {
-            let response = clips
-                .into_iter()
-                .map(|clip| ClipResponse::from(clip))
-                .collect::<Vec<_>>();
-            Ok(Json(response))
+    check_upload_quota(&state, &user, &headers).await?;
+
+    let mut files = Vec::new();
+    let mut metadata: Option<Vec<BatchClipRequest>> = None;
+    let mut pattern = state.config.upload.file_name_pattern.clone();
+
+    // 读取表单出错时清理已保存的文件
+    let parsed = async {
+        while let Some(field) = multipart
+            .next_field()
+            .await
+            .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+        {
+            match field.name().unwrap_or("") {
+                "file" => {
+                    let file_name = field.file_name().unwrap_or("").to_string();
+                    if files.len() >= state.config.upload.max_batch_files {
+                        return Err((StatusCode::BAD_REQUEST, "Too many files".into()));
+                    }
+                    let file_ext = match file_ext(&file_name) {
+                        Ok(ext) => ext,
+                        Err((_, e)) => {
+                            files.push((file_name, Err(e)));
+                            continue;
+                        }
+                    };
+
+                    let body_with_io_error = field.map_err(io::Error::other);
+                    let mut reader = pin!(StreamReader::new(body_with_io_error));
+                    let saved = state
+                        .clip_svc
+                        .save_clip_to_tmp(&mut reader, &file_ext)
+                        .await
+                        .map_err(|e| {
+                            tracing::error!("Failed to save uploaded file {}: {}", file_name, e);
+                            (StatusCode::BAD_REQUEST, e.to_string())
+                        })?;
+                    files.push((file_name, Ok(saved)));
+                }
+                "metadata" => {
+                    let json = field
+                        .text()
+                        .await
+                        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+                    metadata = Some(serde_json::from_str(&json).map_err(|_| {
+                        (StatusCode::BAD_REQUEST, "Invalid metadata format".into())
+                    })?);
+                }
+                "pattern" => {
+                    let text = field
+                        .text()
+                        .await
+                        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+                    pattern = Some(text).filter(|p| !p.trim().is_empty());
+                }
+                _ => {
+                    return Err((
+                        StatusCode::BAD_REQUEST,
+                        "Unexpected field in multipart request".into(),
+                    ));
+                }
+            }
+        }
+        if files.is_empty() {
+            return Err((StatusCode::BAD_REQUEST, "Missing file".into()));
         }
+
+        match (&metadata, pattern) {
+            (None, Some(p)) => FileNamePattern::new(&p)
+                .map(Some)
+                .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string())),
+            _ => Ok(None),
+        }
+    }
+    .await;
+    let pattern = match parsed {
+        Ok(pattern) => pattern,
         Err(e) => {
-            tracing::error!("Failed to list clips: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            for (_, saved) in &files {
+                if let Ok((_, path, _)) = saved {
+                    tokio::fs::remove_file(path).await.ok();
+                }
+            }
+            return Err(e);
+        }
+    };
+    let by_name = metadata
+        .as_ref()
+        .is_some_and(|m| m.iter().any(|r| r.file_name.is_some()));
+
+    let mut results = Vec::with_capacity(files.len());
+    for (index, (file_name, saved)) in files.into_iter().enumerate() {
+        let result = async {
+            let (uuid, path, content_hash) = saved?;
+            let req: Option<clip::Model> = match (&metadata, &pattern) {
+                (Some(metadata), _) if by_name => metadata
+                    .iter()
+                    .find(|r| r.file_name.as_deref() == Some(file_name.as_str()))
+                    .map(|r| r.clip.clone().into()),
+                (Some(metadata), _) => metadata.get(index).map(|r| r.clip.clone().into()),
+                (None, Some(pattern)) => pattern.parse(&file_name),
+                (None, None) => None,
+            };
+            let Some(req) = req.filter(|r| !r.title.trim().is_empty()) else {
+                tokio::fs::remove_file(&path).await.ok();
+                return Err(format!("No metadata for {file_name}"));
+            };
+
+            let clip = clip::Model {
+                uuid,
+                upload_time: chrono::Utc::now(),
+                content_hash: Some(content_hash),
+                ..req
+            };
+            state
+                .clip_svc
+                .create_clip(&user, clip, path)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to create clip for {}: {}", file_name, e);
+                    e.to_string()
+                })
+        }
+        .await;
+
+        results.push(match result {
+            Ok(clip) => BatchUploadResult {
+                file_name,
+                clip: Some(ClipResponse::from(clip)),
+                error: None,
+            },
+            Err(e) => BatchUploadResult {
+                file_name,
+                clip: None,
+                error: Some(e),
+            },
+        });
+    }
+
+    Ok(Json(results))
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportClipRequest {
+    pub url: String,
+    #[serde(flatten)]
+    pub clip: ClipRequest,
+}
+
+pub async fn import_clip(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Json(req): Json<ImportClipRequest>,
+) -> impl IntoResponse {
+    let clip = clip::Model {
+        upload_time: chrono::Utc::now(),
+        ..req.clip.into()
+    };
+    match state
+        .clip_svc
+        .import_clip(&user, &req.url, state.config.max_file_size.as_u64(), clip)
+        .await
+    {
+        Ok(clip) => Ok(Json(ClipResponse::from(clip))),
+        Err(e) => {
+            tracing::error!("Failed to import clip from {}: {}", req.url, e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateUploadRequest {
+    pub file_name: String,
+    pub size: u64,
+    pub metadata: ClipRequest,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UploadResponse {
+    pub id: String,
+    pub offset: u64,
+    pub size: u64,
+}
+
+impl From<UploadSession> for UploadResponse {
+    fn from(session: UploadSession) -> Self {
+        UploadResponse {
+            id: session.id.to_string(),
+            offset: session.offset,
+            size: session.size,
+        }
+    }
+}
+
+const UPLOAD_OFFSET: &str = "upload-offset";
+
+pub async fn create_upload(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Json(req): Json<CreateUploadRequest>,
+) -> impl IntoResponse {
+    let file_ext = file_ext(&req.file_name)?;
+    if req.size == 0 || req.size > state.config.max_file_size.as_u64() {
+        return Err((StatusCode::PAYLOAD_TOO_LARGE, "Invalid file size".into()));
+    }
+
+    match state
+        .clip_svc
+        .create_upload(&user, &file_ext, req.size, req.metadata.into())
+        .await
+    {
+        Ok(session) => Ok((StatusCode::CREATED, Json(UploadResponse::from(session)))),
+        Err(e) => {
+            tracing::error!("Failed to create upload: {}", e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+pub async fn get_upload(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Path(id): Path<uuid::Uuid>,
+) -> impl IntoResponse {
+    match state.clip_svc.get_upload(user.id, id).await {
+        Ok(session) => Ok((
+            [(UPLOAD_OFFSET, session.offset.to_string())],
+            Json(UploadResponse::from(session)),
+        )),
+        Err(e) => Err((super::error_status(&e), e.to_string())),
+    }
+}
+
+pub async fn append_upload(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Path(id): Path<uuid::Uuid>,
+    headers: HeaderMap,
+    body: Body,
+) -> impl IntoResponse {
+    let offset = headers
+        .get(UPLOAD_OFFSET)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+        .ok_or((
+            StatusCode::BAD_REQUEST,
+            "Missing or invalid Upload-Offset header".to_string(),
+        ))?;
+
+    let body_with_io_error = body.into_data_stream().map_err(io::Error::other);
+    let mut reader = pin!(StreamReader::new(body_with_io_error));
+    match state
+        .clip_svc
+        .append_upload(user.id, id, offset, &mut reader)
+        .await
+    {
+        Ok(session) => Ok((
+            [(UPLOAD_OFFSET, session.offset.to_string())],
+            Json(UploadResponse::from(session)),
+        )),
+        Err(e) => {
+            tracing::warn!("Failed to append to upload {}: {}", id, e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+pub async fn finalize_upload(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Path(id): Path<uuid::Uuid>,
+) -> impl IntoResponse {
+    match state.clip_svc.finalize_upload(&user, id).await {
+        Ok(clip) => Ok(Json(ClipResponse::from(clip))),
+        Err(e) => {
+            tracing::error!("Failed to finalize upload {}: {}", id, e);
+            Err((super::error_status(&e), e.to_string()))
         }
     }
 }
 
+pub async fn cancel_upload(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Path(id): Path<uuid::Uuid>,
+) -> impl IntoResponse {
+    match state.clip_svc.cancel_upload(user.id, id).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err((super::error_status(&e), e.to_string())),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListClipsQuery {
+    // 逗号分隔的标签 id，需全部包含
+    pub tags: Option<String>,
+    // 在标题、vup 和歌曲中搜索
+    pub q: Option<String>,
+    // 逗号分隔的状态，如 "reviewing,failed"
+    pub status: Option<String>,
+    pub user_id: Option<i64>,
+    pub vup_id: Option<i64>,
+    pub song_id: Option<i64>,
+    // 上传时间范围，Unix 时间戳（秒），包含 from 不包含 to
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    // 查看回收站
+    #[serde(default)]
+    pub trashed: bool,
+    #[serde(default)]
+    pub sort: ClipSort,
+    // asc 或 desc，默认 desc
+    pub order: Option<String>,
+    pub page: Option<u64>,
+    pub page_size: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClipListResponse {
+    pub items: Vec<ClipResponse>,
+    pub total: u64,
+    pub page: u64,
+    pub page_size: u64,
+}
+
+fn split_list(value: Option<&str>) -> impl Iterator<Item = &str> {
+    value
+        .unwrap_or("")
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+fn parse_timestamp(ts: Option<i64>) -> Result<Option<chrono::DateTime<chrono::Utc>>, String> {
+    ts.map(|ts| chrono::DateTime::from_timestamp(ts, 0).ok_or(format!("Invalid timestamp {ts}")))
+        .transpose()
+}
+
+impl TryFrom<ListClipsQuery> for ClipFilter {
+    type Error = String;
+
+    fn try_from(query: ListClipsQuery) -> Result<Self, Self::Error> {
+        let tags = split_list(query.tags.as_deref())
+            .map(str::parse)
+            .collect::<Result<Vec<i64>, _>>()
+            .map_err(|_| "Invalid tag id".to_string())?;
+        let statuses = split_list(query.status.as_deref())
+            .map(|s| clip::Status::try_from_value(&s.to_string()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| "Invalid status".to_string())?;
+        let ascending = match query.order.as_deref() {
+            None | Some("desc") => false,
+            Some("asc") => true,
+            Some(other) => return Err(format!("Invalid order {other}")),
+        };
+        Ok(ClipFilter {
+            tags,
+            search: query.q,
+            statuses,
+            user_id: query.user_id,
+            vup_id: query.vup_id,
+            song_id: query.song_id,
+            from: parse_timestamp(query.from)?,
+            to: parse_timestamp(query.to)?,
+            trashed: query.trashed,
+            sort: query.sort,
+            ascending,
+            page: query.page.unwrap_or(1),
+            page_size: query.page_size.unwrap_or(DEFAULT_PAGE_SIZE),
+        })
+    }
+}
+
+pub async fn list_clip(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Query(query): Query<ListClipsQuery>,
+) -> impl IntoResponse {
+    let filter = ClipFilter::try_from(query).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    let page = state
+        .clip_svc
+        .list_clips_by_user(&user, &filter)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list clips: {}", e);
+            (super::error_status(&e), e.to_string())
+        })?;
+    let mut tags = state
+        .tag_svc
+        .tags_of_clips(&page.clips)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list clip tags: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        })?;
+    let items = page
+        .clips
+        .into_iter()
+        .map(|clip| {
+            let tags = tags.remove(&clip.id).unwrap_or_default();
+            ClipResponse::from(clip).with_tags(tags)
+        })
+        .collect();
+    Ok::<_, (StatusCode, String)>(Json(ClipListResponse {
+        items,
+        total: page.total,
+        page: page.page,
+        page_size: page.page_size,
+    }))
+}
+
+pub async fn get_clip(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Path(uuid): Path<uuid::Uuid>,
+) -> impl IntoResponse {
+    let clip = state.clip_svc.get_clip(&user, uuid).await.map_err(|e| {
+        tracing::error!("Failed to get clip {}: {}", uuid, e);
+        (super::error_status(&e), e.to_string())
+    })?;
+    let mut tags = state
+        .tag_svc
+        .tags_of_clips(std::slice::from_ref(&clip))
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list clip tags: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        })?;
+    let tags = tags.remove(&clip.id).unwrap_or_default();
+    Ok::<_, (StatusCode, String)>(Json(ClipResponse::from(clip).with_tags(tags)))
+}
+
+pub async fn list_reviewed_clip(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ListClipsQuery>,
+) -> impl IntoResponse {
+    let filter = ClipFilter::try_from(query).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let page = state
+        .clip_svc
+        .list_reviewed_clips(&filter)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list reviewed clips: {}", e);
+            (super::error_status(&e), e.to_string())
+        })?;
+    Ok::<_, (StatusCode, String)>(Json(ClipListResponse {
+        items: page.clips.into_iter().map(ClipResponse::from).collect(),
+        total: page.total,
+        page: page.page,
+        page_size: page.page_size,
+    }))
+}
+
 pub async fn update_clip(
     State(state): State<Arc<AppState>>,
     Extension(user): Extension<user::Model>,
@@ -200,38 +820,375 @@ pub async fn reviewed_clip(
     }
 
     match state.clip_svc.set_clip_reviewed(&user, uuid).await {
+        Ok(clip) => Ok(Json(ClipResponse::from(clip))),
+        Err(e) => {
+            tracing::error!("Failed to mark clip as reviewed: {}", e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+pub async fn review_clip(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Path(uuid): Path<uuid::Uuid>,
+    Json(req): Json<ReviewRequest>,
+) -> impl IntoResponse {
+    match state
+        .clip_svc
+        .review_clip(&user, uuid, req.decision, req.note)
+        .await
+    {
+        Ok(clip) => Ok(Json(ClipResponse::from(clip))),
+        Err(e) => {
+            tracing::error!("Failed to review clip {}: {}", uuid, e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+pub async fn resubmit_clip(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Path(uuid): Path<uuid::Uuid>,
+    Json(req): Json<ResubmitRequest>,
+) -> impl IntoResponse {
+    match state.clip_svc.resubmit_clip(&user, uuid, req.note).await {
+        Ok(clip) => Ok(Json(ClipResponse::from(clip))),
+        Err(e) => {
+            tracing::error!("Failed to resubmit clip {}: {}", uuid, e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+pub async fn list_clip_reviews(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Path(uuid): Path<uuid::Uuid>,
+) -> impl IntoResponse {
+    match state.clip_svc.list_clip_reviews(&user, uuid).await {
+        Ok(reviews) => Ok(Json(
+            reviews
+                .into_iter()
+                .map(ClipReviewResponse::from)
+                .collect::<Vec<_>>(),
+        )),
+        Err(e) => {
+            tracing::error!("Failed to list reviews of clip {}: {}", uuid, e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+pub async fn review_queue(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Query(query): Query<PageQuery>,
+) -> impl IntoResponse {
+    let page = state
+        .clip_svc
+        .review_queue(
+            &user,
+            query.page.unwrap_or(1),
+            query.page_size.unwrap_or(DEFAULT_PAGE_SIZE),
+        )
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list review queue: {}", e);
+            (super::error_status(&e), e.to_string())
+        })?;
+    Ok::<_, (StatusCode, String)>(Json(ClipListResponse {
+        items: page.clips.into_iter().map(ClipResponse::from).collect(),
+        total: page.total,
+        page: page.page,
+        page_size: page.page_size,
+    }))
+}
+
+pub async fn reprocess_clip(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Path(uuid): Path<uuid::Uuid>,
+) -> impl IntoResponse {
+    if uuid.is_nil() {
+        return Err((StatusCode::BAD_REQUEST, "Invalid UUID".into()));
+    }
+
+    match state.clip_svc.reprocess_clip(&user, uuid).await {
         Ok(Some(clip)) => Ok(Json(ClipResponse::from(clip))),
         Ok(None) => Err((StatusCode::NOT_FOUND, "Clip not found".into())),
         Err(e) => {
-            tracing::error!("Failed to mark clip as reviewed: {}", e);
+            tracing::error!("Failed to reprocess clip: {}", e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+pub async fn accept_suggested_trim(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Path(uuid): Path<uuid::Uuid>,
+) -> impl IntoResponse {
+    if uuid.is_nil() {
+        return Err((StatusCode::BAD_REQUEST, "Invalid UUID".into()));
+    }
+
+    match state.clip_svc.accept_suggested_trim(&user, uuid).await {
+        Ok(Some(clip)) => Ok(Json(ClipResponse::from(clip))),
+        Ok(None) => Err((StatusCode::NOT_FOUND, "Clip not found".into())),
+        Err(e) => {
+            tracing::error!("Failed to accept suggested trim: {}", e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct EditClipRequest {
+    #[serde(flatten)]
+    pub edit: ClipEdit,
+    // 为 true 时保留原切片，以编辑结果创建新切片
+    #[serde(default)]
+    pub new_clip: bool,
+}
+
+pub async fn edit_clip(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Path(uuid): Path<uuid::Uuid>,
+    Json(req): Json<EditClipRequest>,
+) -> impl IntoResponse {
+    if uuid.is_nil() {
+        return Err((StatusCode::BAD_REQUEST, "Invalid UUID".into()));
+    }
+
+    match state
+        .clip_svc
+        .edit_clip(&user, uuid, req.edit, req.new_clip)
+        .await
+    {
+        Ok(Some(clip)) if req.new_clip => Ok((StatusCode::CREATED, Json(ClipResponse::from(clip)))),
+        Ok(Some(clip)) => Ok((StatusCode::OK, Json(ClipResponse::from(clip)))),
+        Ok(None) => Err((StatusCode::NOT_FOUND, "Clip not found".into())),
+        Err(e) => {
+            tracing::error!("Failed to edit clip: {}", e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+/// 上传新文件替换切片，表单中只有 `file` 字段
+pub async fn replace_clip_file(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Path(uuid): Path<uuid::Uuid>,
+    headers: HeaderMap,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    if uuid.is_nil() {
+        return Err((StatusCode::BAD_REQUEST, "Invalid UUID".into()));
+    }
+    let incoming = incoming_length(&state, &user, &headers)?;
+    state
+        .clip_svc
+        .check_replace_quota_before_upload(&user, uuid, incoming)
+        .await
+        .map_err(|e| (super::error_status(&e), e.to_string()))?;
+
+    let mut uploaded = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+    {
+        if field.name() != Some("file") || uploaded.is_some() {
+            if let Some((_, path, _)) = uploaded {
+                tokio::fs::remove_file(path).await.ok();
+            }
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Unexpected field in multipart request".into(),
+            ));
+        }
+        let file_ext = file_ext(field.file_name().unwrap_or(""))?;
+        let body_with_io_error = field.map_err(io::Error::other);
+        let mut reader = pin!(StreamReader::new(body_with_io_error));
+        let saved = state
+            .clip_svc
+            .save_clip_to_tmp(&mut reader, &file_ext)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to save uploaded file: {}", e);
+                (StatusCode::BAD_REQUEST, e.to_string())
+            })?;
+        uploaded = Some(saved);
+    }
+    let Some((_, path, content_hash)) = uploaded else {
+        return Err((StatusCode::BAD_REQUEST, "Missing file".into()));
+    };
+
+    match state
+        .clip_svc
+        .replace_clip_file(&user, uuid, path, content_hash)
+        .await
+    {
+        Ok(Some(clip)) => Ok(Json(ClipResponse::from(clip))),
+        Ok(None) => Err((StatusCode::NOT_FOUND, "Clip not found".into())),
+        Err(e) => {
+            tracing::error!("Failed to replace clip file: {}", e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+pub async fn list_clip_versions(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Path(uuid): Path<uuid::Uuid>,
+) -> impl IntoResponse {
+    match state.clip_svc.list_clip_versions(&user, uuid).await {
+        Ok(Some(versions)) => Ok(Json(
+            versions
+                .into_iter()
+                .map(ClipVersionResponse::from)
+                .collect::<Vec<_>>(),
+        )),
+        Ok(None) => Err((StatusCode::NOT_FOUND, "Clip not found".into())),
+        Err(e) => {
+            tracing::error!("Failed to list clip versions: {}", e);
             Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
         }
     }
 }
 
+pub async fn rollback_clip(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Path((uuid, version_id)): Path<(uuid::Uuid, i64)>,
+) -> impl IntoResponse {
+    match state.clip_svc.rollback_clip(&user, uuid, version_id).await {
+        Ok(Some(clip)) => Ok(Json(ClipResponse::from(clip))),
+        Ok(None) => Err((StatusCode::NOT_FOUND, "Clip not found".into())),
+        Err(e) => {
+            tracing::error!("Failed to roll back clip: {}", e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteClipQuery {
+    // 永久删除回收站中的切片，否则移入回收站
+    #[serde(default)]
+    pub permanent: bool,
+}
+
 pub async fn delete_clip(
     State(state): State<Arc<AppState>>,
     Extension(user): Extension<user::Model>,
     Path(uuid): Path<uuid::Uuid>,
+    Query(query): Query<DeleteClipQuery>,
 ) -> impl IntoResponse {
     if uuid.is_nil() {
         return Err((StatusCode::BAD_REQUEST, "Invalid UUID".into()));
     }
 
-    match state.clip_svc.delete_clip(&user, uuid).await {
+    let result = if query.permanent {
+        state.clip_svc.purge_clip(&user, uuid).await
+    } else {
+        state.clip_svc.delete_clip(&user, uuid).await
+    };
+    match result {
         Ok(()) => Ok(StatusCode::NO_CONTENT),
         Err(e) => {
             tracing::error!("Failed to delete clip: {}", e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+pub async fn restore_clip(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Path(uuid): Path<uuid::Uuid>,
+) -> impl IntoResponse {
+    match state.clip_svc.restore_clip(&user, uuid).await {
+        Ok(clip) => Ok(Json(ClipResponse::from(clip))),
+        Err(e) => {
+            tracing::error!("Failed to restore clip {}: {}", uuid, e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+pub async fn list_duplicate_clips(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match state.clip_svc.list_duplicate_clips().await {
+        Ok(clips) => Ok(Json(
+            clips
+                .into_iter()
+                .map(ClipResponse::from)
+                .collect::<Vec<_>>(),
+        )),
+        Err(e) => {
+            tracing::error!("Failed to list duplicate clips: {}", e);
             Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
         }
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct MergeClipRequest {
+    pub into: uuid::Uuid,
+}
+
+pub async fn merge_clip(
+    State(state): State<Arc<AppState>>,
+    Path(uuid): Path<uuid::Uuid>,
+    Json(req): Json<MergeClipRequest>,
+) -> impl IntoResponse {
+    if uuid.is_nil() || req.into.is_nil() || uuid == req.into {
+        return Err((StatusCode::BAD_REQUEST, "Invalid UUID".into()));
+    }
+
+    match state.clip_svc.merge_clip(uuid, req.into).await {
+        Ok(Some(clip)) => Ok(Json(ClipResponse::from(clip))),
+        Ok(None) => Err((StatusCode::NOT_FOUND, "Clip not found".into())),
+        Err(e) => {
+            tracing::error!("Failed to merge clip: {}", e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
 #[derive(Deserialize)]
 pub struct RangeQuery {
     token: Option<String>,
 }
 
+fn verify_query_token(state: &AppState, token: Option<&str>) -> Result<(), (StatusCode, String)> {
+    let Some(token) = token else {
+        return Err((StatusCode::FORBIDDEN, "Forbidden".to_string()));
+    };
+    // Get JWT secret from config
+    let jwt_secret = state
+        .config
+        .jwt_secret
+        .as_deref()
+        .unwrap_or(DEFAULT_SECRET_KEY);
+    match jwt::verify_token(token, jwt_secret) {
+        Ok(claims) => {
+            tracing::trace!(
+                "Token validated for user: {} ({})",
+                claims.uname,
+                claims.mid
+            );
+            Ok(())
+        }
+        Err(_) => Err((StatusCode::FORBIDDEN, "Token expired".to_string())),
+    }
+}
+
 pub async fn preview_clip(
     State(state): State<Arc<AppState>>,
     Path(uuid): Path<uuid::Uuid>,
@@ -242,28 +1199,7 @@ pub async fn preview_clip(
         return Err((StatusCode::BAD_REQUEST, "Invalid UUID".to_string()));
     }
 
-    if let Some(token) = query.token {
-        // Get JWT secret from config
-        let jwt_secret = state
-            .config
-            .jwt_secret
-            .as_deref()
-            .unwrap_or(DEFAULT_SECRET_KEY);
-        match jwt::verify_token(&token, jwt_secret) {
-            Ok(claims) => {
-                tracing::trace!(
-                    "Token validated for user: {} ({})",
-                    claims.uname,
-                    claims.mid
-                );
-            }
-            Err(_) => {
-                return Err((StatusCode::FORBIDDEN, "Token expired".to_string()));
-            }
-        }
-    } else {
-        return Err((StatusCode::FORBIDDEN, "Forbidden".to_string()));
-    }
+    verify_query_token(&state, query.token.as_deref())?;
 
     let range_header = headers.get(header::RANGE);
 
@@ -319,3 +1255,81 @@ pub async fn preview_clip(
         }
     }
 }
+
+async fn serve_thumbnail(
+    state: &AppState,
+    uuid: uuid::Uuid,
+    token: Option<&str>,
+    thumbnail: Thumbnail,
+) -> Result<Response, (StatusCode, String)> {
+    if uuid.is_nil() {
+        return Err((StatusCode::BAD_REQUEST, "Invalid UUID".to_string()));
+    }
+    verify_query_token(state, token)?;
+
+    let (stream, file_size) = state
+        .clip_svc
+        .get_thumbnail(uuid, thumbnail)
+        .await
+        .map_err(|e| {
+            tracing::debug!("Failed to get thumbnail for UUID {}: {}", uuid, e);
+            (StatusCode::NOT_FOUND, "Thumbnail not found".to_string())
+        })?;
+
+    let body = if thumbnail == Thumbnail::SpriteVtt {
+        // 给 VTT 中的雪碧图地址补上访问令牌
+        let mut vtt = String::new();
+        pin!(stream)
+            .read_to_string(&mut vtt)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        let image = format!("{SPRITE_VTT_IMAGE}#");
+        let token = token.unwrap_or_default();
+        Body::from(vtt.replace(&image, &format!("{SPRITE_VTT_IMAGE}?token={token}#")))
+    } else {
+        Body::from_stream(ReaderStream::new(stream))
+    };
+
+    let mut response = Response::new(body);
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        thumbnail.content_type().parse().unwrap(),
+    );
+    if thumbnail != Thumbnail::SpriteVtt {
+        headers.insert(
+            header::CONTENT_LENGTH,
+            file_size.to_string().parse().unwrap(),
+        );
+    }
+    headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*".parse().unwrap());
+    headers.insert(
+        header::CACHE_CONTROL,
+        "private, max-age=3600".parse().unwrap(),
+    );
+    Ok(response)
+}
+
+pub async fn get_clip_poster(
+    State(state): State<Arc<AppState>>,
+    Path(uuid): Path<uuid::Uuid>,
+    Query(query): Query<RangeQuery>,
+) -> impl IntoResponse {
+    serve_thumbnail(&state, uuid, query.token.as_deref(), Thumbnail::Poster).await
+}
+
+pub async fn get_clip_sprite(
+    State(state): State<Arc<AppState>>,
+    Path(uuid): Path<uuid::Uuid>,
+    Query(query): Query<RangeQuery>,
+) -> impl IntoResponse {
+    serve_thumbnail(&state, uuid, query.token.as_deref(), Thumbnail::Sprite).await
+}
+
+pub async fn get_clip_sprite_vtt(
+    State(state): State<Arc<AppState>>,
+    Path(uuid): Path<uuid::Uuid>,
+    Query(query): Query<RangeQuery>,
+) -> impl IntoResponse {
+    serve_thumbnail(&state, uuid, query.token.as_deref(), Thumbnail::SpriteVtt).await
+}
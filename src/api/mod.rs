@@ -1,5 +1,23 @@
 pub mod admin;
+pub mod catalog;
 pub mod clip;
 pub mod live;
 pub mod playlist;
+pub mod tag;
 pub mod user;
+
+use axum::http::StatusCode;
+
+use crate::service::errors::Error;
+
+/// 根据服务层返回的错误类型选择响应状态码，未分类的错误视为服务器内部错误
+pub(crate) fn error_status(e: &anyhow::Error) -> StatusCode {
+    match e.downcast_ref::<Error>() {
+        Some(Error::NotFound(_)) => StatusCode::NOT_FOUND,
+        Some(Error::Unauthorized(_)) => StatusCode::UNAUTHORIZED,
+        Some(Error::BadRequest(_)) => StatusCode::BAD_REQUEST,
+        Some(Error::Forbidden(_)) => StatusCode::FORBIDDEN,
+        Some(Error::Conflict(_)) => StatusCode::CONFLICT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
@@ -7,9 +7,10 @@ use axum::response::IntoResponse;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::core::entity::playlist;
 use crate::core::entity::user::Model as UserModel;
+use crate::core::entity::{playlist, playlist_share};
 use crate::server::AppState;
+use crate::service::playlist::Access;
 
 #[derive(Deserialize)]
 pub struct PlaylistItemReq {
@@ -31,6 +32,28 @@ pub struct PlaylistRequest {
     pub is_active: Option<bool>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareRequest {
+    pub mid: i64,
+    pub role: playlist_share::Role,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareResponse {
+    pub user_id: i64,
+    pub mid: i64,
+    pub uname: String,
+    pub role: playlist_share::Role,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedPlaylistResponse {
+    #[serde(flatten)]
+    pub playlist: playlist::Model,
+    pub role: playlist_share::Role,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlaylistResponse {
     pub id: i32,
@@ -50,6 +73,18 @@ pub struct PlaylistItemResponse {
     pub position: i64,
     pub clip_title: String,
     pub clip_vup: String,
+    pub duration_ms: Option<i64>,
+    pub start_offset_ms: Option<i64>,
+    pub eta: Option<i64>,
+    // 直播时会跳过，不计入时间表
+    pub skipped: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaylistItemsResponse {
+    pub items: Vec<PlaylistItemResponse>,
+    pub total_duration_ms: Option<i64>,
+    pub now_playing: Option<i64>,
 }
 
 pub async fn list_playlists(
@@ -60,7 +95,25 @@ pub async fn list_playlists(
         Ok(playlists) => Ok(Json(playlists)),
         Err(e) => {
             tracing::error!("Failed to list playlists: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+pub async fn list_shared_playlists(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+) -> impl IntoResponse {
+    match state.playlist_svc.get_shared_playlists(user.id).await {
+        Ok(playlists) => Ok(Json(
+            playlists
+                .into_iter()
+                .map(|(playlist, role)| SharedPlaylistResponse { playlist, role })
+                .collect::<Vec<_>>(),
+        )),
+        Err(e) => {
+            tracing::error!("Failed to list shared playlists: {}", e);
+            Err((super::error_status(&e), e.to_string()))
         }
     }
 }
@@ -82,7 +135,7 @@ pub async fn create_playlist(
         Ok(playlist) => Ok(Json(playlist)),
         Err(e) => {
             tracing::error!("Failed to create playlist: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err((super::error_status(&e), e.to_string()))
         }
     }
 }
@@ -92,11 +145,15 @@ pub async fn get_playlist_by_id(
     Extension(user): Extension<UserModel>,
     Path(id): Path<i64>,
 ) -> impl IntoResponse {
-    match state.playlist_svc.get_playlist(user.id, id).await {
+    match state
+        .playlist_svc
+        .get_playlist_with_access(user.id, id, Access::Viewer)
+        .await
+    {
         Ok(playlist) => Ok(Json(playlist)),
         Err(e) => {
             tracing::error!("Failed to get playlist: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err((super::error_status(&e), e.to_string()))
         }
     }
 }
@@ -116,11 +173,11 @@ pub async fn update_playlist(
         ..Default::default()
     };
 
-    match state.playlist_svc.update_playlist(user.mid, req).await {
+    match state.playlist_svc.update_playlist(user.id, req).await {
         Ok(playlist) => Ok(Json(playlist)),
         Err(e) => {
             tracing::error!("Failed to update playlist: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err((super::error_status(&e), e.to_string()))
         }
     }
 }
@@ -134,7 +191,84 @@ pub async fn delete_playlist(
         Ok(_) => Ok(Json(())),
         Err(e) => {
             tracing::error!("Failed to delete playlist: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+pub async fn list_playlist_shares(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    match state.playlist_svc.list_shares(user.id, id).await {
+        Ok(shares) => Ok(Json(
+            shares
+                .into_iter()
+                .map(|(share, shared_user)| ShareResponse {
+                    user_id: shared_user.id,
+                    mid: shared_user.mid,
+                    uname: shared_user.uname,
+                    role: share.role,
+                    created_at: share.created_at.timestamp(),
+                })
+                .collect::<Vec<_>>(),
+        )),
+        Err(e) => {
+            tracing::error!("Failed to list playlist shares: {}", e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+pub async fn share_playlist(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path(id): Path<i64>,
+    Json(req): Json<ShareRequest>,
+) -> impl IntoResponse {
+    let target = match state.user_svc.get_user_by_mid(req.mid).await {
+        Ok(Some(target)) => target,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to get user by mid: {}", e);
+            return Err((super::error_status(&e), e.to_string()));
+        }
+    };
+
+    match state
+        .playlist_svc
+        .share_playlist(user.id, id, target.id, req.role)
+        .await
+    {
+        Ok(share) => Ok(Json(ShareResponse {
+            user_id: target.id,
+            mid: target.mid,
+            uname: target.uname,
+            role: share.role,
+            created_at: share.created_at.timestamp(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to share playlist: {}", e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+pub async fn unshare_playlist(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path((id, user_id)): Path<(i64, i64)>,
+) -> impl IntoResponse {
+    match state
+        .playlist_svc
+        .unshare_playlist(user.id, id, user_id)
+        .await
+    {
+        Ok(_) => Ok(Json(())),
+        Err(e) => {
+            tracing::error!("Failed to unshare playlist: {}", e);
+            Err((super::error_status(&e), e.to_string()))
         }
     }
 }
@@ -148,7 +282,7 @@ pub async fn set_active_playlist(
         Ok(_) => Ok(Json(())),
         Err(e) => {
             tracing::error!("Failed to set active playlist: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err((super::error_status(&e), e.to_string()))
         }
     }
 }
@@ -162,7 +296,7 @@ pub async fn unset_active_playlist(
         Ok(_) => Ok(Json(())),
         Err(e) => {
             tracing::error!("Failed to set active playlist: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err((super::error_status(&e), e.to_string()))
         }
     }
 }
@@ -175,7 +309,7 @@ pub async fn get_active_playlist(
         Ok(playlist) => Ok(Json(playlist)),
         Err(e) => {
             tracing::error!("Failed to get active playlist: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err((super::error_status(&e), e.to_string()))
         }
     }
 }
@@ -193,7 +327,7 @@ pub async fn add_clip_to_playlist(
         Ok(_) => Ok(Json(())),
         Err(e) => {
             tracing::error!("Failed to add clip to playlist: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err((super::error_status(&e), e.to_string()))
         }
     }
 }
@@ -211,7 +345,7 @@ pub async fn remove_clip_from_playlist(
         Ok(_) => Ok(Json(())),
         Err(e) => {
             tracing::error!("Failed to remove clip from playlist: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err((super::error_status(&e), e.to_string()))
         }
     }
 }
@@ -221,14 +355,16 @@ pub async fn get_playlist_items(
     Extension(user): Extension<UserModel>,
     Path(id): Path<i64>,
 ) -> impl IntoResponse {
+    let now_playing = state.live_svc.now_playing(id);
     match state
         .playlist_svc
-        .get_playlist_item_by_playlist_id(user.id, id)
+        .get_playlist_schedule(user.id, id, now_playing)
         .await
     {
-        Ok(items) => {
-            let mut resp = Vec::with_capacity(items.len());
-            for (item, clip) in &items {
+        Ok(schedule) => {
+            let mut resp = Vec::with_capacity(schedule.items.len());
+            for scheduled in &schedule.items {
+                let (item, clip) = (&scheduled.item, &scheduled.clip);
                 resp.push(PlaylistItemResponse {
                     id: item.id,
                     playlist_id: item.playlist_id,
@@ -236,13 +372,21 @@ pub async fn get_playlist_items(
                     position: item.position,
                     clip_title: clip.title.clone(),
                     clip_vup: clip.vup.clone(),
+                    duration_ms: clip.duration_ms,
+                    start_offset_ms: scheduled.start_offset_ms,
+                    eta: scheduled.eta.map(|eta| eta.timestamp()),
+                    skipped: scheduled.skipped,
                 });
             }
-            Ok(Json(resp))
+            Ok(Json(PlaylistItemsResponse {
+                items: resp,
+                total_duration_ms: schedule.total_duration_ms,
+                now_playing: schedule.now_playing,
+            }))
         }
         Err(e) => {
             tracing::error!("Failed to get playlist items: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err((super::error_status(&e), e.to_string()))
         }
     }
 }
@@ -260,7 +404,7 @@ pub async fn reorder_playlist_item(
         Ok(_) => Ok(Json(())),
         Err(e) => {
             tracing::error!("Failed to reorder playlist item: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err((super::error_status(&e), e.to_string()))
         }
     }
 }
@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use axum::Json;
+use axum::extract::{Extension, Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::api::clip::ClipResponse;
+use crate::core::entity::{tag, user};
+use crate::server::AppState;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagRequest {
+    pub name: String,
+    pub category: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagResponse {
+    pub id: i64,
+    pub name: String,
+    pub category: Option<String>,
+}
+
+impl From<tag::Model> for TagResponse {
+    fn from(tag: tag::Model) -> Self {
+        TagResponse {
+            id: tag.id,
+            name: tag.name,
+            category: tag.category,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClipTagsRequest {
+    pub tags: Vec<i64>,
+}
+
+pub async fn list_tags(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match state.tag_svc.list_tags().await {
+        Ok(tags) => Ok(Json(
+            tags.into_iter().map(TagResponse::from).collect::<Vec<_>>(),
+        )),
+        Err(e) => {
+            tracing::error!("Failed to list tags: {}", e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+        }
+    }
+}
+
+pub async fn create_tag(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<TagRequest>,
+) -> impl IntoResponse {
+    match state
+        .tag_svc
+        .create_tag(&req.name, req.category.as_deref())
+        .await
+    {
+        Ok(tag) => Ok((StatusCode::CREATED, Json(TagResponse::from(tag)))),
+        Err(e) => {
+            tracing::error!("Failed to create tag: {}", e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+pub async fn update_tag(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<TagRequest>,
+) -> impl IntoResponse {
+    match state
+        .tag_svc
+        .update_tag(id, &req.name, req.category.as_deref())
+        .await
+    {
+        Ok(tag) => Ok(Json(TagResponse::from(tag))),
+        Err(e) => {
+            tracing::error!("Failed to update tag: {}", e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+pub async fn delete_tag(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    match state.tag_svc.delete_tag(id).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            tracing::error!("Failed to delete tag: {}", e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
+
+pub async fn set_clip_tags(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<user::Model>,
+    Path(uuid): Path<Uuid>,
+    Json(req): Json<ClipTagsRequest>,
+) -> impl IntoResponse {
+    match state.tag_svc.set_clip_tags(&user, uuid, &req.tags).await {
+        Ok((clip, tags)) => Ok(Json(ClipResponse::from(clip).with_tags(tags))),
+        Err(e) => {
+            tracing::error!("Failed to set clip tags: {}", e);
+            Err((super::error_status(&e), e.to_string()))
+        }
+    }
+}
@@ -5,6 +5,8 @@ use serde::Deserialize;
 
 use crate::core::storage::StorageConfig;
 use crate::core::streamer::RtmpStreamerConfig;
+use crate::service::clip::{ProcessConfig, UploadConfig};
+use crate::service::inbox::InboxConfig;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
@@ -16,6 +18,12 @@ pub struct Config {
     pub tmp_dir: String,
     pub storage: StorageConfig,
     pub stream: RtmpStreamerConfig,
+    #[serde(default)]
+    pub process: ProcessConfig,
+    #[serde(default)]
+    pub upload: UploadConfig,
+    #[serde(default)]
+    pub inbox: InboxConfig,
 }
 
 impl Config {
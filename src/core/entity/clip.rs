@@ -1,6 +1,6 @@
 use sea_orm::prelude::*;
 use serde::{Deserialize, Serialize};
-#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize, DeriveEntityModel)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize, DeriveEntityModel)]
 #[sea_orm(table_name = "clip")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -10,9 +10,40 @@ pub struct Model {
     pub title: String,
     pub vup: String,
     pub song: String,
+    pub vup_id: Option<i64>,
+    pub song_id: Option<i64>,
     pub upload_time: chrono::DateTime<chrono::Utc>,
     pub status: Status,
     pub user_id: i64,
+    pub duration_ms: Option<i64>,
+    pub process_stage: Option<ProcessStage>,
+    pub process_progress: Option<i32>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub frame_rate: Option<f64>,
+    pub video_codec: Option<String>,
+    pub audio_codec: Option<String>,
+    pub bit_rate: Option<i64>,
+    pub file_size: Option<i64>,
+    pub loudness: Option<f64>,
+    pub measured_i: Option<f64>,
+    pub measured_tp: Option<f64>,
+    pub measured_lra: Option<f64>,
+    pub measured_thresh: Option<f64>,
+    pub suggested_in_ms: Option<i64>,
+    pub suggested_out_ms: Option<i64>,
+    pub content_hash: Option<String>,
+    #[serde(skip)]
+    pub fingerprint: Option<Vec<u8>>,
+    pub duplicate_of: Option<Uuid>,
+    // 最近一次审核的意见，退回时为退回原因
+    pub review_note: Option<String>,
+    // 移入回收站的时间，为空表示未删除
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+    // 处理时按推流参数转码的参数摘要，未转码时为空
+    pub stream_profile: Option<String>,
+    // 标准化之后累计的音量调整，重新处理时需要再次应用
+    pub volume_db: f64,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, EnumIter, DeriveActiveEnum)]
@@ -28,6 +59,22 @@ pub enum Status {
     Reviewing,
     #[sea_orm(string_value = "reviewed")]
     Reviewed,
+    #[sea_orm(string_value = "rejected")]
+    Rejected,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(32))")]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessStage {
+    #[sea_orm(string_value = "detecting")]
+    Detecting,
+    #[sea_orm(string_value = "analyzing")]
+    Analyzing,
+    #[sea_orm(string_value = "normalizing")]
+    Normalizing,
+    #[sea_orm(string_value = "uploading")]
+    Uploading,
 }
 
 impl Default for Status {
@@ -47,6 +94,29 @@ pub enum Relation {
 
     #[sea_orm(has_many = "super::playlist_item::Entity")]
     PlaylistItem,
+
+    #[sea_orm(has_many = "super::clip_version::Entity")]
+    ClipVersion,
+
+    #[sea_orm(has_many = "super::clip_review::Entity")]
+    ClipReview,
+
+    #[sea_orm(has_many = "super::trashed_playlist_item::Entity")]
+    TrashedPlaylistItem,
+
+    #[sea_orm(
+        belongs_to = "super::vup::Entity",
+        from = "Column::VupId",
+        to = "super::vup::Column::Id"
+    )]
+    Vup,
+
+    #[sea_orm(
+        belongs_to = "super::song::Entity",
+        from = "Column::SongId",
+        to = "super::song::Column::Id"
+    )]
+    Song,
 }
 
 impl Related<super::user::Entity> for Entity {
@@ -61,4 +131,34 @@ impl Related<super::playlist_item::Entity> for Entity {
     }
 }
 
+impl Related<super::clip_version::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ClipVersion.def()
+    }
+}
+
+impl Related<super::clip_review::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ClipReview.def()
+    }
+}
+
+impl Related<super::trashed_playlist_item::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TrashedPlaylistItem.def()
+    }
+}
+
+impl Related<super::vup::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Vup.def()
+    }
+}
+
+impl Related<super::song::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Song.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,65 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 切片的审核记录，包括上传者重新提交
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "clip_review")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub clip_id: i64,
+    // 做出这条记录的用户
+    pub user_id: i64,
+    pub decision: Decision,
+    pub note: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(32))")]
+#[serde(rename_all = "snake_case")]
+pub enum Decision {
+    #[sea_orm(string_value = "approved")]
+    Approved,
+    #[sea_orm(string_value = "rejected")]
+    Rejected,
+    // 只留下意见，不改变状态
+    #[sea_orm(string_value = "comment")]
+    Comment,
+    #[sea_orm(string_value = "resubmitted")]
+    Resubmitted,
+    // 可信用户的切片通过自动审核规则
+    #[sea_orm(string_value = "auto_approved")]
+    AutoApproved,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::clip::Entity",
+        from = "Column::ClipId",
+        to = "super::clip::Column::Id"
+    )]
+    Clip,
+
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::clip::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Clip.def()
+    }
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
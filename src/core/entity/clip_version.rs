@@ -0,0 +1,46 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 切片被替换前的文件，存储为 `{uuid}_v{id}.mp4`
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "clip_version")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub clip_id: i64,
+    // 替换掉这个版本的用户
+    pub user_id: i64,
+    pub duration_ms: Option<i64>,
+    pub file_size: Option<i64>,
+    pub content_hash: Option<String>,
+    pub measured_i: Option<f64>,
+    pub measured_tp: Option<f64>,
+    pub measured_lra: Option<f64>,
+    pub measured_thresh: Option<f64>,
+    pub volume_db: f64,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+impl Model {
+    pub fn file_name(&self, uuid: Uuid) -> String {
+        format!("{uuid}_v{}.mp4", self.id)
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::clip::Entity",
+        from = "Column::ClipId",
+        to = "super::clip::Column::Id"
+    )]
+    Clip,
+}
+
+impl Related<super::clip::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Clip.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
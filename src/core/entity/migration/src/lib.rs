@@ -4,6 +4,22 @@ mod m20250601_000001_create_user;
 mod m20250601_000002_create_table;
 mod m20250601_000003_create_playlists;
 mod m20250627_000001_add_user_permissions;
+mod m20250701_000001_add_clip_duration;
+mod m20250702_000001_create_playlist_share;
+mod m20250703_000001_add_clip_progress;
+mod m20250704_000001_add_clip_media_info;
+mod m20250705_000001_add_clip_loudness_measurements;
+mod m20250706_000001_add_clip_suggested_trim;
+mod m20250707_000001_add_clip_dedup;
+mod m20250708_000001_add_user_quota;
+mod m20250709_000001_create_clip_version;
+mod m20250710_000001_create_tag;
+mod m20250711_000001_create_catalog;
+mod m20250712_000001_create_clip_review;
+mod m20250713_000001_add_user_trusted;
+mod m20250714_000001_add_clip_trash;
+mod m20250715_000001_add_clip_stream_profile;
+mod m20250716_000001_add_clip_volume;
 
 pub struct Migrator;
 
@@ -15,6 +31,22 @@ impl MigratorTrait for Migrator {
             Box::new(m20250601_000002_create_table::Migration),
             Box::new(m20250601_000003_create_playlists::Migration),
             Box::new(m20250627_000001_add_user_permissions::Migration),
+            Box::new(m20250701_000001_add_clip_duration::Migration),
+            Box::new(m20250702_000001_create_playlist_share::Migration),
+            Box::new(m20250703_000001_add_clip_progress::Migration),
+            Box::new(m20250704_000001_add_clip_media_info::Migration),
+            Box::new(m20250705_000001_add_clip_loudness_measurements::Migration),
+            Box::new(m20250706_000001_add_clip_suggested_trim::Migration),
+            Box::new(m20250707_000001_add_clip_dedup::Migration),
+            Box::new(m20250708_000001_add_user_quota::Migration),
+            Box::new(m20250709_000001_create_clip_version::Migration),
+            Box::new(m20250710_000001_create_tag::Migration),
+            Box::new(m20250711_000001_create_catalog::Migration),
+            Box::new(m20250712_000001_create_clip_review::Migration),
+            Box::new(m20250713_000001_add_user_trusted::Migration),
+            Box::new(m20250714_000001_add_clip_trash::Migration),
+            Box::new(m20250715_000001_add_clip_stream_profile::Migration),
+            Box::new(m20250716_000001_add_clip_volume::Migration),
         ]
     }
 }
@@ -0,0 +1,86 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PlaylistShare::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PlaylistShare::Id))
+                    .col(big_integer(PlaylistShare::PlaylistId).not_null())
+                    .col(big_integer(PlaylistShare::UserId).not_null())
+                    .col(string_len(PlaylistShare::Role, 16).not_null())
+                    .col(
+                        timestamp_with_time_zone(PlaylistShare::CreatedAt)
+                            .not_null()
+                            .default(Expr::cust("CURRENT_TIMESTAMP")),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_playlist_share_playlist")
+                            .from(PlaylistShare::Table, PlaylistShare::PlaylistId)
+                            .to(Playlist::Table, Playlist::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .to_owned(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(PlaylistShare::Table)
+                    .name("idx_playlist_share_playlist_user")
+                    .unique()
+                    .col(PlaylistShare::PlaylistId)
+                    .col(PlaylistShare::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(PlaylistShare::Table)
+                    .name("idx_playlist_share_user")
+                    .col(PlaylistShare::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(PlaylistShare::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Playlist {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum PlaylistShare {
+    Table,
+    Id,
+    PlaylistId,
+    UserId,
+    Role,
+    CreatedAt,
+}
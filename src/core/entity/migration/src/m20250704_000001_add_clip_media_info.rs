@@ -0,0 +1,81 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite 每次只能添加一列
+        let columns = [
+            ColumnDef::new(Clip::Width).integer().null().to_owned(),
+            ColumnDef::new(Clip::Height).integer().null().to_owned(),
+            ColumnDef::new(Clip::FrameRate).double().null().to_owned(),
+            ColumnDef::new(Clip::VideoCodec)
+                .string_len(32)
+                .null()
+                .to_owned(),
+            ColumnDef::new(Clip::AudioCodec)
+                .string_len(32)
+                .null()
+                .to_owned(),
+            ColumnDef::new(Clip::BitRate)
+                .big_integer()
+                .null()
+                .to_owned(),
+            ColumnDef::new(Clip::FileSize)
+                .big_integer()
+                .null()
+                .to_owned(),
+            ColumnDef::new(Clip::Loudness).double().null().to_owned(),
+        ];
+        for column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Clip::Table)
+                        .add_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let columns = [
+            Clip::Width,
+            Clip::Height,
+            Clip::FrameRate,
+            Clip::VideoCodec,
+            Clip::AudioCodec,
+            Clip::BitRate,
+            Clip::FileSize,
+            Clip::Loudness,
+        ];
+        for column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Clip::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Clip {
+    Table,
+    Width,
+    Height,
+    FrameRate,
+    VideoCodec,
+    AudioCodec,
+    BitRate,
+    FileSize,
+    Loudness,
+}
@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let columns = [
+            Clip::MeasuredI,
+            Clip::MeasuredTp,
+            Clip::MeasuredLra,
+            Clip::MeasuredThresh,
+        ];
+        for column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Clip::Table)
+                        .add_column(ColumnDef::new(column).double().null())
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let columns = [
+            Clip::MeasuredI,
+            Clip::MeasuredTp,
+            Clip::MeasuredLra,
+            Clip::MeasuredThresh,
+        ];
+        for column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Clip::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Clip {
+    Table,
+    MeasuredI,
+    MeasuredTp,
+    MeasuredLra,
+    MeasuredThresh,
+}
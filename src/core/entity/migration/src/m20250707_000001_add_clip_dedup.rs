@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let columns = [
+            ColumnDef::new(Clip::ContentHash)
+                .string_len(64)
+                .null()
+                .to_owned(),
+            ColumnDef::new(Clip::Fingerprint).blob().null().to_owned(),
+            ColumnDef::new(Clip::DuplicateOf).uuid().null().to_owned(),
+        ];
+        for column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Clip::Table)
+                        .add_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(Clip::Table)
+                    .name("idx_clip_content_hash")
+                    .col(Clip::ContentHash)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_clip_content_hash")
+                    .table(Clip::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        let columns = [Clip::ContentHash, Clip::Fingerprint, Clip::DuplicateOf];
+        for column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Clip::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Clip {
+    Table,
+    ContentHash,
+    Fingerprint,
+    DuplicateOf,
+}
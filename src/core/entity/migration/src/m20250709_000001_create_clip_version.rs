@@ -0,0 +1,84 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ClipVersion::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ClipVersion::Id))
+                    .col(big_integer(ClipVersion::ClipId).not_null())
+                    .col(big_integer(ClipVersion::UserId).not_null())
+                    .col(big_integer_null(ClipVersion::DurationMs))
+                    .col(big_integer_null(ClipVersion::FileSize))
+                    .col(string_len_null(ClipVersion::ContentHash, 64))
+                    .col(double_null(ClipVersion::MeasuredI))
+                    .col(double_null(ClipVersion::MeasuredTp))
+                    .col(double_null(ClipVersion::MeasuredLra))
+                    .col(double_null(ClipVersion::MeasuredThresh))
+                    .col(
+                        timestamp_with_time_zone(ClipVersion::CreatedAt)
+                            .not_null()
+                            .default(Expr::cust("CURRENT_TIMESTAMP")),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_clip_version_clip")
+                            .from(ClipVersion::Table, ClipVersion::ClipId)
+                            .to(Clip::Table, Clip::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .to_owned(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(ClipVersion::Table)
+                    .name("idx_clip_version_clip")
+                    .col(ClipVersion::ClipId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ClipVersion::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Clip {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum ClipVersion {
+    Table,
+    Id,
+    ClipId,
+    UserId,
+    DurationMs,
+    FileSize,
+    ContentHash,
+    MeasuredI,
+    MeasuredTp,
+    MeasuredLra,
+    MeasuredThresh,
+    CreatedAt,
+}
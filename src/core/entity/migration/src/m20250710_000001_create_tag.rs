@@ -0,0 +1,106 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Tag::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Tag::Id))
+                    .col(string_len(Tag::Name, 64).not_null())
+                    .col(string_len_null(Tag::Category, 32))
+                    .col(
+                        timestamp_with_time_zone(Tag::CreatedAt)
+                            .not_null()
+                            .default(Expr::cust("CURRENT_TIMESTAMP")),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(Tag::Table)
+                    .name("idx_tag_name")
+                    .unique()
+                    .col(Tag::Name)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ClipTag::Table)
+                    .if_not_exists()
+                    .col(big_integer(ClipTag::ClipId).not_null())
+                    .col(big_integer(ClipTag::TagId).not_null())
+                    .primary_key(Index::create().col(ClipTag::ClipId).col(ClipTag::TagId))
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_clip_tag_clip")
+                            .from(ClipTag::Table, ClipTag::ClipId)
+                            .to(Clip::Table, Clip::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .to_owned(),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_clip_tag_tag")
+                            .from(ClipTag::Table, ClipTag::TagId)
+                            .to(Tag::Table, Tag::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .to_owned(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(ClipTag::Table)
+                    .name("idx_clip_tag_tag")
+                    .col(ClipTag::TagId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ClipTag::Table).if_exists().to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Tag::Table).if_exists().to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Clip {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Tag {
+    Table,
+    Id,
+    Name,
+    Category,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ClipTag {
+    Table,
+    ClipId,
+    TagId,
+}
@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+
+use sea_orm_migration::sea_orm::ConnectionTrait;
+use sea_orm_migration::{prelude::*, schema::*};
+
+// 条目名称、别名和规范化键的长度上限
+const MAX_NAME_LEN: usize = 128;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Vup::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Vup::Id))
+                    .col(string_len(Vup::Name, MAX_NAME_LEN as u32).not_null())
+                    .col(string_null(Vup::AvatarUrl))
+                    .col(string_null(Vup::Link))
+                    .col(
+                        timestamp_with_time_zone(Vup::CreatedAt)
+                            .not_null()
+                            .default(Expr::cust("CURRENT_TIMESTAMP")),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(VupAlias::Table)
+                    .if_not_exists()
+                    .col(pk_auto(VupAlias::Id))
+                    .col(big_integer(VupAlias::VupId).not_null())
+                    .col(string_len(VupAlias::Alias, MAX_NAME_LEN as u32).not_null())
+                    .col(string_len(VupAlias::Key, MAX_NAME_LEN as u32).not_null())
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_vup_alias_vup")
+                            .from(VupAlias::Table, VupAlias::VupId)
+                            .to(Vup::Table, Vup::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .to_owned(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Song::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Song::Id))
+                    .col(string_len(Song::Name, MAX_NAME_LEN as u32).not_null())
+                    .col(string_null(Song::CoverUrl))
+                    .col(string_null(Song::Link))
+                    .col(
+                        timestamp_with_time_zone(Song::CreatedAt)
+                            .not_null()
+                            .default(Expr::cust("CURRENT_TIMESTAMP")),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(SongAlias::Table)
+                    .if_not_exists()
+                    .col(pk_auto(SongAlias::Id))
+                    .col(big_integer(SongAlias::SongId).not_null())
+                    .col(string_len(SongAlias::Alias, MAX_NAME_LEN as u32).not_null())
+                    .col(string_len(SongAlias::Key, MAX_NAME_LEN as u32).not_null())
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_song_alias_song")
+                            .from(SongAlias::Table, SongAlias::SongId)
+                            .to(Song::Table, Song::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .to_owned(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        let indexes = [
+            Index::create()
+                .table(VupAlias::Table)
+                .name("idx_vup_alias_key")
+                .unique()
+                .col(VupAlias::Key)
+                .to_owned(),
+            Index::create()
+                .table(VupAlias::Table)
+                .name("idx_vup_alias_vup")
+                .col(VupAlias::VupId)
+                .to_owned(),
+            Index::create()
+                .table(SongAlias::Table)
+                .name("idx_song_alias_key")
+                .unique()
+                .col(SongAlias::Key)
+                .to_owned(),
+            Index::create()
+                .table(SongAlias::Table)
+                .name("idx_song_alias_song")
+                .col(SongAlias::SongId)
+                .to_owned(),
+        ];
+        for index in indexes {
+            manager.create_index(index).await?;
+        }
+
+        for column in [Clip::VupId, Clip::SongId] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Clip::Table)
+                        .add_column(ColumnDef::new(column).big_integer().null())
+                        .to_owned(),
+                )
+                .await?;
+        }
+        for (column, name) in [
+            (Clip::VupId, "idx_clip_vup"),
+            (Clip::SongId, "idx_clip_song"),
+        ] {
+            manager
+                .create_index(
+                    Index::create()
+                        .table(Clip::Table)
+                        .name(name)
+                        .col(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        cluster(manager, "vup").await?;
+        cluster(manager, "song").await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for name in ["idx_clip_vup", "idx_clip_song"] {
+            manager
+                .drop_index(Index::drop().name(name).table(Clip::Table).to_owned())
+                .await?;
+        }
+        for column in [Clip::VupId, Clip::SongId] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Clip::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        manager
+            .drop_table(Table::drop().table(VupAlias::Table).if_exists().to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(SongAlias::Table).if_exists().to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Vup::Table).if_exists().to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Song::Table).if_exists().to_owned())
+            .await
+    }
+}
+
+/// 把切片上已有的 `kind`（vup 或 song）名称按规范化后的写法归并为目录条目，
+/// 每组取出现次数最多的写法作为正式名称
+async fn cluster(manager: &SchemaManager<'_>, kind: &str) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    let backend = manager.get_database_backend();
+    let name_col = Alias::new(kind);
+    let id_col = Alias::new(format!("{kind}_id"));
+    let table = Alias::new(kind);
+    let alias_table = Alias::new(format!("{kind}_alias"));
+
+    let select = Query::select()
+        .column(name_col.clone())
+        .expr_as(Func::count(Expr::col(Clip::Id)), Alias::new("count"))
+        .from(Clip::Table)
+        .and_where(Expr::col(name_col.clone()).ne(""))
+        .group_by_col(name_col.clone())
+        .to_owned();
+    let mut groups: HashMap<String, Vec<(String, i64)>> = HashMap::new();
+    for row in db.query_all(backend.build(&select)).await? {
+        let name: String = row.try_get("", kind)?;
+        let count: i64 = row.try_get("", "count")?;
+        let key = truncate(&normalize_key(&name));
+        if !key.is_empty() {
+            groups.entry(key).or_default().push((name, count));
+        }
+    }
+
+    for (key, mut spellings) in groups {
+        spellings.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        // 切片上的名称可能比条目名称长，超出部分截断
+        let canonical = truncate(spellings[0].0.trim());
+
+        let insert = Query::insert()
+            .into_table(table.clone())
+            .columns([Alias::new("name")])
+            .values_panic([canonical.clone().into()])
+            .to_owned();
+        db.execute(backend.build(&insert)).await?;
+        // 不同数据库取回自增 id 的方式不一致，按名称查回
+        let select = Query::select()
+            .column(Alias::new("id"))
+            .from(table.clone())
+            .and_where(Expr::col(Alias::new("name")).eq(canonical.clone()))
+            .order_by(Alias::new("id"), Order::Desc)
+            .limit(1)
+            .to_owned();
+        let row = db
+            .query_one(backend.build(&select))
+            .await?
+            .ok_or_else(|| DbErr::Custom(format!("Failed to create {kind} {canonical}")))?;
+        let id: i64 = row.try_get("", "id")?;
+
+        let insert = Query::insert()
+            .into_table(alias_table.clone())
+            .columns([id_col.clone(), Alias::new("alias"), Alias::new("key")])
+            .values_panic([id.into(), canonical.clone().into(), key.into()])
+            .to_owned();
+        db.execute(backend.build(&insert)).await?;
+
+        let names: Vec<String> = spellings.into_iter().map(|(name, _)| name).collect();
+        let update = Query::update()
+            .table(Clip::Table)
+            .value(id_col.clone(), id)
+            .value(name_col.clone(), canonical)
+            .and_where(Expr::col(name_col.clone()).is_in(names))
+            .to_owned();
+        db.execute(backend.build(&update)).await?;
+    }
+    Ok(())
+}
+
+fn truncate(s: &str) -> String {
+    s.chars().take(MAX_NAME_LEN).collect()
+}
+
+/// 与 `service::catalog::normalize_key` 保持一致
+fn normalize_key(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            // 全角字符转半角
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            _ => c,
+        })
+        .filter(|c| !c.is_whitespace() && !"·・_-.,'\"".contains(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[derive(DeriveIden)]
+enum Clip {
+    Table,
+    Id,
+    VupId,
+    SongId,
+}
+
+#[derive(DeriveIden)]
+enum Vup {
+    Table,
+    Id,
+    Name,
+    AvatarUrl,
+    Link,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum VupAlias {
+    Table,
+    Id,
+    VupId,
+    Alias,
+    Key,
+}
+
+#[derive(DeriveIden)]
+enum Song {
+    Table,
+    Id,
+    Name,
+    CoverUrl,
+    Link,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum SongAlias {
+    Table,
+    Id,
+    SongId,
+    Alias,
+    Key,
+}
@@ -0,0 +1,92 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ClipReview::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ClipReview::Id))
+                    .col(big_integer(ClipReview::ClipId).not_null())
+                    .col(big_integer(ClipReview::UserId).not_null())
+                    .col(string_len(ClipReview::Decision, 32).not_null())
+                    .col(text_null(ClipReview::Note))
+                    .col(
+                        timestamp_with_time_zone(ClipReview::CreatedAt)
+                            .not_null()
+                            .default(Expr::cust("CURRENT_TIMESTAMP")),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_clip_review_clip")
+                            .from(ClipReview::Table, ClipReview::ClipId)
+                            .to(Clip::Table, Clip::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .to_owned(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(ClipReview::Table)
+                    .name("idx_clip_review_clip")
+                    .col(ClipReview::ClipId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Clip::Table)
+                    .add_column(text_null(Clip::ReviewNote))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Clip::Table)
+                    .drop_column(Clip::ReviewNote)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ClipReview::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Clip {
+    Table,
+    Id,
+    ReviewNote,
+}
+
+#[derive(DeriveIden)]
+enum ClipReview {
+    Table,
+    Id,
+    ClipId,
+    UserId,
+    Decision,
+    Note,
+    CreatedAt,
+}
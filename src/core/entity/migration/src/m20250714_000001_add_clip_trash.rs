@@ -0,0 +1,117 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Clip::Table)
+                    .add_column(timestamp_with_time_zone_null(Clip::DeletedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(Clip::Table)
+                    .name("idx_clip_deleted_at")
+                    .col(Clip::DeletedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // 移入回收站时记录切片在各播放列表中的位置，恢复时放回原处
+        manager
+            .create_table(
+                Table::create()
+                    .table(TrashedPlaylistItem::Table)
+                    .if_not_exists()
+                    .col(pk_auto(TrashedPlaylistItem::Id))
+                    .col(big_integer(TrashedPlaylistItem::ClipId).not_null())
+                    .col(big_integer(TrashedPlaylistItem::PlaylistId).not_null())
+                    .col(big_integer(TrashedPlaylistItem::Position).not_null())
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_trashed_playlist_item_clip")
+                            .from(TrashedPlaylistItem::Table, TrashedPlaylistItem::ClipId)
+                            .to(Clip::Table, Clip::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .to_owned(),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_trashed_playlist_item_playlist")
+                            .from(TrashedPlaylistItem::Table, TrashedPlaylistItem::PlaylistId)
+                            .to(Playlist::Table, Playlist::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .to_owned(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(TrashedPlaylistItem::Table)
+                    .name("idx_trashed_playlist_item_clip")
+                    .col(TrashedPlaylistItem::ClipId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(TrashedPlaylistItem::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .table(Clip::Table)
+                    .name("idx_clip_deleted_at")
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Clip::Table)
+                    .drop_column(Clip::DeletedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Clip {
+    Table,
+    Id,
+    DeletedAt,
+}
+
+#[derive(DeriveIden)]
+enum Playlist {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum TrashedPlaylistItem {
+    Table,
+    Id,
+    ClipId,
+    PlaylistId,
+    Position,
+}
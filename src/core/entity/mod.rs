@@ -1,4 +1,14 @@
 pub mod clip;
+pub mod clip_review;
+pub mod clip_tag;
+pub mod clip_version;
 pub mod playlist;
 pub mod playlist_item;
+pub mod playlist_share;
+pub mod song;
+pub mod song_alias;
+pub mod tag;
+pub mod trashed_playlist_item;
 pub mod user;
+pub mod vup;
+pub mod vup_alias;
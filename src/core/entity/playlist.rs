@@ -19,6 +19,9 @@ pub enum Relation {
     #[sea_orm(has_many = "super::playlist_item::Entity")]
     PlaylistItem,
 
+    #[sea_orm(has_many = "super::playlist_share::Entity")]
+    PlaylistShare,
+
     #[sea_orm(
         belongs_to = "super::user::Entity",
         from = "Column::UserId",
@@ -33,6 +36,12 @@ impl Related<super::playlist_item::Entity> for Entity {
     }
 }
 
+impl Related<super::playlist_share::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PlaylistShare.def()
+    }
+}
+
 impl Related<super::user::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::User.def()
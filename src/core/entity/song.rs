@@ -0,0 +1,37 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "song")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    // 正式名称，切片上的 song 字段与之同步
+    pub name: String,
+    pub cover_url: Option<String>,
+    pub link: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::song_alias::Entity")]
+    SongAlias,
+
+    #[sea_orm(has_many = "super::clip::Entity")]
+    Clip,
+}
+
+impl Related<super::song_alias::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SongAlias.def()
+    }
+}
+
+impl Related<super::clip::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Clip.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
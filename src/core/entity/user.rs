@@ -1,7 +1,7 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "user")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -16,8 +16,18 @@ pub struct Model {
     pub can_stream: bool,
     #[serde(default)]
     pub is_disabled: bool,
+    // 可信用户的切片满足自动审核规则时直接通过
+    #[serde(default)]
+    pub is_trusted: bool,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    // 用户单独的配额，为空时使用角色配额，负数表示不限
+    #[serde(default)]
+    pub quota_bytes: Option<i64>,
+    #[serde(default)]
+    pub quota_clips: Option<i64>,
+    #[serde(default)]
+    pub quota_daily_uploads: Option<i64>,
 }
 
 fn default_can_stream() -> bool {
@@ -0,0 +1,37 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "vup")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    // 正式名称，切片上的 vup 字段与之同步
+    pub name: String,
+    pub avatar_url: Option<String>,
+    pub link: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::vup_alias::Entity")]
+    VupAlias,
+
+    #[sea_orm(has_many = "super::clip::Entity")]
+    Clip,
+}
+
+impl Related<super::vup_alias::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::VupAlias.def()
+    }
+}
+
+impl Related<super::clip::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Clip.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
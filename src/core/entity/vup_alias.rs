@@ -0,0 +1,32 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "vup_alias")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub vup_id: i64,
+    pub alias: String,
+    // 规范化后的别名，用于匹配不同写法
+    #[sea_orm(unique)]
+    pub key: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::vup::Entity",
+        from = "Column::VupId",
+        to = "super::vup::Column::Id"
+    )]
+    Vup,
+}
+
+impl Related<super::vup::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Vup.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,682 @@
+use std::ffi::OsStr;
+use std::path::Path;
+use std::process::{ExitStatus, Stdio};
+
+use anyhow::anyhow;
+use regex::Regex;
+use serde::Deserialize;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::process::Command;
+
+use crate::core::streamer::RtmpStreamerConfig;
+
+#[derive(Debug, Clone, Default)]
+pub struct MediaInfo {
+    pub duration_ms: Option<i64>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub frame_rate: Option<f64>,
+    pub video_codec: Option<String>,
+    pub audio_codec: Option<String>,
+    pub bit_rate: Option<i64>,
+    pub file_size: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct ProbeOutput {
+    format: ProbeFormat,
+    #[serde(default)]
+    streams: Vec<ProbeStream>,
+}
+
+#[derive(Deserialize)]
+struct ProbeFormat {
+    duration: Option<String>,
+    size: Option<String>,
+    bit_rate: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ProbeStream {
+    codec_type: Option<String>,
+    codec_name: Option<String>,
+    width: Option<i32>,
+    height: Option<i32>,
+    avg_frame_rate: Option<String>,
+}
+
+pub async fn probe(path: &Path) -> anyhow::Result<MediaInfo> {
+    let output = Command::new("ffprobe")
+        .arg("-v")
+        .arg("error")
+        .arg("-print_format")
+        .arg("json")
+        .arg("-show_format")
+        .arg("-show_streams")
+        .arg(path)
+        .stdin(Stdio::null())
+        .stderr(Stdio::piped())
+        .stdout(Stdio::piped())
+        .output()
+        .await
+        .map_err(|e| anyhow!("Failed to run ffprobe: {}", e))?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "FFprobe failed with status: {}, file: {}, output: {}",
+            output.status,
+            path.display(),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let probe: ProbeOutput = serde_json::from_slice(&output.stdout)
+        .map_err(|e| anyhow!("Failed to parse ffprobe output: {}", e))?;
+
+    let stream = |kind: &str| {
+        probe
+            .streams
+            .iter()
+            .find(|s| s.codec_type.as_deref() == Some(kind))
+    };
+    let video = stream("video");
+    let audio = stream("audio");
+
+    Ok(MediaInfo {
+        duration_ms: probe
+            .format
+            .duration
+            .as_deref()
+            .and_then(|d| d.parse::<f64>().ok())
+            .map(|d| (d * 1000.0).round() as i64),
+        width: video.and_then(|v| v.width),
+        height: video.and_then(|v| v.height),
+        frame_rate: video
+            .and_then(|v| v.avg_frame_rate.as_deref())
+            .and_then(parse_frame_rate),
+        video_codec: video.and_then(|v| v.codec_name.clone()),
+        audio_codec: audio.and_then(|a| a.codec_name.clone()),
+        bit_rate: probe.format.bit_rate.and_then(|b| b.parse().ok()),
+        file_size: probe.format.size.and_then(|s| s.parse().ok()),
+    })
+}
+
+/// 解码各个流的前 `secs` 秒，遇到解码错误即失败
+pub async fn decode_check(path: &Path, secs: u32) -> anyhow::Result<()> {
+    let output = Command::new("ffmpeg")
+        .arg("-v")
+        .arg("error")
+        .arg("-xerror")
+        .arg("-t")
+        .arg(secs.to_string())
+        .arg("-i")
+        .arg(path)
+        .arg("-f")
+        .arg("null")
+        .arg("-")
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| anyhow!("Failed to run ffmpeg: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(
+            "{}",
+            stderr.lines().last().unwrap_or("decoding failed").trim()
+        );
+    }
+    Ok(())
+}
+
+/// 解析 ffprobe 输出的分数帧率，如 `30000/1001`
+fn parse_frame_rate(rate: &str) -> Option<f64> {
+    let (num, den) = rate.split_once('/').unwrap_or((rate, "1"));
+    let (num, den) = (num.parse::<f64>().ok()?, den.parse::<f64>().ok()?);
+    (num > 0.0 && den > 0.0).then(|| num / den)
+}
+
+/// `loudnorm` 滤镜以 `print_format=json` 输出的测量值
+#[derive(Debug, Clone, Deserialize)]
+pub struct LoudnormStats {
+    #[serde(deserialize_with = "de_f64_str")]
+    pub input_i: f64,
+    #[serde(deserialize_with = "de_f64_str")]
+    pub input_tp: f64,
+    #[serde(deserialize_with = "de_f64_str")]
+    pub input_lra: f64,
+    #[serde(deserialize_with = "de_f64_str")]
+    pub input_thresh: f64,
+    #[serde(default, deserialize_with = "de_opt_f64_str")]
+    pub output_i: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_f64_str")]
+    pub output_tp: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_f64_str")]
+    pub output_lra: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_f64_str")]
+    pub output_thresh: Option<f64>,
+}
+
+impl LoudnormStats {
+    pub fn new(input_i: f64, input_tp: f64, input_lra: f64, input_thresh: f64) -> Self {
+        Self {
+            input_i,
+            input_tp,
+            input_lra,
+            input_thresh,
+            output_i: None,
+            output_tp: None,
+            output_lra: None,
+            output_thresh: None,
+        }
+    }
+
+    /// 第二遍处理的输出值，即处理后文件的测量值
+    pub fn output(&self) -> Option<LoudnormStats> {
+        Some(Self::new(
+            self.output_i?,
+            self.output_tp?,
+            self.output_lra?,
+            self.output_thresh?,
+        ))
+    }
+
+    /// 线性增益 `db` 之后的测量值
+    pub fn with_gain(&self, db: f64) -> LoudnormStats {
+        Self::new(
+            self.input_i + db,
+            self.input_tp + db,
+            self.input_lra,
+            self.input_thresh + db,
+        )
+    }
+}
+
+fn de_f64_str<'de, D: serde::Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+    let s = String::deserialize(d)?;
+    s.trim().parse().map_err(serde::de::Error::custom)
+}
+
+fn de_opt_f64_str<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Option<f64>, D::Error> {
+    let s = Option::<String>::deserialize(d)?;
+    // 静音输入时 loudnorm 会输出 "-inf"
+    Ok(s.and_then(|s| s.trim().parse().ok().filter(|v: &f64| v.is_finite())))
+}
+
+/// 从 ffmpeg 的 stderr 中找出 loudnorm 的 JSON 输出
+pub fn parse_loudnorm(stderr: &str) -> Option<LoudnormStats> {
+    let start = stderr.find("Parsed_loudnorm").unwrap_or(0);
+    let re = Regex::new(r"(?s)\{.*?\}").expect("Failed to compile regex");
+    let json = re.find(&stderr[start..])?;
+    serde_json::from_str(json.as_str()).ok()
+}
+
+/// 带 `-progress` 运行 ffmpeg，进度百分比（0~100）变化时调用 `on_progress`，
+/// 返回退出状态和 ffmpeg 写到 stderr 的全部内容
+pub async fn ffmpeg_with_progress<I, S, F, Fut>(
+    args: I,
+    duration_ms: Option<i64>,
+    mut on_progress: F,
+) -> anyhow::Result<(ExitStatus, String)>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+    F: FnMut(i32) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let mut child = Command::new("ffmpeg")
+        .arg("-progress")
+        .arg("pipe:1")
+        .arg("-nostats")
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("Failed to run ffmpeg: {}", e))?;
+
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    // stderr 需要同时读取，否则缓冲区写满会阻塞 ffmpeg
+    let stderr_task = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        stderr.read_to_end(&mut buf).await.ok();
+        buf
+    });
+
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let mut lines = BufReader::new(stdout).lines();
+    let mut last = None;
+    while let Some(line) = lines.next_line().await? {
+        let Some(duration_ms) = duration_ms.filter(|d| *d > 0) else {
+            continue;
+        };
+        let Some(out_time_us) = line
+            .strip_prefix("out_time_us=")
+            .and_then(|v| v.trim().parse::<i64>().ok())
+        else {
+            continue;
+        };
+        let percent = (out_time_us / 10 / duration_ms).clamp(0, 100) as i32;
+        if last != Some(percent) {
+            last = Some(percent);
+            on_progress(percent).await;
+        }
+    }
+
+    let status = child.wait().await?;
+    let stderr = stderr_task.await.unwrap_or_default();
+    Ok((status, String::from_utf8_lossy(&stderr).into_owned()))
+}
+
+/// 按推流参数编码的 ffmpeg 视频参数，推流时可以不重新编码直接转发
+pub fn stream_profile_args(cfg: &RtmpStreamerConfig) -> Vec<String> {
+    let (w, h, fps, kbps) = (
+        cfg.video_width,
+        cfg.video_height,
+        cfg.video_framerate,
+        cfg.video_bitrate,
+    );
+    [
+        "-vf".to_string(),
+        format!(
+            "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2,setsar=1,fps={fps}"
+        ),
+        "-c:v".to_string(),
+        "libx264".to_string(),
+        "-preset".to_string(),
+        cfg.video_speed.clone(),
+        "-profile:v".to_string(),
+        "main".to_string(),
+        "-pix_fmt".to_string(),
+        "yuv420p".to_string(),
+        "-b:v".to_string(),
+        format!("{kbps}k"),
+        "-maxrate".to_string(),
+        format!("{kbps}k"),
+        "-bufsize".to_string(),
+        format!("{}k", kbps * 2),
+        // 与推流编码器一致：无 B 帧，关键帧间隔 30
+        "-bf".to_string(),
+        "0".to_string(),
+        "-g".to_string(),
+        "30".to_string(),
+        "-refs".to_string(),
+        "2".to_string(),
+    ]
+    .into()
+}
+
+/// 截取 `at_ms` 处的一帧，缩放到 `width` 宽并保存为 JPEG
+pub async fn extract_poster(
+    input: &Path,
+    output: &Path,
+    at_ms: i64,
+    width: i32,
+) -> anyhow::Result<()> {
+    let status = Command::new("ffmpeg")
+        .arg("-y")
+        .arg("-loglevel")
+        .arg("error")
+        .arg("-ss")
+        .arg(format!("{:.3}", at_ms as f64 / 1000.0))
+        .arg("-i")
+        .arg(input)
+        .arg("-frames:v")
+        .arg("1")
+        .arg("-vf")
+        .arg(format!("scale={width}:-2"))
+        .arg("-q:v")
+        .arg("3")
+        .arg(output)
+        .stdin(Stdio::null())
+        .status()
+        .await
+        .map_err(|e| anyhow!("Failed to run ffmpeg: {}", e))?;
+
+    if !status.success() {
+        anyhow::bail!("FFmpeg poster extraction failed with status: {}", status);
+    }
+    Ok(())
+}
+
+/// 缩略图雪碧图的排列方式
+#[derive(Debug, Clone, Copy)]
+pub struct SpriteLayout {
+    pub columns: u32,
+    pub rows: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+}
+
+impl SpriteLayout {
+    pub fn new(
+        columns: u32,
+        rows: u32,
+        tile_width: u32,
+        video_width: i32,
+        video_height: i32,
+    ) -> Self {
+        // 高度取偶数，保证编码器可用
+        let tile_height = (tile_width as f64 * video_height as f64 / video_width as f64 / 2.0)
+            .round()
+            .max(1.0) as u32
+            * 2;
+        Self {
+            columns,
+            rows,
+            tile_width,
+            tile_height,
+        }
+    }
+
+    pub fn frames(&self) -> u32 {
+        self.columns * self.rows
+    }
+}
+
+/// 均匀截取 `layout.frames()` 帧，拼成一张 JPEG 雪碧图
+pub async fn extract_sprite(
+    input: &Path,
+    output: &Path,
+    duration_ms: i64,
+    layout: SpriteLayout,
+) -> anyhow::Result<()> {
+    let fps = layout.frames() as f64 * 1000.0 / duration_ms as f64;
+    let status = Command::new("ffmpeg")
+        .arg("-y")
+        .arg("-loglevel")
+        .arg("error")
+        .arg("-i")
+        .arg(input)
+        .arg("-vf")
+        .arg(format!(
+            "fps={fps:.6},scale={}:{},tile={}x{}",
+            layout.tile_width, layout.tile_height, layout.columns, layout.rows
+        ))
+        .arg("-frames:v")
+        .arg("1")
+        .arg("-q:v")
+        .arg("4")
+        .arg(output)
+        .stdin(Stdio::null())
+        .status()
+        .await
+        .map_err(|e| anyhow!("Failed to run ffmpeg: {}", e))?;
+
+    if !status.success() {
+        anyhow::bail!("FFmpeg sprite extraction failed with status: {}", status);
+    }
+    Ok(())
+}
+
+/// 为 [`extract_sprite`] 生成的雪碧图生成 WebVTT 索引
+pub fn sprite_vtt(image: &str, duration_ms: i64, layout: SpriteLayout) -> String {
+    let frames = layout.frames() as i64;
+    let mut vtt = String::from("WEBVTT\n");
+    for i in 0..frames {
+        let start = duration_ms * i / frames;
+        let end = duration_ms * (i + 1) / frames;
+        let x = (i as u32 % layout.columns) * layout.tile_width;
+        let y = (i as u32 / layout.columns) * layout.tile_height;
+        vtt.push_str(&format!(
+            "\n{} --> {}\n{}#xywh={},{},{},{}\n",
+            vtt_timestamp(start),
+            vtt_timestamp(end),
+            image,
+            x,
+            y,
+            layout.tile_width,
+            layout.tile_height
+        ));
+    }
+    vtt
+}
+
+fn vtt_timestamp(ms: i64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
+
+/// 检测到的切片内容起止点，以毫秒计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edges {
+    pub in_ms: i64,
+    pub out_ms: i64,
+}
+
+/// 解析 `silencedetect` / `blackdetect` 的输出，返回实际内容的起止点。
+/// 只有音频静音且画面全黑的片段才算空白，没有需要裁掉的部分时返回 `None`
+pub fn parse_edges(
+    stderr: &str,
+    duration_ms: i64,
+    has_audio: bool,
+    has_video: bool,
+) -> Option<Edges> {
+    let secs = |v: &str| v.trim().parse::<f64>().ok().map(|v| (v * 1000.0) as i64);
+
+    let mut silence = Vec::new();
+    let mut black = Vec::new();
+    for line in stderr.lines() {
+        if let Some(start) = line.split("silence_start:").nth(1).and_then(secs) {
+            silence.push((start, duration_ms));
+        } else if let Some(end) = line.split("silence_end:").nth(1) {
+            if let (Some(end), Some(last)) =
+                (end.split('|').next().and_then(secs), silence.last_mut())
+            {
+                last.1 = end;
+            }
+        } else if line.contains("black_start:") {
+            let field = |key: &str| {
+                line.split_whitespace()
+                    .find_map(|f| f.strip_prefix(key))
+                    .and_then(secs)
+            };
+            if let Some(start) = field("black_start:") {
+                black.push((start, field("black_end:").unwrap_or(duration_ms)));
+            }
+        }
+    }
+
+    // 容差，起止点附近的误差
+    const EPSILON_MS: i64 = 100;
+    let leading = |spans: &[(i64, i64)]| {
+        spans
+            .iter()
+            .find(|(start, _)| *start <= EPSILON_MS)
+            .map_or(0, |(_, end)| *end)
+    };
+    let trailing = |spans: &[(i64, i64)]| {
+        spans
+            .iter()
+            .rev()
+            .find(|(_, end)| *end >= duration_ms - EPSILON_MS)
+            .map_or(duration_ms, |(start, _)| *start)
+    };
+
+    let (in_ms, out_ms) = match (has_audio, has_video) {
+        (true, true) => (
+            leading(&silence).min(leading(&black)),
+            trailing(&silence).max(trailing(&black)),
+        ),
+        (true, false) => (leading(&silence), trailing(&silence)),
+        (false, true) => (leading(&black), trailing(&black)),
+        (false, false) => return None,
+    };
+
+    if in_ms >= out_ms || (in_ms <= EPSILON_MS && out_ms >= duration_ms - EPSILON_MS) {
+        return None;
+    }
+    Some(Edges { in_ms, out_ms })
+}
+
+/// 用 chromaprint 计算音频指纹，只取开头一段，返回原始的 32 位子指纹序列。
+pub async fn audio_fingerprint(path: &Path, max_secs: u32) -> anyhow::Result<Vec<u32>> {
+    let output = Command::new("ffmpeg")
+        .arg("-loglevel")
+        .arg("error")
+        .arg("-i")
+        .arg(path)
+        .arg("-t")
+        .arg(max_secs.to_string())
+        .arg("-vn")
+        .arg("-ac")
+        .arg("1")
+        .arg("-f")
+        .arg("chromaprint")
+        .arg("-fp_format")
+        .arg("raw")
+        .arg("-")
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| anyhow!("Failed to run ffmpeg: {}", e))?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "FFmpeg fingerprinting failed with status {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(fingerprint_from_bytes(&output.stdout))
+}
+
+pub fn fingerprint_to_bytes(fp: &[u32]) -> Vec<u8> {
+    fp.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+pub fn fingerprint_from_bytes(bytes: &[u8]) -> Vec<u32> {
+    bytes
+        .chunks_exact(4)
+        .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect()
+}
+
+/// 比较两段指纹的相似度（0~1），允许一定的起始偏移，以应对前后多剪或少剪了几秒。
+pub fn fingerprint_similarity(a: &[u32], b: &[u32]) -> f64 {
+    // 每个子指纹约 0.124 秒，最多错开约 10 秒
+    const MAX_OFFSET: isize = 80;
+    const MIN_OVERLAP: usize = 16;
+
+    let mut best = 0.0f64;
+    for offset in -MAX_OFFSET..=MAX_OFFSET {
+        let (a, b) = match offset {
+            o if o >= 0 => (a.get(o as usize..).unwrap_or(&[]), b),
+            o => (a, b.get((-o) as usize..).unwrap_or(&[])),
+        };
+        let overlap = a.len().min(b.len());
+        if overlap < MIN_OVERLAP {
+            continue;
+        }
+        let diff_bits: u32 = a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum();
+        let similarity = 1.0 - diff_bits as f64 / (overlap as f64 * 32.0);
+        best = best.max(similarity);
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_frame_rate() {
+        assert_eq!(parse_frame_rate("60/1"), Some(60.0));
+        assert!((parse_frame_rate("30000/1001").unwrap() - 29.97).abs() < 0.01);
+        assert_eq!(parse_frame_rate("0/0"), None);
+    }
+
+    #[test]
+    fn test_parse_loudnorm() {
+        let stderr = r#"Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'in.mp4':
+  Metadata:
+    encoder         : Lavf60.3.100
+[Parsed_loudnorm_0 @ 0x5581] 
+{
+	"input_i" : "-23.51",
+	"input_tp" : "-5.20",
+	"input_lra" : "6.30",
+	"input_thresh" : "-33.87",
+	"output_i" : "-14.02",
+	"output_tp" : "-1.00",
+	"output_lra" : "5.10",
+	"output_thresh" : "-24.30",
+	"normalization_type" : "linear",
+	"target_offset" : "0.02"
+}
+"#;
+        let stats = parse_loudnorm(stderr).unwrap();
+        assert_eq!(stats.input_i, -23.51);
+        assert_eq!(stats.input_thresh, -33.87);
+        assert_eq!(stats.output_i, Some(-14.02));
+        let output = stats.output().unwrap();
+        assert_eq!(output.input_i, -14.02);
+        assert_eq!(output.input_lra, 5.10);
+    }
+
+    #[test]
+    fn test_sprite_vtt() {
+        let layout = SpriteLayout::new(2, 2, 160, 1920, 1080);
+        assert_eq!(layout.tile_height, 90);
+        let vtt = sprite_vtt("sprite.jpg", 4000, layout);
+        assert!(vtt.starts_with("WEBVTT\n"));
+        assert!(vtt.contains("00:00:01.000 --> 00:00:02.000\nsprite.jpg#xywh=160,0,160,90\n"));
+        assert!(vtt.contains("00:00:03.000 --> 00:00:04.000\nsprite.jpg#xywh=160,90,160,90\n"));
+    }
+
+    #[test]
+    fn test_parse_edges() {
+        let stderr = "\
+[silencedetect @ 0x1] silence_start: 0
+[silencedetect @ 0x1] silence_end: 2.5 | silence_duration: 2.5
+[blackdetect @ 0x2] black_start:0 black_end:2 black_duration:2
+[silencedetect @ 0x1] silence_start: 57.2
+[blackdetect @ 0x2] black_start:58.5 black_end:60 black_duration:1.5
+[silencedetect @ 0x1] silence_end: 60 | silence_duration: 2.8
+";
+        assert_eq!(
+            parse_edges(stderr, 60_000, true, true),
+            Some(Edges {
+                in_ms: 2000,
+                out_ms: 58_500
+            })
+        );
+        assert_eq!(
+            parse_edges(stderr, 60_000, true, false),
+            Some(Edges {
+                in_ms: 2500,
+                out_ms: 57_200
+            })
+        );
+        assert_eq!(parse_edges("", 60_000, true, true), None);
+    }
+
+    #[test]
+    fn test_fingerprint_similarity() {
+        // xorshift 生成伪随机序列
+        let random = |mut x: u32, n: usize| -> Vec<u32> {
+            (0..n)
+                .map(|_| {
+                    x ^= x << 13;
+                    x ^= x >> 17;
+                    x ^= x << 5;
+                    x
+                })
+                .collect()
+        };
+        let a = random(1, 200);
+        let shifted = a[10..].to_vec();
+        assert_eq!(fingerprint_similarity(&a, &a), 1.0);
+        assert_eq!(fingerprint_similarity(&a, &shifted), 1.0);
+        assert_eq!(fingerprint_similarity(&shifted, &a), 1.0);
+
+        let other = random(7, 200);
+        assert!(fingerprint_similarity(&a, &other) < 0.6);
+        assert_eq!(fingerprint_from_bytes(&fingerprint_to_bytes(&a)), a);
+    }
+}
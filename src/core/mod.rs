@@ -1,4 +1,6 @@
 pub(crate) mod entity;
 pub(crate) mod jwt;
+pub(crate) mod media;
+pub(crate) mod queue;
 pub(crate) mod storage;
 pub(crate) mod streamer;
@@ -0,0 +1,80 @@
+use anyhow::Context;
+use apalis::prelude::Storage as _;
+use apalis_sql::mysql::MysqlStorage;
+use apalis_sql::postgres::PostgresStorage;
+use apalis_sql::sqlite::SqliteStorage;
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// 保存在应用数据库中的任务队列，重启后未完成的任务仍会继续
+pub enum JobQueue<T> {
+    Sqlite(SqliteStorage<T>),
+    Postgres(PostgresStorage<T>),
+    MySql(MysqlStorage<T>),
+}
+
+impl<T> Clone for JobQueue<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Sqlite(storage) => Self::Sqlite(storage.clone()),
+            Self::Postgres(storage) => Self::Postgres(storage.clone()),
+            Self::MySql(storage) => Self::MySql(storage.clone()),
+        }
+    }
+}
+
+impl<T> JobQueue<T>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + Unpin + 'static,
+{
+    pub async fn new(db: &DatabaseConnection) -> anyhow::Result<Self> {
+        let queue = match db.get_database_backend() {
+            DatabaseBackend::Sqlite => {
+                let pool = db.get_sqlite_connection_pool();
+                SqliteStorage::setup(pool)
+                    .await
+                    .context("Failed to set up sqlite job queue")?;
+                Self::Sqlite(SqliteStorage::new(pool.clone()))
+            }
+            DatabaseBackend::Postgres => {
+                let pool = db.get_postgres_connection_pool();
+                PostgresStorage::setup(pool)
+                    .await
+                    .context("Failed to set up postgres job queue")?;
+                Self::Postgres(PostgresStorage::new(pool.clone()))
+            }
+            DatabaseBackend::MySql => {
+                let pool = db.get_mysql_connection_pool();
+                MysqlStorage::setup(pool)
+                    .await
+                    .context("Failed to set up mysql job queue")?;
+                Self::MySql(MysqlStorage::new(pool.clone()))
+            }
+        };
+        Ok(queue)
+    }
+
+    pub async fn push(&self, job: T) -> anyhow::Result<()> {
+        match self.clone() {
+            Self::Sqlite(mut storage) => storage.push(job).await.map(|_| ()),
+            Self::Postgres(mut storage) => storage.push(job).await.map(|_| ()),
+            Self::MySql(mut storage) => storage.push(job).await.map(|_| ()),
+        }
+        .context("Failed to push job to queue")
+    }
+
+    /// 把上次运行时中断的任务重新放回队列
+    pub async fn requeue_orphaned(&self) -> anyhow::Result<()> {
+        let dead_since = chrono::Utc::now();
+        match self.clone() {
+            Self::Sqlite(storage) => storage.reenqueue_orphaned(i32::MAX, dead_since).await,
+            Self::Postgres(mut storage) => storage.reenqueue_orphaned(i32::MAX, dead_since).await,
+            Self::MySql(storage) => storage
+                .reenqueue_orphaned(i32::MAX, dead_since)
+                .await
+                .map(|_| ()),
+        }
+        .context("Failed to requeue orphaned jobs")
+    }
+}
@@ -6,11 +6,14 @@ use gst::prelude::*;
 use gstreamer as gst;
 use gstreamer_app as gst_app;
 use serde::Deserialize;
+use sha2::{Digest, Sha256};
 use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio::select;
 use tokio::sync::{Mutex, Notify};
 use tracing;
 
+use crate::core::media;
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct RtmpStreamerConfig {
     pub buffer_time: u64, // in seconds
@@ -31,6 +34,11 @@ pub struct RtmpStreamerConfig {
     pub title_font: String,
     pub title_halign: String,
     pub title_valign: String,
+
+    // 直接转发已符合推流参数的 H.264 视频，不再重新编码
+    // 开启后画面上不再叠加标题
+    #[serde(default)]
+    pub video_passthrough: bool,
 }
 
 impl Default for RtmpStreamerConfig {
@@ -49,7 +57,86 @@ impl Default for RtmpStreamerConfig {
             title_font: "Sans, 24".to_string(),
             title_halign: "right".to_string(),
             title_valign: "top".to_string(),
+            video_passthrough: false,
+        }
+    }
+}
+
+impl RtmpStreamerConfig {
+    // 转码参数的摘要，记录在切片上用于判断是否按当前推流参数转码过
+    pub fn profile_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        for arg in media::stream_profile_args(self) {
+            hasher.update(arg.as_bytes());
+            hasher.update([0]);
         }
+        hex::encode(&hasher.finalize()[..8])
+    }
+
+    // 开启 video_passthrough 时能否直接转发：必须由处理流程按当前推流参数转码过，
+    // 未经转码的上传文件可能带有 B 帧或不同的 profile 和关键帧间隔
+    pub fn accepts_video(
+        &self,
+        codec: Option<&str>,
+        width: Option<i32>,
+        height: Option<i32>,
+        frame_rate: Option<f64>,
+        stream_profile: Option<&str>,
+    ) -> bool {
+        stream_profile == Some(self.profile_hash().as_str())
+            && codec == Some("h264")
+            && width == Some(self.video_width)
+            && height == Some(self.video_height)
+            && frame_rate.is_some_and(|f| (f - self.video_framerate as f64).abs() < 0.01)
+    }
+
+    fn make_video_encoder(&self) -> anyhow::Result<[gst::Element; 3]> {
+        let x264enc = gst::ElementFactory::make("x264enc")
+            .property_from_str("tune", "zerolatency")
+            .property("bitrate", self.video_bitrate)
+            .property("key-int-max", 30u32)
+            .property("bframes", 0u32)
+            .property("ref", 2u32)
+            .property("byte-stream", true)
+            .property("vbv-buf-capacity", 0u32)
+            .property_from_str("speed-preset", &self.video_speed)
+            .build()?;
+        let h264parse = gst::ElementFactory::make("h264parse")
+            .property("config-interval", -1i32)
+            .build()?;
+        let h264_caps = gst::Caps::builder("video/x-h264")
+            .field("profile", "main")
+            .build();
+        let capsfilter_h264 = gst::ElementFactory::make("capsfilter")
+            .property("caps", h264_caps)
+            .build()?;
+        Ok([x264enc, h264parse, capsfilter_h264])
+    }
+
+    fn make_title_overlay(&self, text: &str) -> anyhow::Result<gst::Element> {
+        let overlay = gst::ElementFactory::make("textoverlay")
+            .name("overlay")
+            .property("text", text)
+            .property_from_str("halignment", &self.title_halign)
+            .property_from_str("valignment", &self.title_valign)
+            .property_from_str("font-desc", &self.title_font)
+            .property("shaded-background", &true)
+            .build()?;
+        Ok(overlay)
+    }
+
+    fn make_h264_parser(&self) -> anyhow::Result<[gst::Element; 2]> {
+        let h264parse = gst::ElementFactory::make("h264parse")
+            .property("config-interval", -1i32)
+            .build()?;
+        let h264_caps = gst::Caps::builder("video/x-h264")
+            .field("stream-format", "byte-stream")
+            .field("alignment", "au")
+            .build();
+        let capsfilter_h264 = gst::ElementFactory::make("capsfilter")
+            .property("caps", h264_caps)
+            .build()?;
+        Ok([h264parse, capsfilter_h264])
     }
 }
 
@@ -64,6 +151,8 @@ struct StreamerState {
     video_src: Option<gst::Element>,
     audio_src: Option<gst::Element>,
     title: Option<gst::Element>,
+    // 直通模式下为单独编码的切片叠加的标题
+    title_text: Option<String>,
     pipeline: Option<gst::Pipeline>,
     stop: Option<Arc<Notify>>,
     is_streaming: bool,
@@ -79,6 +168,7 @@ impl RtmpStreamer {
                 video_src: None,
                 audio_src: None,
                 title: None,
+                title_text: None,
                 pipeline: None,
                 stop: None,
                 is_streaming: false,
@@ -96,32 +186,35 @@ impl RtmpStreamer {
             .property("format", &gst::Format::Time)
             .build()?;
 
-        let videoconvert = gst::ElementFactory::make("videoconvert").build()?;
-        let overlay = gst::ElementFactory::make("textoverlay")
-            .name("overlay")
-            .property("text", "Initial Title")
-            .property_from_str("halignment", &self.config.title_halign)
-            .property_from_str("valignment", &self.config.title_valign)
-            .property_from_str("font-desc", &self.config.title_font)
-            .property("shaded-background", &true)
-            .build()?;
-        let x264enc = gst::ElementFactory::make("x264enc")
-            .property_from_str("tune", &"zerolatency")
-            .property("bitrate", &self.config.video_bitrate)
-            .property("key-int-max", &30u32)
-            .property("bframes", &0u32)
-            .property("ref", &2u32)
-            .property("byte-stream", &true)
-            .property("vbv-buf-capacity", &0u32)
-            .property_from_str("speed-preset", &self.config.video_speed)
-            .build()?;
-        let h264parse = gst::ElementFactory::make("h264parse").build()?;
-        let h264_caps = gst::Caps::builder("video/x-h264")
-            .field("profile", &"main")
-            .build();
-        let capsfilter_h264 = gst::ElementFactory::make("capsfilter")
-            .property("caps", &h264_caps)
-            .build()?;
+        // 直通模式下 videosrc 接收编码好的 H.264，否则接收原始画面并在此编码
+        let (video_chain, overlay) = if self.config.video_passthrough {
+            video_src.set_property(
+                "caps",
+                &gst::Caps::builder("video/x-h264")
+                    .field("stream-format", "byte-stream")
+                    .field("alignment", "au")
+                    .build(),
+            );
+            tracing::warn!(
+                "Video passthrough is enabled, titles are only shown on re-encoded clips"
+            );
+            let [h264parse, capsfilter_h264] = self.config.make_h264_parser()?;
+            (vec![h264parse, capsfilter_h264], None)
+        } else {
+            let videoconvert = gst::ElementFactory::make("videoconvert").build()?;
+            let overlay = self.config.make_title_overlay("Initial Title")?;
+            let [x264enc, h264parse, capsfilter_h264] = self.config.make_video_encoder()?;
+            (
+                vec![
+                    videoconvert,
+                    overlay.clone(),
+                    x264enc,
+                    h264parse,
+                    capsfilter_h264,
+                ],
+                Some(overlay),
+            )
+        };
 
         // video
         let audio_src = gst::ElementFactory::make("appsrc")
@@ -162,13 +255,9 @@ impl RtmpStreamer {
             .build()?;
 
         // add elements to pipeline
+        pipeline.add(&video_src)?;
+        pipeline.add_many(&video_chain)?;
         pipeline.add_many(&[
-            &video_src,
-            &videoconvert,
-            &overlay,
-            &x264enc,
-            &h264parse,
-            &capsfilter_h264,
             &audio_src,
             &audioconvert,
             &aacenc,
@@ -180,15 +269,11 @@ impl RtmpStreamer {
         ])?;
 
         // link video elements
-        gst::Element::link_many(&[
-            &video_src,
-            &videoconvert,
-            &overlay,
-            &x264enc,
-            &h264parse,
-            &capsfilter_h264,
-            &muxer,
-        ])?;
+        gst::Element::link_many(
+            std::iter::once(&video_src)
+                .chain(video_chain.iter())
+                .chain(std::iter::once(&muxer)),
+        )?;
 
         // link audio elements
         gst::Element::link_many(&[
@@ -265,7 +350,7 @@ impl RtmpStreamer {
         state.pipeline = Some(pipeline.clone());
         state.video_src = Some(video_src);
         state.audio_src = Some(audio_src);
-        state.title = Some(overlay);
+        state.title = overlay;
         state.stop = Some(notify);
         state.is_streaming = true;
 
@@ -283,6 +368,7 @@ impl RtmpStreamer {
                 anyhow!("Failed to set pipeline to Null state: {}", e)
             })?;
             state.title = None;
+            state.title_text = None;
             state.is_streaming = false;
             state.stop.take_if(|n| {
                 n.notify_waiters();
@@ -297,18 +383,23 @@ impl RtmpStreamer {
 
     pub async fn update_title(&self, new_title: &str) -> anyhow::Result<()> {
         tracing::trace!("Starting to update title to: {}", new_title);
-        let state = self.state.lock().await;
+        let mut state = self.state.lock().await;
         if let Some(overlay) = &state.title {
             overlay.set_property("text", &new_title);
         }
+        state.title_text = Some(new_title.to_string());
         tracing::debug!("Updated title: {}", new_title);
         Ok(())
     }
 
-    pub async fn push<R>(&self, reader: R) -> anyhow::Result<()>
+    /// Pushes a clip into the running stream. `compatible` marks clips whose
+    /// video already matches the stream profile, which are forwarded without
+    /// re-encoding when `video_passthrough` is enabled.
+    pub async fn push<R>(&self, reader: R, compatible: bool) -> anyhow::Result<()>
     where
         R: AsyncRead + Unpin + Send + 'static,
     {
+        let passthrough = self.config.video_passthrough && compatible;
         // Create decoding pipeline
         let pipeline = gst::Pipeline::new();
 
@@ -322,46 +413,55 @@ impl RtmpStreamer {
             .property("do-timestamp", &true)
             .build()?;
         let decodebin = gst::ElementFactory::make("decodebin").build()?;
+        if passthrough {
+            // 视频只解封装不解码，音频照常解码
+            let caps = "video/x-h264; audio/x-raw(ANY)".parse::<gst::Caps>()?;
+            decodebin.set_property("caps", &caps);
+        }
         pipeline.add_many(&[&src, &decodebin])?;
         src.link(&decodebin)?;
 
         // Create video and audio converters and sinks
-        let video_convert = gst::ElementFactory::make("videoconvert").build()?;
-        let video_rate = gst::ElementFactory::make("videorate").build()?;
-        let video_scale = gst::ElementFactory::make("videoscale").build()?;
-        let video_caps = gst::Caps::builder("video/x-raw")
-            .field("format", &"I420")
-            .field("width", &self.config.video_width)
-            .field("height", &self.config.video_height)
-            .field(
-                "framerate",
-                &gst::Fraction::new(self.config.video_framerate, 1),
-            )
-            .build();
-        let video_capsfilter = gst::ElementFactory::make("capsfilter")
-            .property("caps", &video_caps)
-            .build()?;
         let video_sink = gst::ElementFactory::make("appsink")
             .name("video_sink")
             .property("sync", &true)
             .property("emit-signals", &true)
             .property("drop", &true)
             .build()?;
+        let mut video_chain = Vec::new();
+        if passthrough {
+            video_chain.extend(self.config.make_h264_parser()?);
+        } else {
+            let video_convert = gst::ElementFactory::make("videoconvert").build()?;
+            let video_rate = gst::ElementFactory::make("videorate").build()?;
+            let video_scale = gst::ElementFactory::make("videoscale").build()?;
+            let video_caps = gst::Caps::builder("video/x-raw")
+                .field("format", &"I420")
+                .field("width", &self.config.video_width)
+                .field("height", &self.config.video_height)
+                .field(
+                    "framerate",
+                    &gst::Fraction::new(self.config.video_framerate, 1),
+                )
+                .build();
+            let video_capsfilter = gst::ElementFactory::make("capsfilter")
+                .property("caps", &video_caps)
+                .build()?;
+            video_chain.extend([video_convert, video_rate, video_scale, video_capsfilter]);
+            if self.config.video_passthrough {
+                // 主推流管道不再编码，不兼容的切片在这里叠加标题并单独编码
+                let title = self.state.lock().await.title_text.clone();
+                video_chain.push(
+                    self.config
+                        .make_title_overlay(title.as_deref().unwrap_or_default())?,
+                );
+                video_chain.extend(self.config.make_video_encoder()?);
+            }
+        }
+        video_chain.push(video_sink.clone());
 
-        pipeline.add_many(&[
-            &video_convert,
-            &video_rate,
-            &video_scale,
-            &video_capsfilter,
-            &video_sink,
-        ])?;
-        gst::Element::link_many(&[
-            &video_convert,
-            &video_rate,
-            &video_scale,
-            &video_capsfilter,
-            &video_sink,
-        ])?;
+        pipeline.add_many(&video_chain)?;
+        gst::Element::link_many(&video_chain)?;
 
         let audio_convert = gst::ElementFactory::make("audioconvert").build()?;
         let audio_rate = gst::ElementFactory::make("audiorate").build()?;
@@ -408,7 +508,7 @@ impl RtmpStreamer {
             .dynamic_cast::<gst_app::AppSink>()
             .unwrap();
         let audio_convert_clone = audio_convert.clone();
-        let video_convert_clone = video_convert.clone();
+        let video_entry = video_chain[0].clone();
 
         // Handle new pads
         decodebin.connect_pad_added(move |_bin, pad| {
@@ -425,7 +525,7 @@ impl RtmpStreamer {
                     }
                 }
             } else if name.starts_with("video/") {
-                let sink_pad = video_convert_clone.static_pad("sink").unwrap();
+                let sink_pad = video_entry.static_pad("sink").unwrap();
                 if !sink_pad.is_linked() {
                     match pad.link(&sink_pad) {
                         Ok(_) => tracing::debug!("Video pad linked successfully"),
@@ -664,6 +764,23 @@ mod tests {
         tracing_subscriber::fmt().with_env_filter("trace").init();
     }
 
+    #[test]
+    fn test_accepts_video() {
+        let config = RtmpStreamerConfig::default();
+        let profile = config.profile_hash();
+        let accepts = |profile: Option<&str>| {
+            config.accepts_video(Some("h264"), Some(1280), Some(720), Some(30.0), profile)
+        };
+        assert!(accepts(Some(&profile)));
+        // 参数相同但未经转码
+        assert!(!accepts(None));
+        let changed = RtmpStreamerConfig {
+            video_bitrate: 4000,
+            ..RtmpStreamerConfig::default()
+        };
+        assert!(!accepts(Some(&changed.profile_hash())));
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_rtmp_streamer() -> anyhow::Result<()> {
         let config = RtmpStreamerConfig::default();
@@ -675,15 +792,15 @@ mod tests {
         // read from test files
         let video_file = File::open("data/test_001.mp4").await?;
         streamer.update_title("Test test_000 Stream").await?;
-        streamer.push(video_file).await?;
+        streamer.push(video_file, false).await?;
 
         let video_file = File::open("data/test_001.mp4").await?;
         streamer.update_title("Test test_001 Stream").await?;
-        streamer.push(video_file).await?;
+        streamer.push(video_file, false).await?;
 
         let video_file = File::open("data/test_002.mp4").await?;
         streamer.update_title("Test test_002 Stream").await?;
-        streamer.push(video_file).await?;
+        streamer.push(video_file, false).await?;
 
         drop(streamer);
         Ok(())
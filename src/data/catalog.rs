@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+
+use sea_orm::prelude::*;
+use sea_orm::sea_query::Expr;
+use sea_orm::{IntoActiveModel, Order, QueryOrder, QuerySelect, Set, TransactionTrait};
+
+use crate::core::entity::{clip, song, song_alias, vup, vup_alias};
+
+/// 切片引用的目录
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CatalogKind {
+    Vup,
+    Song,
+}
+
+/// vup 或歌曲条目，`image_url` 对应头像或封面
+#[derive(Clone, Debug, Default)]
+pub struct CatalogEntry {
+    pub id: i64,
+    pub name: String,
+    pub image_url: Option<String>,
+    pub link: Option<String>,
+    pub aliases: Vec<String>,
+    pub clip_count: i64,
+}
+
+impl From<vup::Model> for CatalogEntry {
+    fn from(v: vup::Model) -> Self {
+        Self {
+            id: v.id,
+            name: v.name,
+            image_url: v.avatar_url,
+            link: v.link,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<song::Model> for CatalogEntry {
+    fn from(s: song::Model) -> Self {
+        Self {
+            id: s.id,
+            name: s.name,
+            image_url: s.cover_url,
+            link: s.link,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CatalogData {
+    db: DatabaseConnection,
+}
+
+impl CatalogData {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 按规范化后的别名查找条目，返回 (id, 正式名称)
+    pub async fn find_catalog_by_key(
+        &self,
+        kind: CatalogKind,
+        key: &str,
+    ) -> anyhow::Result<Option<(i64, String)>> {
+        let found = match kind {
+            CatalogKind::Vup => vup_alias::Entity::find()
+                .filter(vup_alias::Column::Key.eq(key))
+                .find_also_related(vup::Entity)
+                .one(&self.db)
+                .await?
+                .and_then(|(_, v)| v)
+                .map(|v| (v.id, v.name)),
+            CatalogKind::Song => song_alias::Entity::find()
+                .filter(song_alias::Column::Key.eq(key))
+                .find_also_related(song::Entity)
+                .one(&self.db)
+                .await?
+                .and_then(|(_, s)| s)
+                .map(|s| (s.id, s.name)),
+        };
+        Ok(found)
+    }
+
+    /// 新建条目，`key` 已被占用时返回错误
+    pub async fn create_catalog_entry(
+        &self,
+        kind: CatalogKind,
+        name: &str,
+        key: &str,
+    ) -> anyhow::Result<i64> {
+        let tx = self.db.begin().await?;
+        let now = chrono::Utc::now();
+        let id = match kind {
+            CatalogKind::Vup => {
+                let entry = vup::ActiveModel {
+                    name: Set(name.to_string()),
+                    created_at: Set(now.into()),
+                    ..Default::default()
+                }
+                .insert(&tx)
+                .await?;
+                vup_alias::ActiveModel {
+                    vup_id: Set(entry.id),
+                    alias: Set(name.to_string()),
+                    key: Set(key.to_string()),
+                    ..Default::default()
+                }
+                .insert(&tx)
+                .await?;
+                entry.id
+            }
+            CatalogKind::Song => {
+                let entry = song::ActiveModel {
+                    name: Set(name.to_string()),
+                    created_at: Set(now.into()),
+                    ..Default::default()
+                }
+                .insert(&tx)
+                .await?;
+                song_alias::ActiveModel {
+                    song_id: Set(entry.id),
+                    alias: Set(name.to_string()),
+                    key: Set(key.to_string()),
+                    ..Default::default()
+                }
+                .insert(&tx)
+                .await?;
+                entry.id
+            }
+        };
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    /// 全部条目，附带别名和引用的切片数
+    pub async fn list_catalog(&self, kind: CatalogKind) -> anyhow::Result<Vec<CatalogEntry>> {
+        let (entries, aliases, counts): (Vec<CatalogEntry>, Vec<(i64, String)>, Vec<_>) = match kind
+        {
+            CatalogKind::Vup => (
+                vup::Entity::find()
+                    .order_by(vup::Column::Name, Order::Asc)
+                    .all(&self.db)
+                    .await?
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+                vup_alias::Entity::find()
+                    .order_by(vup_alias::Column::Id, Order::Asc)
+                    .all(&self.db)
+                    .await?
+                    .into_iter()
+                    .map(|a| (a.vup_id, a.alias))
+                    .collect(),
+                self.count_clips_by(clip::Column::VupId).await?,
+            ),
+            CatalogKind::Song => (
+                song::Entity::find()
+                    .order_by(song::Column::Name, Order::Asc)
+                    .all(&self.db)
+                    .await?
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+                song_alias::Entity::find()
+                    .order_by(song_alias::Column::Id, Order::Asc)
+                    .all(&self.db)
+                    .await?
+                    .into_iter()
+                    .map(|a| (a.song_id, a.alias))
+                    .collect(),
+                self.count_clips_by(clip::Column::SongId).await?,
+            ),
+        };
+
+        let mut alias_map: HashMap<i64, Vec<String>> = HashMap::new();
+        for (id, alias) in aliases {
+            alias_map.entry(id).or_default().push(alias);
+        }
+        let counts: HashMap<i64, i64> = counts.into_iter().collect();
+        Ok(entries
+            .into_iter()
+            .map(|mut entry| {
+                entry.aliases = alias_map.remove(&entry.id).unwrap_or_default();
+                entry.clip_count = counts.get(&entry.id).copied().unwrap_or(0);
+                entry
+            })
+            .collect())
+    }
+
+    async fn count_clips_by(&self, column: clip::Column) -> anyhow::Result<Vec<(i64, i64)>> {
+        let counts: Vec<(Option<i64>, i64)> = clip::Entity::find()
+            .select_only()
+            .column(column)
+            .column_as(clip::Column::Id.count(), "count")
+            .filter(column.is_not_null())
+            .group_by(column)
+            .into_tuple()
+            .all(&self.db)
+            .await?;
+        Ok(counts
+            .into_iter()
+            .filter_map(|(id, count)| Some((id?, count)))
+            .collect())
+    }
+
+    pub async fn get_catalog_entry(
+        &self,
+        kind: CatalogKind,
+        id: i64,
+    ) -> anyhow::Result<Option<CatalogEntry>> {
+        let entry = match kind {
+            CatalogKind::Vup => vup::Entity::find_by_id(id)
+                .one(&self.db)
+                .await?
+                .map(Into::into),
+            CatalogKind::Song => song::Entity::find_by_id(id)
+                .one(&self.db)
+                .await?
+                .map(Into::into),
+        };
+        Ok(entry)
+    }
+
+    /// 更新条目并替换全部别名，`aliases` 为 (别名, 规范化 key)，引用它的切片同步正式名称
+    pub async fn update_catalog_entry(
+        &self,
+        kind: CatalogKind,
+        entry: &CatalogEntry,
+        aliases: &[(String, String)],
+    ) -> anyhow::Result<()> {
+        let tx = self.db.begin().await?;
+        match kind {
+            CatalogKind::Vup => {
+                let mut active = vup::Entity::find_by_id(entry.id)
+                    .one(&tx)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("Vup {} not found", entry.id))?
+                    .into_active_model();
+                active.name = Set(entry.name.clone());
+                active.avatar_url = Set(entry.image_url.clone());
+                active.link = Set(entry.link.clone());
+                active.update(&tx).await?;
+                vup_alias::Entity::delete_many()
+                    .filter(vup_alias::Column::VupId.eq(entry.id))
+                    .exec(&tx)
+                    .await?;
+                vup_alias::Entity::insert_many(aliases.iter().map(|(alias, key)| {
+                    vup_alias::ActiveModel {
+                        vup_id: Set(entry.id),
+                        alias: Set(alias.clone()),
+                        key: Set(key.clone()),
+                        ..Default::default()
+                    }
+                }))
+                .exec(&tx)
+                .await?;
+                clip::Entity::update_many()
+                    .col_expr(clip::Column::Vup, Expr::value(entry.name.clone()))
+                    .filter(clip::Column::VupId.eq(entry.id))
+                    .exec(&tx)
+                    .await?;
+            }
+            CatalogKind::Song => {
+                let mut active = song::Entity::find_by_id(entry.id)
+                    .one(&tx)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("Song {} not found", entry.id))?
+                    .into_active_model();
+                active.name = Set(entry.name.clone());
+                active.cover_url = Set(entry.image_url.clone());
+                active.link = Set(entry.link.clone());
+                active.update(&tx).await?;
+                song_alias::Entity::delete_many()
+                    .filter(song_alias::Column::SongId.eq(entry.id))
+                    .exec(&tx)
+                    .await?;
+                song_alias::Entity::insert_many(aliases.iter().map(|(alias, key)| {
+                    song_alias::ActiveModel {
+                        song_id: Set(entry.id),
+                        alias: Set(alias.clone()),
+                        key: Set(key.clone()),
+                        ..Default::default()
+                    }
+                }))
+                .exec(&tx)
+                .await?;
+                clip::Entity::update_many()
+                    .col_expr(clip::Column::Song, Expr::value(entry.name.clone()))
+                    .filter(clip::Column::SongId.eq(entry.id))
+                    .exec(&tx)
+                    .await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// 把 `from` 合并进 `into`：别名和切片都转到 `into`，然后删除 `from`
+    pub async fn merge_catalog_entries(
+        &self,
+        kind: CatalogKind,
+        from: i64,
+        into: &CatalogEntry,
+    ) -> anyhow::Result<()> {
+        let tx = self.db.begin().await?;
+        match kind {
+            CatalogKind::Vup => {
+                vup_alias::Entity::update_many()
+                    .col_expr(vup_alias::Column::VupId, Expr::value(into.id))
+                    .filter(vup_alias::Column::VupId.eq(from))
+                    .exec(&tx)
+                    .await?;
+                clip::Entity::update_many()
+                    .col_expr(clip::Column::VupId, Expr::value(into.id))
+                    .col_expr(clip::Column::Vup, Expr::value(into.name.clone()))
+                    .filter(clip::Column::VupId.eq(from))
+                    .exec(&tx)
+                    .await?;
+                vup::Entity::delete_by_id(from).exec(&tx).await?;
+            }
+            CatalogKind::Song => {
+                song_alias::Entity::update_many()
+                    .col_expr(song_alias::Column::SongId, Expr::value(into.id))
+                    .filter(song_alias::Column::SongId.eq(from))
+                    .exec(&tx)
+                    .await?;
+                clip::Entity::update_many()
+                    .col_expr(clip::Column::SongId, Expr::value(into.id))
+                    .col_expr(clip::Column::Song, Expr::value(into.name.clone()))
+                    .filter(clip::Column::SongId.eq(from))
+                    .exec(&tx)
+                    .await?;
+                song::Entity::delete_by_id(from).exec(&tx).await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
@@ -1,8 +1,35 @@
 use sea_orm::prelude::*;
-use sea_orm::{IntoActiveModel, Order, QueryOrder, Set, TransactionTrait};
+use sea_orm::sea_query::{Func, LikeExpr, Query};
+use sea_orm::{
+    Condition, IntoActiveModel, Order, PaginatorTrait, QueryOrder, QuerySelect, Set,
+    TransactionTrait,
+};
 use uuid::Uuid;
 
-use crate::core::entity::{clip, playlist_item};
+use crate::core::entity::{
+    clip, clip_review, clip_tag, clip_version, playlist_item, trashed_playlist_item, user,
+};
+
+/// `list_clips` 的查询条件，所有条件同时满足
+#[derive(Clone, Debug)]
+pub struct ClipQuery {
+    pub user_id: Option<i64>,
+    // 需同时包含的标签
+    pub tag_ids: Vec<i64>,
+    // 在标题、vup 和歌曲中模糊搜索
+    pub search: Option<String>,
+    pub statuses: Vec<clip::Status>,
+    pub vup_id: Option<i64>,
+    pub song_id: Option<i64>,
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    // 为 true 时只查回收站中的切片，否则排除它们
+    pub trashed: bool,
+    pub sort: clip::Column,
+    pub order: Order,
+    pub offset: u64,
+    pub limit: u64,
+}
 
 #[derive(Clone)]
 pub struct ClipData {
@@ -24,16 +51,165 @@ impl ClipData {
         Ok(clip)
     }
 
-    pub async fn list_clips_by_user(&self, user_id: i64) -> anyhow::Result<Vec<clip::Model>> {
-        let clips = clip::Entity::find()
+    pub async fn update_progress(
+        &self,
+        id: i64,
+        stage: Option<clip::ProcessStage>,
+        progress: Option<i32>,
+    ) -> anyhow::Result<()> {
+        clip::ActiveModel {
+            id: Set(id),
+            process_stage: Set(stage),
+            process_progress: Set(progress),
+            ..Default::default()
+        }
+        .update(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    /// 按条件筛选切片，返回当前页的切片和符合条件的总数
+    pub async fn list_clips(&self, q: &ClipQuery) -> anyhow::Result<(Vec<clip::Model>, u64)> {
+        let mut query = clip::Entity::find().filter(if q.trashed {
+            clip::Column::DeletedAt.is_not_null()
+        } else {
+            clip::Column::DeletedAt.is_null()
+        });
+        if let Some(user_id) = q.user_id {
+            query = query.filter(clip::Column::UserId.eq(user_id));
+        }
+        if !q.statuses.is_empty() {
+            query = query.filter(clip::Column::Status.is_in(q.statuses.iter().cloned()));
+        }
+        if let Some(vup_id) = q.vup_id {
+            query = query.filter(clip::Column::VupId.eq(vup_id));
+        }
+        if let Some(song_id) = q.song_id {
+            query = query.filter(clip::Column::SongId.eq(song_id));
+        }
+        if let Some(from) = q.from {
+            query = query.filter(clip::Column::UploadTime.gte(from));
+        }
+        if let Some(to) = q.to {
+            query = query.filter(clip::Column::UploadTime.lt(to));
+        }
+        if let Some(ref search) = q.search {
+            let pattern = format!("%{}%", escape_like(search));
+            let like = |col: clip::Column| {
+                Expr::col((clip::Entity, col)).like(LikeExpr::new(pattern.clone()).escape('\\'))
+            };
+            query = query.filter(
+                Condition::any()
+                    .add(like(clip::Column::Title))
+                    .add(like(clip::Column::Vup))
+                    .add(like(clip::Column::Song)),
+            );
+        }
+        if !q.tag_ids.is_empty() {
+            query = query.filter(
+                clip::Column::Id.in_subquery(
+                    Query::select()
+                        .column(clip_tag::Column::ClipId)
+                        .from(clip_tag::Entity)
+                        .and_where(clip_tag::Column::TagId.is_in(q.tag_ids.iter().copied()))
+                        .group_by_col(clip_tag::Column::ClipId)
+                        .and_having(
+                            Expr::expr(Func::count(Expr::col(clip_tag::Column::TagId)))
+                                .eq(q.tag_ids.len() as i64),
+                        )
+                        .to_owned(),
+                ),
+            );
+        }
+
+        let total = query.clone().count(&self.db).await?;
+        let clips = query
+            .order_by(q.sort, q.order.clone())
+            // 排序字段相同时按 id 保证翻页稳定
+            .order_by(clip::Column::Id, q.order.clone())
+            .offset(q.offset)
+            .limit(q.limit)
+            .all(&self.db)
+            .await?;
+        Ok((clips, total))
+    }
+
+    /// 用户所有切片的文件大小，用于统计配额
+    pub async fn list_file_sizes(&self, user_id: i64) -> anyhow::Result<Vec<Option<i64>>> {
+        let sizes = clip::Entity::find()
+            .select_only()
+            .column(clip::Column::FileSize)
             .filter(clip::Column::UserId.eq(user_id))
+            .into_tuple()
             .all(&self.db)
             .await?;
-        Ok(clips)
+        Ok(sizes)
+    }
+
+    /// 用户切片的历史版本文件大小，历史版本同样计入切片所有者的配额
+    pub async fn list_version_file_sizes(&self, user_id: i64) -> anyhow::Result<Vec<Option<i64>>> {
+        let sizes = clip_version::Entity::find()
+            .select_only()
+            .column(clip_version::Column::FileSize)
+            .inner_join(clip::Entity)
+            .filter(clip::Column::UserId.eq(user_id))
+            .into_tuple()
+            .all(&self.db)
+            .await?;
+        Ok(sizes)
+    }
+
+    pub async fn count_uploads_since(
+        &self,
+        user_id: i64,
+        since: DateTimeUtc,
+    ) -> anyhow::Result<u64> {
+        let count = clip::Entity::find()
+            .filter(clip::Column::UserId.eq(user_id))
+            .filter(clip::Column::UploadTime.gte(since))
+            .count(&self.db)
+            .await?;
+        Ok(count)
     }
 
-    pub(crate) async fn list_all_clips(&self) -> anyhow::Result<Vec<clip::Model>> {
-        let clips = clip::Entity::find().all(&self.db).await?;
+    pub async fn get_clip(&self, uuid: Uuid) -> anyhow::Result<Option<clip::Model>> {
+        let clip = clip::Entity::find()
+            .filter(clip::Column::Uuid.eq(uuid))
+            .one(&self.db)
+            .await?;
+        Ok(clip)
+    }
+
+    /// 按上传内容的哈希查找最早的一个切片
+    pub async fn find_clip_by_hash(&self, hash: &str) -> anyhow::Result<Option<clip::Model>> {
+        let clip = clip::Entity::find()
+            .filter(clip::Column::ContentHash.eq(hash))
+            .filter(clip::Column::DeletedAt.is_null())
+            .order_by(clip::Column::Id, Order::Asc)
+            .one(&self.db)
+            .await?;
+        Ok(clip)
+    }
+
+    pub async fn list_fingerprints(&self) -> anyhow::Result<Vec<(Uuid, Vec<u8>)>> {
+        let fingerprints = clip::Entity::find()
+            .select_only()
+            .column(clip::Column::Uuid)
+            .column(clip::Column::Fingerprint)
+            .filter(clip::Column::Fingerprint.is_not_null())
+            .filter(clip::Column::DeletedAt.is_null())
+            .order_by(clip::Column::Id, Order::Asc)
+            .into_tuple()
+            .all(&self.db)
+            .await?;
+        Ok(fingerprints)
+    }
+
+    pub async fn list_duplicate_clips(&self) -> anyhow::Result<Vec<clip::Model>> {
+        let clips = clip::Entity::find()
+            .filter(clip::Column::DuplicateOf.is_not_null())
+            .all(&self.db)
+            .await?;
         Ok(clips)
     }
 
@@ -58,16 +234,12 @@ impl ClipData {
         Ok(clip)
     }
 
-    pub async fn delete_clip_with_playlist_items(
-        &self,
-        user_id: i64,
-        uuid: Uuid,
-    ) -> anyhow::Result<()> {
+    /// 永久删除切片及其播放列表项
+    pub async fn delete_clip_with_playlist_items(&self, uuid: Uuid) -> anyhow::Result<()> {
         let tx = self.db.begin().await?;
 
         let clip = clip::Entity::find()
             .filter(clip::Column::Uuid.eq(uuid))
-            .filter(clip::Column::UserId.eq(user_id))
             .one(&tx)
             .await?;
 
@@ -85,28 +257,301 @@ impl ClipData {
         // 删除播放列表项并重新排序
         for item in playlist_items {
             let playlist_id = item.playlist_id;
-            let item_model = item.into_active_model();
-            item_model.delete(&tx).await?;
-
-            // 重新排序剩余的播放列表项
-            let mut items = playlist_item::Entity::find()
-                .filter(playlist_item::Column::PlaylistId.eq(playlist_id))
-                .order_by(playlist_item::Column::Position, Order::Asc)
-                .all(&tx)
-                .await?;
-
-            for (index, item) in items.iter_mut().enumerate() {
-                if item.position != index as i64 {
-                    let mut model = item.clone().into_active_model();
-                    model.position = Set(index as i64);
-                    model.update(&tx).await?;
-                }
-            }
+            item.into_active_model().delete(&tx).await?;
+            compact_positions(&tx, playlist_id).await?;
         }
 
+        trashed_playlist_item::Entity::delete_many()
+            .filter(trashed_playlist_item::Column::ClipId.eq(clip.id))
+            .exec(&tx)
+            .await?;
+
+        // 指向该切片的重复标记已无意义
+        clip::Entity::update_many()
+            .col_expr(clip::Column::DuplicateOf, Expr::value(Option::<Uuid>::None))
+            .filter(clip::Column::DuplicateOf.eq(clip.uuid))
+            .exec(&tx)
+            .await?;
+
         // 删除clip
         clip.into_active_model().delete(&tx).await?;
         tx.commit().await?;
         Ok(())
     }
+
+    /// 移入回收站：从所有播放列表中移除并记下原来的位置
+    pub async fn trash_clip(&self, clip: clip::Model) -> anyhow::Result<clip::Model> {
+        let tx = self.db.begin().await?;
+
+        let playlist_items = playlist_item::Entity::find()
+            .filter(playlist_item::Column::ClipUuid.eq(clip.uuid))
+            .all(&tx)
+            .await?;
+        for item in playlist_items {
+            trashed_playlist_item::ActiveModel {
+                clip_id: Set(clip.id),
+                playlist_id: Set(item.playlist_id),
+                position: Set(item.position),
+                ..Default::default()
+            }
+            .insert(&tx)
+            .await?;
+            let playlist_id = item.playlist_id;
+            item.into_active_model().delete(&tx).await?;
+            compact_positions(&tx, playlist_id).await?;
+        }
+
+        let mut active = clip.into_active_model();
+        active.deleted_at = Set(Some(chrono::Utc::now()));
+        let clip = active.update(&tx).await?;
+        tx.commit().await?;
+        Ok(clip)
+    }
+
+    /// 从回收站恢复，放回原来的播放列表位置，列表变短时放到末尾
+    pub async fn restore_clip(&self, clip: clip::Model) -> anyhow::Result<clip::Model> {
+        let tx = self.db.begin().await?;
+
+        let trashed = trashed_playlist_item::Entity::find()
+            .filter(trashed_playlist_item::Column::ClipId.eq(clip.id))
+            .order_by(trashed_playlist_item::Column::Position, Order::Asc)
+            .all(&tx)
+            .await?;
+        let now: DateTimeWithTimeZone = chrono::Utc::now().into();
+        for item in trashed {
+            let exists = playlist_item::Entity::find()
+                .filter(playlist_item::Column::PlaylistId.eq(item.playlist_id))
+                .filter(playlist_item::Column::ClipUuid.eq(clip.uuid))
+                .one(&tx)
+                .await?
+                .is_some();
+            if exists {
+                continue;
+            }
+            let count = playlist_item::Entity::find()
+                .filter(playlist_item::Column::PlaylistId.eq(item.playlist_id))
+                .count(&tx)
+                .await? as i64;
+            let position = item.position.min(count);
+            playlist_item::Entity::update_many()
+                .col_expr(
+                    playlist_item::Column::Position,
+                    Expr::col(playlist_item::Column::Position).add(1),
+                )
+                .filter(playlist_item::Column::PlaylistId.eq(item.playlist_id))
+                .filter(playlist_item::Column::Position.gte(position))
+                .exec(&tx)
+                .await?;
+            playlist_item::ActiveModel {
+                playlist_id: Set(item.playlist_id),
+                clip_uuid: Set(clip.uuid),
+                position: Set(position),
+                created_at: Set(now),
+                ..Default::default()
+            }
+            .insert(&tx)
+            .await?;
+        }
+        trashed_playlist_item::Entity::delete_many()
+            .filter(trashed_playlist_item::Column::ClipId.eq(clip.id))
+            .exec(&tx)
+            .await?;
+
+        let mut active = clip.into_active_model();
+        active.deleted_at = Set(None);
+        let clip = active.update(&tx).await?;
+        tx.commit().await?;
+        Ok(clip)
+    }
+
+    /// 在 `before` 之前移入回收站的切片
+    pub async fn list_trashed_before(
+        &self,
+        before: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<clip::Model>> {
+        let clips = clip::Entity::find()
+            .filter(clip::Column::DeletedAt.lt(before))
+            .all(&self.db)
+            .await?;
+        Ok(clips)
+    }
+
+    /// 已处理完成但没有记录时长的切片，时长是后来才开始记录的
+    pub async fn list_processed_without_duration(&self) -> anyhow::Result<Vec<clip::Model>> {
+        let clips = clip::Entity::find()
+            .filter(clip::Column::DurationMs.is_null())
+            .filter(clip::Column::Status.is_in([
+                clip::Status::Reviewing,
+                clip::Status::Reviewed,
+                clip::Status::Rejected,
+            ]))
+            .all(&self.db)
+            .await?;
+        Ok(clips)
+    }
+
+    /// 把 `from` 合并进 `into`：播放列表项原位改指向 `into`，然后删除 `from`。
+    /// 回收站中的切片不能作为 `into`
+    pub async fn merge_clips(&self, from: Uuid, into: Uuid) -> anyhow::Result<Option<clip::Model>> {
+        let tx = self.db.begin().await?;
+
+        let from_clip = clip::Entity::find()
+            .filter(clip::Column::Uuid.eq(from))
+            .one(&tx)
+            .await?;
+        let into_clip = clip::Entity::find()
+            .filter(clip::Column::Uuid.eq(into))
+            .one(&tx)
+            .await?;
+        let (Some(from_clip), Some(into_clip)) = (from_clip, into_clip) else {
+            return Ok(None);
+        };
+        if into_clip.deleted_at.is_some() {
+            anyhow::bail!("Cannot merge into clip {} in the trash", into);
+        }
+
+        // 已包含 `into` 的播放列表直接去掉 `from`，避免同一切片出现两次
+        let into_playlists: Vec<i64> = playlist_item::Entity::find()
+            .select_only()
+            .column(playlist_item::Column::PlaylistId)
+            .filter(playlist_item::Column::ClipUuid.eq(into))
+            .into_tuple()
+            .all(&tx)
+            .await?;
+        let duplicates = playlist_item::Entity::find()
+            .filter(playlist_item::Column::ClipUuid.eq(from))
+            .filter(playlist_item::Column::PlaylistId.is_in(into_playlists))
+            .all(&tx)
+            .await?;
+        for item in duplicates {
+            let playlist_id = item.playlist_id;
+            item.into_active_model().delete(&tx).await?;
+            compact_positions(&tx, playlist_id).await?;
+        }
+
+        playlist_item::Entity::update_many()
+            .col_expr(playlist_item::Column::ClipUuid, Expr::value(into))
+            .filter(playlist_item::Column::ClipUuid.eq(from))
+            .exec(&tx)
+            .await?;
+
+        // 其他指向被合并切片的重复标记改指向保留的切片
+        clip::Entity::update_many()
+            .col_expr(clip::Column::DuplicateOf, Expr::value(into))
+            .filter(clip::Column::DuplicateOf.eq(from))
+            .filter(clip::Column::Uuid.ne(into))
+            .exec(&tx)
+            .await?;
+
+        from_clip.into_active_model().delete(&tx).await?;
+
+        let into_clip = if into_clip.duplicate_of == Some(from) {
+            let mut active = into_clip.into_active_model();
+            active.duplicate_of = Set(None);
+            active.update(&tx).await?
+        } else {
+            into_clip
+        };
+
+        tx.commit().await?;
+        Ok(Some(into_clip))
+    }
+
+    pub async fn create_version(
+        &self,
+        version: clip_version::ActiveModel,
+    ) -> anyhow::Result<clip_version::Model> {
+        let version = version.insert(&self.db).await?;
+        Ok(version)
+    }
+
+    /// 更新切片状态并写入一条审核记录
+    pub async fn update_clip_with_review(
+        &self,
+        clip: clip::ActiveModel,
+        review: clip_review::ActiveModel,
+    ) -> anyhow::Result<clip::Model> {
+        let tx = self.db.begin().await?;
+        let clip = clip.update(&tx).await?;
+        review.insert(&tx).await?;
+        tx.commit().await?;
+        Ok(clip)
+    }
+
+    pub async fn get_owner(&self, clip: &clip::Model) -> anyhow::Result<Option<user::Model>> {
+        let owner = clip.find_related(user::Entity).one(&self.db).await?;
+        Ok(owner)
+    }
+
+    /// 切片的审核记录和对应的用户，按时间先后排列
+    pub async fn list_reviews(
+        &self,
+        clip_id: i64,
+    ) -> anyhow::Result<Vec<(clip_review::Model, Option<user::Model>)>> {
+        let reviews = clip_review::Entity::find()
+            .filter(clip_review::Column::ClipId.eq(clip_id))
+            .find_also_related(user::Entity)
+            .order_by(clip_review::Column::Id, Order::Asc)
+            .all(&self.db)
+            .await?;
+        Ok(reviews)
+    }
+
+    /// 切片的历史版本，新的在前
+    pub async fn list_versions(&self, clip_id: i64) -> anyhow::Result<Vec<clip_version::Model>> {
+        let versions = clip_version::Entity::find()
+            .filter(clip_version::Column::ClipId.eq(clip_id))
+            .order_by(clip_version::Column::Id, Order::Desc)
+            .all(&self.db)
+            .await?;
+        Ok(versions)
+    }
+
+    pub async fn get_version(
+        &self,
+        clip_id: i64,
+        id: i64,
+    ) -> anyhow::Result<Option<clip_version::Model>> {
+        let version = clip_version::Entity::find_by_id(id)
+            .filter(clip_version::Column::ClipId.eq(clip_id))
+            .one(&self.db)
+            .await?;
+        Ok(version)
+    }
+
+    pub async fn delete_version(&self, id: i64) -> anyhow::Result<()> {
+        clip_version::Entity::delete_by_id(id)
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+}
+
+/// 删除播放列表项后让剩余项的位置保持连续
+async fn compact_positions(db: &impl ConnectionTrait, playlist_id: i64) -> anyhow::Result<()> {
+    let items = playlist_item::Entity::find()
+        .filter(playlist_item::Column::PlaylistId.eq(playlist_id))
+        .order_by(playlist_item::Column::Position, Order::Asc)
+        .all(db)
+        .await?;
+    for (index, item) in items.into_iter().enumerate() {
+        if item.position != index as i64 {
+            let mut model = item.into_active_model();
+            model.position = Set(index as i64);
+            model.update(db).await?;
+        }
+    }
+    Ok(())
+}
+
+/// 转义 LIKE 中的通配符，反斜杠作为转义字符
+fn escape_like(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
 }
@@ -1,7 +1,11 @@
+pub mod catalog;
 pub mod clip;
 pub mod playlist;
+pub mod tag;
 pub mod user;
 
-pub use clip::ClipData;
+pub use catalog::{CatalogData, CatalogEntry, CatalogKind};
+pub use clip::{ClipData, ClipQuery};
 pub use playlist::PlaylistData;
+pub use tag::TagData;
 pub use user::UserData;
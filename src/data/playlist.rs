@@ -3,7 +3,7 @@ use sea_orm::prelude::*;
 use sea_orm::{IntoActiveModel, Order, QueryOrder, Set, TransactionTrait};
 use uuid::Uuid;
 
-use crate::core::entity::{clip, playlist, playlist_item};
+use crate::core::entity::{clip, playlist, playlist_item, playlist_share, user};
 
 #[derive(Clone)]
 pub struct PlaylistData {
@@ -32,17 +32,91 @@ impl PlaylistData {
         Ok(playlists)
     }
 
-    pub async fn get_playlist(
+    pub async fn get_playlist_by_id(&self, id: i64) -> anyhow::Result<Option<playlist::Model>> {
+        let playlist = playlist::Entity::find_by_id(id).one(&self.db).await?;
+        Ok(playlist)
+    }
+
+    pub async fn get_shared_playlists(
         &self,
         user_id: i64,
-        id: i64,
-    ) -> anyhow::Result<Option<playlist::Model>> {
-        let playlist = playlist::Entity::find()
-            .filter(playlist::Column::Id.eq(id))
-            .filter(playlist::Column::UserId.eq(user_id))
+    ) -> anyhow::Result<Vec<(playlist_share::Model, Option<playlist::Model>)>> {
+        let shares = playlist_share::Entity::find()
+            .filter(playlist_share::Column::UserId.eq(user_id))
+            .order_by(playlist_share::Column::CreatedAt, Order::Desc)
+            .find_also_related(playlist::Entity)
+            .all(&self.db)
+            .await?;
+        Ok(shares)
+    }
+
+    pub async fn get_share(
+        &self,
+        playlist_id: i64,
+        user_id: i64,
+    ) -> anyhow::Result<Option<playlist_share::Model>> {
+        let share = playlist_share::Entity::find()
+            .filter(playlist_share::Column::PlaylistId.eq(playlist_id))
+            .filter(playlist_share::Column::UserId.eq(user_id))
             .one(&self.db)
             .await?;
-        Ok(playlist)
+        Ok(share)
+    }
+
+    pub async fn list_shares(
+        &self,
+        playlist_id: i64,
+    ) -> anyhow::Result<Vec<(playlist_share::Model, Option<user::Model>)>> {
+        let shares = playlist_share::Entity::find()
+            .filter(playlist_share::Column::PlaylistId.eq(playlist_id))
+            .order_by(playlist_share::Column::CreatedAt, Order::Asc)
+            .find_also_related(user::Entity)
+            .all(&self.db)
+            .await?;
+        Ok(shares)
+    }
+
+    pub async fn save_share(
+        &self,
+        playlist_id: i64,
+        user_id: i64,
+        role: playlist_share::Role,
+    ) -> anyhow::Result<playlist_share::Model> {
+        let share = match self.get_share(playlist_id, user_id).await? {
+            Some(share) => {
+                let mut model = share.into_active_model();
+                model.role = Set(role);
+                model.update(&self.db).await?
+            }
+            None => {
+                let model = playlist_share::ActiveModel {
+                    playlist_id: Set(playlist_id),
+                    user_id: Set(user_id),
+                    role: Set(role),
+                    created_at: Set(chrono::Utc::now().into()),
+                    ..Default::default()
+                };
+                model.insert(&self.db).await?
+            }
+        };
+        Ok(share)
+    }
+
+    pub async fn delete_share(&self, playlist_id: i64, user_id: i64) -> anyhow::Result<()> {
+        playlist_share::Entity::delete_many()
+            .filter(playlist_share::Column::PlaylistId.eq(playlist_id))
+            .filter(playlist_share::Column::UserId.eq(user_id))
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_clip(&self, clip_uuid: Uuid) -> anyhow::Result<Option<clip::Model>> {
+        let clip = clip::Entity::find()
+            .filter(clip::Column::Uuid.eq(clip_uuid))
+            .one(&self.db)
+            .await?;
+        Ok(clip)
     }
 
     pub async fn update_playlist(
@@ -0,0 +1,98 @@
+use sea_orm::prelude::*;
+use sea_orm::{Order, QueryOrder, Set, TransactionTrait};
+
+use crate::core::entity::{clip_tag, tag};
+
+#[derive(Clone)]
+pub struct TagData {
+    db: DatabaseConnection,
+}
+
+impl TagData {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    pub async fn list_tags(&self) -> anyhow::Result<Vec<tag::Model>> {
+        let tags = tag::Entity::find()
+            .order_by(tag::Column::Category, Order::Asc)
+            .order_by(tag::Column::Name, Order::Asc)
+            .all(&self.db)
+            .await?;
+        Ok(tags)
+    }
+
+    pub async fn get_tag(&self, id: i64) -> anyhow::Result<Option<tag::Model>> {
+        let tag = tag::Entity::find_by_id(id).one(&self.db).await?;
+        Ok(tag)
+    }
+
+    pub async fn find_tag_by_name(&self, name: &str) -> anyhow::Result<Option<tag::Model>> {
+        let tag = tag::Entity::find()
+            .filter(tag::Column::Name.eq(name))
+            .one(&self.db)
+            .await?;
+        Ok(tag)
+    }
+
+    pub async fn find_tags(&self, ids: &[i64]) -> anyhow::Result<Vec<tag::Model>> {
+        let tags = tag::Entity::find()
+            .filter(tag::Column::Id.is_in(ids.iter().copied()))
+            .all(&self.db)
+            .await?;
+        Ok(tags)
+    }
+
+    pub async fn create_tag(&self, tag: tag::ActiveModel) -> anyhow::Result<tag::Model> {
+        let tag = tag.insert(&self.db).await?;
+        Ok(tag)
+    }
+
+    pub async fn update_tag(&self, tag: tag::ActiveModel) -> anyhow::Result<tag::Model> {
+        let tag = tag.update(&self.db).await?;
+        Ok(tag)
+    }
+
+    pub async fn delete_tag(&self, id: i64) -> anyhow::Result<()> {
+        let tx = self.db.begin().await?;
+        clip_tag::Entity::delete_many()
+            .filter(clip_tag::Column::TagId.eq(id))
+            .exec(&tx)
+            .await?;
+        tag::Entity::delete_by_id(id).exec(&tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// 一组切片的标签，返回 (切片 id, 标签)
+    pub async fn list_clip_tags(&self, clip_ids: &[i64]) -> anyhow::Result<Vec<(i64, tag::Model)>> {
+        let tags = clip_tag::Entity::find()
+            .filter(clip_tag::Column::ClipId.is_in(clip_ids.iter().copied()))
+            .find_also_related(tag::Entity)
+            .order_by(tag::Column::Name, Order::Asc)
+            .all(&self.db)
+            .await?;
+        Ok(tags
+            .into_iter()
+            .filter_map(|(ct, tag)| Some((ct.clip_id, tag?)))
+            .collect())
+    }
+
+    pub async fn set_clip_tags(&self, clip_id: i64, tag_ids: &[i64]) -> anyhow::Result<()> {
+        let tx = self.db.begin().await?;
+        clip_tag::Entity::delete_many()
+            .filter(clip_tag::Column::ClipId.eq(clip_id))
+            .exec(&tx)
+            .await?;
+        if !tag_ids.is_empty() {
+            clip_tag::Entity::insert_many(tag_ids.iter().map(|&tag_id| clip_tag::ActiveModel {
+                clip_id: Set(clip_id),
+                tag_id: Set(tag_id),
+            }))
+            .exec(&tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
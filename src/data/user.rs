@@ -61,6 +61,7 @@ impl UserData {
                     is_disabled: Set(false),      // 默认不禁用
                     created_at: Set(now.clone()),
                     updated_at: Set(now),
+                    ..Default::default()
                 };
 
                 let new_user = user_active
@@ -129,6 +130,7 @@ impl UserData {
         is_admin: Option<bool>,
         can_stream: Option<bool>,
         is_disabled: Option<bool>,
+        is_trusted: Option<bool>,
     ) -> anyhow::Result<user::Model> {
         let user = user::Entity::find_by_id(user_id)
             .one(&self.db)
@@ -148,6 +150,9 @@ impl UserData {
         if let Some(disabled) = is_disabled {
             user_active.is_disabled = Set(disabled);
         }
+        if let Some(trusted) = is_trusted {
+            user_active.is_trusted = Set(trusted);
+        }
         user_active.updated_at = Set(now);
 
         let updated_user = user_active
@@ -156,4 +161,30 @@ impl UserData {
             .map_err(|e| anyhow::anyhow!("Failed to update user permissions in database: {}", e))?;
         Ok(updated_user)
     }
+
+    pub async fn update_user_quota(
+        &self,
+        user_id: i64,
+        quota_bytes: Option<i64>,
+        quota_clips: Option<i64>,
+        quota_daily_uploads: Option<i64>,
+    ) -> anyhow::Result<user::Model> {
+        let user = user::Entity::find_by_id(user_id)
+            .one(&self.db)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to query user from database: {}", e))?
+            .ok_or_else(|| anyhow::anyhow!("User not found"))?;
+
+        let mut user_active = user.into_active_model();
+        user_active.quota_bytes = Set(quota_bytes);
+        user_active.quota_clips = Set(quota_clips);
+        user_active.quota_daily_uploads = Set(quota_daily_uploads);
+        user_active.updated_at = Set(chrono::Utc::now().into());
+
+        let updated_user = user_active
+            .update(&self.db)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to update user quota in database: {}", e))?;
+        Ok(updated_user)
+    }
 }
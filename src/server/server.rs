@@ -1,9 +1,13 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Context, anyhow};
-use apalis::prelude::{MemoryStorage, WorkerBuilder, WorkerBuilderExt, WorkerFactoryFn};
+use apalis::layers::retry::HasherRng;
+use apalis::layers::retry::RetryPolicy;
+use apalis::layers::retry::backoff::{ExponentialBackoffMaker, MakeBackoff};
+use apalis::prelude::{WorkerBuilder, WorkerBuilderExt, WorkerFactoryFn};
 use axum::extract::{DefaultBodyLimit, State};
-use axum::routing::{delete, get, post};
+use axum::routing::{delete, get, patch, post};
 use axum::{Json, Router, middleware};
 use migration::*;
 use sea_orm::Database;
@@ -14,10 +18,12 @@ use tower_http::cors::CorsLayer;
 use crate::api;
 use crate::config::Config;
 use crate::core::jwt::DEFAULT_SECRET_KEY;
+use crate::core::queue::JobQueue;
 use crate::core::storage::Storage;
-use crate::data::{ClipData, PlaylistData, UserData};
+use crate::data::{CatalogData, ClipData, PlaylistData, TagData, UserData};
 use crate::server::auth;
-use crate::service::{clip::process_clip, *};
+use crate::service::clip::{FileNamePattern, process_clip};
+use crate::service::*;
 
 pub async fn run(config: Config) -> anyhow::Result<()> {
     let host = config.host.clone();
@@ -47,12 +53,22 @@ pub async fn run(config: Config) -> anyhow::Result<()> {
         )
     })?;
 
-    let queue = MemoryStorage::new();
+    let queue = JobQueue::new(&db)
+        .await
+        .context("Failed to set up processing queue")?;
+    // 重新排队上次退出时仍在处理中的任务
+    queue
+        .requeue_orphaned()
+        .await
+        .map_err(|e| tracing::warn!("Failed to requeue orphaned jobs: {}", e))
+        .ok();
 
     // Create data layer instances
     let user_data = UserData::new(db.clone());
     let clip_data = ClipData::new(db.clone());
     let playlist_data = PlaylistData::new(db.clone());
+    let tag_data = TagData::new(db.clone());
+    let catalog_data = CatalogData::new(db.clone());
 
     // Get JWT secret from config or use default
     let jwt_secret = config.jwt_secret.clone().unwrap_or_else(|| {
@@ -65,13 +81,36 @@ pub async fn run(config: Config) -> anyhow::Result<()> {
     // Create service layer instances with data layer dependencies
     let user_svc = Arc::new(UserService::new(user_data, jwt_secret));
     let playlist_svc = Arc::new(PlaylistService::new(playlist_data));
+    let tag_svc = Arc::new(TagService::new(tag_data, clip_data.clone()));
+    let catalog_svc = Arc::new(CatalogService::new(catalog_data.clone()));
     let clip_svc = Arc::new(ClipService::new(
         tmp_dir,
         clip_data,
+        catalog_data,
         storage.clone(),
         queue.clone(),
+        &config,
     ));
 
+    if config.inbox.path.is_some() {
+        let pattern = config
+            .upload
+            .file_name_pattern
+            .as_deref()
+            .map(FileNamePattern::new)
+            .transpose()
+            .context("Invalid upload file name pattern")?;
+        let inbox_svc = Arc::new(InboxService::new(
+            clip_svc.clone(),
+            user_svc.clone(),
+            config.inbox.clone(),
+            pattern,
+        ));
+        tokio::spawn(inbox_svc.run());
+    }
+    tokio::spawn(clip_svc.clone().run_trash_purge());
+    tokio::spawn(clip_svc.clone().backfill_durations());
+
     let wbi = Arc::new(Mutex::new(
         bilive::wbi::WBI::new().await.map_err(|e| anyhow!(e))?,
     ));
@@ -84,21 +123,40 @@ pub async fn run(config: Config) -> anyhow::Result<()> {
         wbi.clone(),
     ));
 
+    let backoff = ExponentialBackoffMaker::new(
+        Duration::from_secs(config.process.retry_min_delay),
+        Duration::from_secs(config.process.retry_max_delay),
+        1.0,
+        HasherRng::default(),
+    )
+    .context("Invalid processing retry delays")?
+    .make_backoff();
     let worker = WorkerBuilder::new("processer")
+        .retry(RetryPolicy::retries(config.process.max_retries).with_backoff(backoff))
         .concurrency(2)
-        .data(clip_svc.clone())
-        .backend(queue)
-        .build_fn(process_clip);
-
-    tokio::spawn(async move {
-        worker.run().await;
-    });
+        .data(clip_svc.clone());
+    match queue {
+        JobQueue::Sqlite(backend) => {
+            let worker = worker.backend(backend).build_fn(process_clip);
+            tokio::spawn(async move { worker.run().await });
+        }
+        JobQueue::Postgres(backend) => {
+            let worker = worker.backend(backend).build_fn(process_clip);
+            tokio::spawn(async move { worker.run().await });
+        }
+        JobQueue::MySql(backend) => {
+            let worker = worker.backend(backend).build_fn(process_clip);
+            tokio::spawn(async move { worker.run().await });
+        }
+    }
 
     let state = Arc::new(AppState {
         clip_svc,
         user_svc,
         playlist_svc,
         live_svc,
+        tag_svc,
+        catalog_svc,
         config: config.clone(),
     });
     let cors = CorsLayer::new()
@@ -123,15 +181,56 @@ pub async fn run(config: Config) -> anyhow::Result<()> {
         )
         .route("/user/login/qrcode", get(api::user::get_login_qrcode))
         .route("/user/login/check", get(api::user::check_bilibili_login))
-        .route("/clip/{uuid}/preview", get(api::clip::preview_clip)); // check permissions internally
+        .route("/clip/{uuid}/preview", get(api::clip::preview_clip)) // check permissions internally
+        .route("/clip/{uuid}/poster.jpg", get(api::clip::get_clip_poster))
+        .route("/clip/{uuid}/sprite.jpg", get(api::clip::get_clip_sprite))
+        .route(
+            "/clip/{uuid}/sprite.vtt",
+            get(api::clip::get_clip_sprite_vtt),
+        );
 
     let protected_routes = Router::new()
         .route("/clips", get(api::clip::list_clip))
+        .route("/clips/reviewed", get(api::clip::list_reviewed_clip))
+        .route("/clips/import", post(api::clip::import_clip))
         .route("/upload", post(api::clip::upload))
+        .route(
+            "/upload/batch",
+            post(api::clip::batch_upload).layer(DefaultBodyLimit::max(
+                config.max_file_size.as_u64() as usize * config.upload.max_batch_files.max(1),
+            )),
+        )
+        .route("/uploads", post(api::clip::create_upload))
+        .route("/uploads/{id}", get(api::clip::get_upload))
+        .route("/uploads/{id}", patch(api::clip::append_upload))
+        .route("/uploads/{id}", delete(api::clip::cancel_upload))
+        .route("/uploads/{id}/finalize", post(api::clip::finalize_upload))
+        .route("/clip/{uuid}/file", post(api::clip::replace_clip_file))
         .layer(DefaultBodyLimit::max(config.max_file_size.as_u64() as usize))
         .route(
             "/clip/{uuid}",
-            post(api::clip::update_clip).delete(api::clip::delete_clip),
+            get(api::clip::get_clip)
+                .post(api::clip::update_clip)
+                .delete(api::clip::delete_clip),
+        )
+        .route("/clip/{uuid}/reprocess", post(api::clip::reprocess_clip))
+        .route("/clip/{uuid}/edit", post(api::clip::edit_clip))
+        .route("/clip/{uuid}/resubmit", post(api::clip::resubmit_clip))
+        .route("/clip/{uuid}/restore", post(api::clip::restore_clip))
+        .route("/clip/{uuid}/reviews", get(api::clip::list_clip_reviews))
+        .route("/clip/{uuid}/tags", post(api::tag::set_clip_tags))
+        .route("/tags", get(api::tag::list_tags))
+        .route("/tags", post(api::tag::create_tag))
+        .route("/vups", get(api::catalog::list_vups))
+        .route("/songs", get(api::catalog::list_songs))
+        .route("/clip/{uuid}/versions", get(api::clip::list_clip_versions))
+        .route(
+            "/clip/{uuid}/versions/{id}/rollback",
+            post(api::clip::rollback_clip),
+        )
+        .route(
+            "/clip/{uuid}/trim/accept",
+            post(api::clip::accept_suggested_trim),
         )
         // 用户信息接口
         .route("/user/me", get(api::admin::get_current_user))
@@ -142,6 +241,10 @@ pub async fn run(config: Config) -> anyhow::Result<()> {
         .route("/playlists", get(api::playlist::list_playlists))
         .route("/playlists", post(api::playlist::create_playlist))
         .route("/playlists/active", get(api::playlist::get_active_playlist))
+        .route(
+            "/playlists/shared",
+            get(api::playlist::list_shared_playlists),
+        )
         .route("/playlists/{id}", get(api::playlist::get_playlist_by_id))
         .route("/playlists/{id}", post(api::playlist::update_playlist))
         .route("/playlists/{id}", delete(api::playlist::delete_playlist))
@@ -149,6 +252,14 @@ pub async fn run(config: Config) -> anyhow::Result<()> {
             "/playlists/{id}/active",
             post(api::playlist::set_active_playlist).delete(api::playlist::unset_active_playlist),
         )
+        .route(
+            "/playlists/{id}/shares",
+            get(api::playlist::list_playlist_shares).post(api::playlist::share_playlist),
+        )
+        .route(
+            "/playlists/{id}/shares/{user_id}",
+            delete(api::playlist::unshare_playlist),
+        )
         .route(
             "/playlists/{id}/items",
             get(api::playlist::get_playlist_items),
@@ -177,11 +288,28 @@ pub async fn run(config: Config) -> anyhow::Result<()> {
 
     let admin_routes = Router::new()
         .route("/clip/{uuid}/reviewed", post(api::clip::reviewed_clip))
+        .route("/clip/{uuid}/review", post(api::clip::review_clip))
+        .route("/admin/reviews/queue", get(api::clip::review_queue))
         .route("/admin/users", get(api::admin::list_all_users))
+        .route(
+            "/admin/clips/duplicates",
+            get(api::clip::list_duplicate_clips),
+        )
+        .route("/admin/clips/{uuid}/merge", post(api::clip::merge_clip))
+        .route("/admin/tags/{id}", post(api::tag::update_tag))
+        .route("/admin/tags/{id}", delete(api::tag::delete_tag))
+        .route("/admin/vups/{id}", post(api::catalog::update_vup))
+        .route("/admin/vups/{id}/merge", post(api::catalog::merge_vup))
+        .route("/admin/songs/{id}", post(api::catalog::update_song))
+        .route("/admin/songs/{id}/merge", post(api::catalog::merge_song))
         .route(
             "/admin/users/{user_id}/permissions",
             post(api::admin::update_user_permissions),
         )
+        .route(
+            "/admin/users/{user_id}/quota",
+            post(api::admin::update_user_quota),
+        )
         .layer(middleware::from_fn_with_state(
             state.clone(),
             auth::auth_admin,
@@ -210,5 +338,7 @@ pub(crate) struct AppState {
     pub(crate) user_svc: Arc<UserService>,
     pub(crate) playlist_svc: Arc<PlaylistService>,
     pub(crate) live_svc: Arc<LiveService>,
+    pub(crate) tag_svc: Arc<TagService>,
+    pub(crate) catalog_svc: Arc<CatalogService>,
     pub(crate) config: Config,
 }
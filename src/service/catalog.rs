@@ -0,0 +1,191 @@
+use tracing::{debug, trace, warn};
+
+use crate::data::{CatalogData, CatalogEntry, CatalogKind};
+use crate::service::errors::Error;
+
+const MAX_NAME_LEN: usize = 128;
+const MAX_ALIASES: usize = 32;
+
+/// 管理员修改条目时提交的内容，`aliases` 不必包含正式名称
+#[derive(Clone, Debug, Default)]
+pub struct CatalogUpdate {
+    pub name: String,
+    pub image_url: Option<String>,
+    pub link: Option<String>,
+    pub aliases: Vec<String>,
+}
+
+pub struct CatalogService {
+    catalog_data: CatalogData,
+}
+
+impl CatalogService {
+    pub fn new(catalog_data: CatalogData) -> Self {
+        Self { catalog_data }
+    }
+
+    pub async fn list(&self, kind: CatalogKind) -> anyhow::Result<Vec<CatalogEntry>> {
+        self.catalog_data.list_catalog(kind).await
+    }
+
+    async fn get(&self, kind: CatalogKind, id: i64) -> anyhow::Result<CatalogEntry> {
+        self.catalog_data
+            .get_catalog_entry(kind, id)
+            .await?
+            .ok_or_else(|| Error::NotFound(format!("{kind:?} not found")).into())
+    }
+
+    pub async fn update(
+        &self,
+        kind: CatalogKind,
+        id: i64,
+        update: CatalogUpdate,
+    ) -> anyhow::Result<CatalogEntry> {
+        let mut entry = self.get(kind, id).await?;
+        let name = update.name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+            return Err(
+                Error::BadRequest(format!("Name must be 1 to {MAX_NAME_LEN} characters")).into(),
+            );
+        }
+        if update.aliases.len() > MAX_ALIASES {
+            return Err(
+                Error::BadRequest(format!("At most {MAX_ALIASES} aliases are allowed")).into(),
+            );
+        }
+
+        // 正式名称总是自身的别名，规范化后相同的写法只保留第一个
+        let mut aliases: Vec<(String, String)> = Vec::new();
+        for alias in std::iter::once(name).chain(update.aliases.iter().map(|a| a.trim())) {
+            let key = normalize_key(alias);
+            if key.is_empty() || aliases.iter().any(|(_, k)| *k == key) {
+                continue;
+            }
+            if alias.chars().count() > MAX_NAME_LEN {
+                return Err(Error::BadRequest(format!(
+                    "Alias must be at most {MAX_NAME_LEN} characters"
+                ))
+                .into());
+            }
+            let owner = self.catalog_data.find_catalog_by_key(kind, &key).await?;
+            if let Some((other, other_name)) = owner.filter(|(other, _)| *other != id) {
+                return Err(Error::Conflict(format!(
+                    "Alias {alias} already belongs to {other_name} ({other}), merge them instead"
+                ))
+                .into());
+            }
+            aliases.push((alias.to_string(), key));
+        }
+        if aliases.is_empty() {
+            return Err(Error::BadRequest("Name must contain letters".to_string()).into());
+        }
+
+        entry.name = name.to_string();
+        entry.image_url = normalize_url(update.image_url)?;
+        entry.link = normalize_url(update.link)?;
+        trace!("Updating {:?} {} to {}", kind, id, entry.name);
+        self.catalog_data
+            .update_catalog_entry(kind, &entry, &aliases)
+            .await?;
+        entry.aliases = aliases.into_iter().map(|(alias, _)| alias).collect();
+        Ok(entry)
+    }
+
+    /// 把 `from` 合并进 `into`，返回合并后的条目
+    pub async fn merge(
+        &self,
+        kind: CatalogKind,
+        from: i64,
+        into: i64,
+    ) -> anyhow::Result<CatalogEntry> {
+        if from == into {
+            return Err(Error::BadRequest("Cannot merge an entry into itself".to_string()).into());
+        }
+        self.get(kind, from).await?;
+        let target = self.get(kind, into).await?;
+        self.catalog_data
+            .merge_catalog_entries(kind, from, &target)
+            .await?;
+        debug!("Merged {:?} {} into {}", kind, from, into);
+        self.catalog_data
+            .list_catalog(kind)
+            .await?
+            .into_iter()
+            .find(|e| e.id == into)
+            .ok_or_else(|| Error::NotFound(format!("{kind:?} not found")).into())
+    }
+}
+
+/// 把切片上填写的名称对应到目录条目，返回 (条目 id, 正式名称)，没有匹配时新建条目
+pub(crate) async fn resolve(
+    catalog_data: &CatalogData,
+    kind: CatalogKind,
+    name: &str,
+) -> anyhow::Result<(Option<i64>, String)> {
+    let name = name.trim();
+    let key = normalize_key(name);
+    if key.is_empty() {
+        return Ok((None, name.to_string()));
+    }
+    if name.chars().count() > MAX_NAME_LEN || key.chars().count() > MAX_NAME_LEN {
+        return Err(Error::BadRequest(format!(
+            "{kind:?} name must be at most {MAX_NAME_LEN} characters"
+        ))
+        .into());
+    }
+    if let Some((id, canonical)) = catalog_data.find_catalog_by_key(kind, &key).await? {
+        return Ok((Some(id), canonical));
+    }
+    match catalog_data.create_catalog_entry(kind, name, &key).await {
+        Ok(id) => {
+            debug!("Created {:?} {} ({})", kind, name, id);
+            Ok((Some(id), name.to_string()))
+        }
+        // 并发上传时可能已被别的请求创建
+        Err(e) => match catalog_data.find_catalog_by_key(kind, &key).await? {
+            Some((id, canonical)) => Ok((Some(id), canonical)),
+            None => {
+                warn!("Failed to create {:?} {}: {}", kind, name, e);
+                Err(e)
+            }
+        },
+    }
+}
+
+/// 用于匹配同一名称的不同写法：全角转半角，去掉空白和常见分隔符，忽略大小写
+pub fn normalize_key(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            _ => c,
+        })
+        .filter(|c| !c.is_whitespace() && !"·・_-.,'\"".contains(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn normalize_url(url: Option<String>) -> anyhow::Result<Option<String>> {
+    let url = url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
+    let invalid = |u: &&String| !(u.starts_with("http://") || u.starts_with("https://"));
+    if let Some(u) = url.as_ref().filter(invalid) {
+        return Err(Error::BadRequest(format!("Invalid URL {u}")).into());
+    }
+    Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_key() {
+        assert_eq!(normalize_key("Hoshimachi Suisei"), "hoshimachisuisei");
+        assert_eq!(
+            normalize_key("ＨＯＳＨＩＭＡＣＨＩ　Suisei"),
+            "hoshimachisuisei"
+        );
+        assert_eq!(normalize_key("hoshimachi-suisei"), "hoshimachisuisei");
+        assert_eq!(normalize_key("星街・すいせい"), "星街すいせい");
+        assert_eq!(normalize_key(" - "), "");
+    }
+}
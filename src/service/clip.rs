@@ -1,22 +1,32 @@
-use std::path::PathBuf;
-use std::process::Stdio;
+use std::collections::HashSet;
+use std::ffi::{OsStr, OsString};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::anyhow;
-use apalis::prelude::{Data, MemoryStorage, MessageQueue};
+use apalis::prelude::{Data, Error as JobError};
 use regex::Regex;
-use sea_orm::{IntoActiveModel, Set};
+use sea_orm::{IntoActiveModel, Order, Set};
 use serde::{Deserialize, Serialize};
-use tokio::io::AsyncRead;
-use tokio::process::Command;
-use tokio::sync::Mutex;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
 use tokio::{fs::File, io::BufWriter};
-use tracing::{debug, error, trace};
+use tokio_util::io::StreamReader;
+use tracing::{debug, error, info, trace, warn};
 use uuid::Uuid;
 
-use crate::core::entity::{clip, user};
+use crate::config::Config;
+use crate::core::entity::{clip, clip_review, clip_version, user};
+use crate::core::media::{self, LoudnormStats};
+use crate::core::queue::JobQueue;
 use crate::core::storage::Storage;
-use crate::data::ClipData;
+use crate::core::streamer::RtmpStreamerConfig;
+use crate::data::{CatalogData, CatalogKind, ClipData, ClipQuery};
+use crate::service::catalog;
+use crate::service::errors::Error;
+use crate::service::quota::{Quota, QuotaConfig, QuotaUsage};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct StatusResponse {
@@ -29,22 +39,34 @@ pub struct StatusResponse {
 pub struct ClipService {
     tmp_dir: PathBuf,
     clip_data: ClipData,
+    catalog_data: CatalogData,
     storage: Arc<Storage>,
-    queue: Arc<Mutex<MemoryStorage<ProcessJob>>>,
+    queue: JobQueue<ProcessJob>,
+    config: ProcessConfig,
+    stream_config: RtmpStreamerConfig,
+    upload_config: UploadConfig,
+    active_uploads: Arc<std::sync::Mutex<HashSet<Uuid>>>,
 }
 
 impl ClipService {
     pub fn new(
         tmp_dir: PathBuf,
         clip_data: ClipData,
+        catalog_data: CatalogData,
         storage: Arc<Storage>,
-        queue: MemoryStorage<ProcessJob>,
+        queue: JobQueue<ProcessJob>,
+        config: &Config,
     ) -> Self {
         Self {
             tmp_dir,
             clip_data,
+            catalog_data,
             storage,
-            queue: Arc::new(Mutex::new(queue)),
+            queue,
+            config: config.process.clone(),
+            stream_config: config.stream.clone(),
+            upload_config: config.upload.clone(),
+            active_uploads: Default::default(),
         }
     }
 
@@ -52,402 +74,2822 @@ impl ClipService {
         &'a self,
         reader: &'a mut R,
         ext: &'a str,
-    ) -> anyhow::Result<(Uuid, PathBuf)>
+    ) -> anyhow::Result<(Uuid, PathBuf, String)>
     where
         R: AsyncRead + Unpin + ?Sized,
     {
         let uuid = uuid::Uuid::new_v4();
         let file_path = self.tmp_dir.join(format!("{uuid}.{ext}"));
         let mut file = BufWriter::new(File::create(&file_path).await?);
-        tokio::io::copy(reader, &mut file).await?;
-        Ok((uuid, file_path))
+        // 写入的同时计算内容哈希，用于查重
+        let mut hasher = Sha256::new();
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            let n = reader.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+            file.write_all(&buf[..n]).await?;
+        }
+        file.flush().await?;
+        Ok((uuid, file_path, hex::encode(hasher.finalize())))
     }
 
     pub async fn create_clip(
         &self,
-        user_id: i64,
+        user: &user::Model,
         req: clip::Model,
         file: PathBuf,
     ) -> anyhow::Result<clip::Model> {
+        let file_size = tokio::fs::metadata(&file).await?.len();
+        let checked = async {
+            self.check_quota(user, file_size).await?;
+            self.validate_media(&file).await
+        }
+        .await;
+        if let Err(e) = checked {
+            debug!("Rejected upload {}: {}", req.uuid, e);
+            tokio::fs::remove_file(&file).await.ok();
+            return Err(e);
+        }
+
+        let duplicate_of = match req.content_hash {
+            Some(ref hash) => self
+                .clip_data
+                .find_clip_by_hash(hash)
+                .await?
+                .map(|c| c.duplicate_of.unwrap_or(c.uuid)),
+            None => None,
+        };
+        if let Some(original) = duplicate_of {
+            debug!("Clip {} is a duplicate of {}", req.uuid, original);
+        }
+
+        let (vup_id, vup) =
+            catalog::resolve(&self.catalog_data, CatalogKind::Vup, &req.vup).await?;
+        let (song_id, song) =
+            catalog::resolve(&self.catalog_data, CatalogKind::Song, &req.song).await?;
         let clip_active = clip::ActiveModel {
             uuid: Set(req.uuid),
             title: Set(req.title.clone()),
-            vup: Set(req.vup.clone()),
-            song: Set(req.song.clone()),
-            user_id: Set(user_id),
+            vup: Set(vup),
+            song: Set(song),
+            vup_id: Set(vup_id),
+            song_id: Set(song_id),
+            upload_time: Set(chrono::Utc::now()),
+            user_id: Set(user.id),
+            // 处理完成前先按上传的文件计入配额
+            file_size: Set(Some(file_size as i64)),
+            content_hash: Set(req.content_hash),
+            duplicate_of: Set(duplicate_of),
             ..Default::default()
         };
 
         let clip = self.clip_data.create_clip(clip_active).await?;
 
-        let mut queue = self.queue.lock().await;
-        match queue
-            .enqueue(ProcessJob {
-                clip: clip.clone(),
+        match self
+            .queue
+            .push(ProcessJob {
+                clip_uuid: clip.uuid,
                 input_path: file,
+                trim: None,
+                effects: None,
             })
             .await
         {
             Ok(_) => debug!("Clip job enqueued successfully"),
-            Err(_) => error!("Failed to enqueue clip job"),
+            Err(e) => error!("Failed to enqueue clip job: {}", e),
         }
 
         Ok(clip)
     }
 
-    pub async fn process_clip(&self, clip: clip::Model, file: PathBuf) -> anyhow::Result<()> {
-        trace!("Processing clip: {}, path: {}", clip.uuid, file.display());
-        let mut active_clip = clip.clone().into_active_model();
-        active_clip.status = Set(clip::Status::Processing);
-        let clip = self.clip_data.update_clip(active_clip).await?;
-        let output_path = self.tmp_dir.join(format!("{}_processed.mp4", clip.uuid));
-        match self.transcode_and_normalize(&file, &output_path).await {
-            Ok(_) => {
-                tokio::fs::rename(output_path, &file).await?;
-                debug!("Clip {} processed successfully", clip.uuid);
-            }
-            Err(e) => {
-                error!("Failed to normalize clip {}: {}", clip.uuid, e);
-                let mut active_clip = clip.clone().into_active_model();
-                active_clip.status = Set(clip::Status::Failed);
-                self.clip_data
-                    .update_clip(active_clip)
-                    .await
-                    .map_err(|e| {
-                        error!("Failed to update clip status to failed: {}", e);
-                    })
-                    .ok();
-                return Err(e.into());
-            }
-        }
+    pub fn quota(&self, user: &user::Model) -> Quota {
+        self.upload_config.quota.resolve(user)
+    }
 
-        match self
-            .storage
-            .store_file(format!("{}.mp4", clip.uuid.to_string()), &file)
-            .await
+    pub async fn quota_usage(&self, user: &user::Model) -> anyhow::Result<QuotaUsage> {
+        // 回收站中的切片仍占用存储，永久删除后才释放配额
+        let sizes = self.clip_data.list_file_sizes(user.id).await?;
+        // 保留的历史版本文件同样占用存储
+        let versions = self.clip_data.list_version_file_sizes(user.id).await?;
+        let since = chrono::Utc::now() - chrono::Duration::days(1);
+        let daily_uploads = self.clip_data.count_uploads_since(user.id, since).await?;
+        Ok(QuotaUsage {
+            bytes: sizes
+                .iter()
+                .chain(&versions)
+                .flatten()
+                .map(|s| *s as u64)
+                .sum(),
+            clips: sizes.len() as u64,
+            daily_uploads,
+        })
+    }
+
+    /// 检查用户能否再上传一个 `incoming` 字节的切片
+    pub async fn check_quota(&self, user: &user::Model, incoming: u64) -> anyhow::Result<()> {
+        let quota = self.quota(user);
+        if quota.max_bytes.is_none()
+            && quota.max_clips.is_none()
+            && quota.max_daily_uploads.is_none()
         {
-            Ok(_) => {
-                debug!("Clip {} stored successfully", clip.uuid);
-            }
-            Err(e) => {
-                error!("Failed to store clip {}: {}", clip.uuid, e);
-                let mut active_clip = clip.clone().into_active_model();
-                active_clip.status = Set(clip::Status::Failed);
-                self.clip_data
-                    .update_clip(active_clip)
-                    .await
-                    .map_err(|e| {
-                        error!("Failed to update clip status to failed: {}", e);
-                    })
-                    .ok();
-                return Err(e.into());
-            }
+            return Ok(());
         }
+        let usage = self.quota_usage(user).await?;
+        quota
+            .check(&usage, incoming)
+            .map_err(|e| Error::Forbidden(e).into())
+    }
 
-        tokio::fs::remove_file(file)
+    /// 入库前检查上传的文件能否解码以及时长、分辨率是否符合要求
+    async fn validate_media(&self, path: &Path) -> anyhow::Result<media::MediaInfo> {
+        let config = &self.upload_config.validation;
+        let info = media::probe(path)
             .await
-            .map_err(|e| {
-                error!("Failed to remove clip file: {}", e);
-            })
-            .ok();
-
-        let mut active_clip = clip.into_active_model();
-        active_clip.status = Set(clip::Status::Reviewing);
-        self.clip_data
-            .update_clip(active_clip)
+            .map_err(|_| Error::BadRequest("File is not a recognizable media file".to_string()))?;
+        check_media(&info, config).map_err(Error::BadRequest)?;
+        media::decode_check(path, config.decode_check_secs)
             .await
-            .map_err(|e| {
-                error!("Failed to update clip status: {}", e);
-            })
-            .ok();
-
-        Ok(())
+            .map_err(|e| Error::BadRequest(format!("File cannot be decoded: {e}")))?;
+        Ok(info)
     }
 
-    pub async fn list_clips_by_user(&self, user: &user::Model) -> anyhow::Result<Vec<clip::Model>> {
-        trace!("Listing clips for user {}", user.id);
-        let clips = match user.is_admin {
-            true => {
-                debug!("User {} is admin, fetching all clips", user.id);
-                self.clip_data.list_all_clips().await.map_err(|e| {
-                    error!("Failed to fetch all clips: {}", e);
-                    e
-                })?
-            }
-            false => {
-                debug!("Fetching clips for user {}", user.id);
-                self.clip_data
-                    .list_clips_by_user(user.id)
-                    .await
-                    .map_err(|e| {
-                        error!("Failed to fetch clips for user {}: {}", user.id, e);
-                        e
-                    })?
-            }
-        };
-        debug!("Fetched {} clips for user {}", clips.len(), user.id);
-        Ok(clips)
-    }
-
-    pub async fn get_clip_by_uuid(
+    /// 从 HTTP(S) 地址下载媒体文件并按普通上传处理
+    pub async fn import_clip(
         &self,
         user: &user::Model,
-        uuid: Uuid,
-    ) -> anyhow::Result<Option<clip::Model>> {
-        trace!("Fetching clip by UUID: {}", uuid.to_string());
-        self.clip_data.get_clip_by_uuid(user.id, uuid).await
-    }
+        url: &str,
+        max_size: u64,
+        req: clip::Model,
+    ) -> anyhow::Result<clip::Model> {
+        trace!("Importing clip from {} for user {}", url, user.id);
+        self.check_quota(user, 0).await?;
+        let config = &self.upload_config.import;
+        let url =
+            reqwest::Url::parse(url).map_err(|e| Error::BadRequest(format!("Invalid URL: {e}")))?;
+        if !["http", "https"].contains(&url.scheme()) {
+            return Err(Error::BadRequest("Only HTTP(S) URLs can be imported".to_string()).into());
+        }
+        let host = url.host_str().unwrap_or_default();
+        if !config.allowed_hosts.iter().any(|h| h == host) {
+            return Err(Error::Forbidden(format!("Importing from {host} is not allowed")).into());
+        }
 
-    async fn _get_clip_by_id(&self, id: i64) -> anyhow::Result<clip::Model> {
-        trace!("Fetching clip by ID: {}", id);
-        let clip = self
-            .clip_data
-            ._get_clip_by_id(id)
+        // 解析后的地址全部是公网地址才下载，并固定使用这些地址，避免 DNS 重绑定访问内网
+        let port = url.port_or_known_default().unwrap_or(80);
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
             .await
-            .map_err(|e| {
-                error!("Failed to fetch clip by ID {}: {}", id, e);
-                e
-            })?
-            .ok_or_else(|| {
-                error!("Clip not found for user ID: {}", id);
-                anyhow::anyhow!("Clip not found")
-            })?;
-        debug!("Fetched clip: {}", clip.uuid.to_string());
-        Ok(clip)
-    }
+            .map(Iterator::collect)
+            .unwrap_or_default();
+        if addrs.is_empty() || !addrs.iter().all(|a| is_public_ip(a.ip())) {
+            debug!("Rejected import from {}: resolved to {:?}", host, addrs);
+            return Err(Error::BadRequest(IMPORT_FAILED.to_string()).into());
+        }
 
-    pub async fn update_clip(
-        &self,
-        user: &user::Model,
-        req: clip::Model,
-    ) -> anyhow::Result<Option<clip::Model>> {
-        trace!("Updating clip {} for {}", req.uuid.to_string(), user.id);
-        let clip = self.get_clip_by_uuid(&user, req.uuid).await.map_err(|e| {
-            error!("Failed to verify clip ownership: {}", e);
-            anyhow!("Failed to verify clip ownership: {}", e)
+        // 不跟随重定向，否则可以绕过上面的检查
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout))
+            .redirect(reqwest::redirect::Policy::none())
+            .resolve_to_addrs(host, &addrs)
+            .build()?;
+        // 远程服务器的错误细节只记录日志，避免被用来探测端口
+        let response = client.get(url.clone()).send().await.map_err(|e| {
+            debug!("Failed to fetch {}: {}", url, e);
+            Error::BadRequest(IMPORT_FAILED.to_string())
         })?;
+        if !response.status().is_success() {
+            debug!("Fetching {} returned {}", url, response.status());
+            return Err(Error::BadRequest(IMPORT_FAILED.to_string()).into());
+        }
 
-        let clip = match clip {
-            Some(ref c) if (c.status == clip::Status::Reviewed && !user.is_admin) => {
-                trace!("Clip {} is already reviewed, updating details", req.uuid);
-                anyhow::bail!("Clip {} is already reviewed", req.uuid);
-            }
-            Some(c) => c,
-            None => {
-                return Ok(None);
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(';').next())
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        if !config
+            .content_types
+            .iter()
+            .any(|t| content_type.starts_with(t.as_str()))
+        {
+            debug!("Unsupported content type {} from {}", content_type, url);
+            return Err(Error::BadRequest(IMPORT_FAILED.to_string()).into());
+        }
+        if let Some(len) = response.content_length() {
+            if len > max_size {
+                return Err(Error::BadRequest(IMPORT_FAILED.to_string()).into());
             }
+            self.check_quota(user, len).await?;
+        }
+
+        // 优先使用链接中的扩展名，否则按内容类型推断
+        let ext = Path::new(url.path())
+            .extension()
+            .and_then(OsStr::to_str)
+            .map(str::to_ascii_lowercase)
+            .filter(|ext| SUPPORTED_EXTENSIONS.contains(&ext.as_str()))
+            .or_else(|| extension_for_content_type(&content_type).map(str::to_string))
+            .unwrap_or_else(|| "mp4".to_string());
+
+        // 未声明长度时边下载边检查大小，多读一个字节用于判断是否超限
+        let body = futures_util::stream::try_unfold(response, |mut response| async move {
+            let chunk = response.chunk().await.map_err(std::io::Error::other)?;
+            Ok::<_, std::io::Error>(chunk.map(|c| (c, response)))
+        });
+        let mut reader = StreamReader::new(Box::pin(body)).take(max_size + 1);
+        let (uuid, path, content_hash) =
+            self.save_clip_to_tmp(&mut reader, &ext)
+                .await
+                .map_err(|e| {
+                    debug!("Failed to download {}: {}", url, e);
+                    Error::BadRequest(IMPORT_FAILED.to_string())
+                })?;
+        if tokio::fs::metadata(&path).await?.len() > max_size {
+            tokio::fs::remove_file(&path).await.ok();
+            return Err(Error::BadRequest(IMPORT_FAILED.to_string()).into());
+        }
+        debug!("Imported {} as clip {}", url, uuid);
+
+        let req = clip::Model {
+            uuid,
+            content_hash: Some(content_hash),
+            ..req
         };
+        self.create_clip(user, req, path).await
+    }
 
-        let mut active_clip = clip.clone().into_active_model();
-        active_clip.title = Set(req.title);
-        active_clip.vup = Set(req.vup);
-        active_clip.song = Set(req.song);
-        let clip = self
-            .clip_data
-            .update_clip(active_clip)
-            .await
-            .map_err(|e| anyhow!("Failed to update clip: {}", e))?;
-        debug!("Clip updated successfully: {}", clip.uuid.to_string());
-        Ok(Some(clip))
+    fn upload_dir(&self) -> PathBuf {
+        self.tmp_dir.join("uploads")
     }
 
-    pub async fn set_clip_reviewed(
+    fn upload_paths(&self, id: Uuid) -> (PathBuf, PathBuf) {
+        let dir = self.upload_dir();
+        (
+            dir.join(format!("{id}.json")),
+            dir.join(format!("{id}.part")),
+        )
+    }
+
+    /// 创建可续传的上传会话，会话 ID 即之后的切片 UUID
+    pub async fn create_upload(
         &self,
         user: &user::Model,
-        uuid: Uuid,
-    ) -> anyhow::Result<Option<clip::Model>> {
+        ext: &str,
+        size: u64,
+        req: clip::Model,
+    ) -> anyhow::Result<UploadSession> {
         trace!(
-            "Setting clip {} for {} as reviewed",
-            uuid.to_string(),
-            user.id
+            "Creating resumable upload of {} bytes for {}",
+            size, user.id
         );
+        self.check_quota(user, size).await?;
+        self.cleanup_expired_uploads().await;
 
-        let clip = self.get_clip_by_uuid(&user, uuid).await.map_err(|e| {
-            error!("Failed to verify clip ownership: {}", e);
-            anyhow!("Failed to verify clip ownership: {}", e)
-        })?;
+        tokio::fs::create_dir_all(self.upload_dir()).await?;
+        let session = UploadSession {
+            id: Uuid::new_v4(),
+            user_id: user.id,
+            ext: ext.to_string(),
+            size,
+            title: req.title,
+            vup: req.vup,
+            song: req.song,
+            created_at: chrono::Utc::now(),
+            offset: 0,
+        };
+        let (manifest, part) = self.upload_paths(session.id);
+        File::create(&part).await?;
+        tokio::fs::write(&manifest, serde_json::to_vec(&session)?).await?;
+        debug!("Upload {} created", session.id);
+        Ok(session)
+    }
 
-        let clip = match clip {
-            Some(c) => c,
-            None => {
-                error!("Clip {} not found for user ID: {}", uuid, user.id);
-                return Ok(None);
+    pub async fn get_upload(&self, user_id: i64, id: Uuid) -> anyhow::Result<UploadSession> {
+        let (manifest, part) = self.upload_paths(id);
+        let session = match tokio::fs::read(&manifest).await {
+            Ok(bytes) => serde_json::from_slice::<UploadSession>(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(Error::NotFound("Upload not found".to_string()).into());
             }
+            Err(e) => return Err(e.into()),
         };
-        if clip.status != clip::Status::Reviewing {
-            return Err(anyhow!("Clip is not in reviewing status"));
+        if session.user_id != user_id {
+            return Err(Error::NotFound("Upload not found".to_string()).into());
         }
-
-        let mut active_clip = clip.into_active_model();
-        active_clip.status = Set(clip::Status::Reviewed);
-        let clip = self.clip_data.update_clip(active_clip).await.map_err(|e| {
-            error!("Failed to set clip as reviewed: {}", e);
-            e
-        })?;
-        debug!(
-            "Clip set as reviewed successfully: {}",
-            clip.uuid.to_string()
-        );
-        Ok(Some(clip))
+        let offset = tokio::fs::metadata(&part).await?.len();
+        Ok(UploadSession { offset, ..session })
     }
 
-    pub async fn delete_clip(&self, user: &user::Model, uuid: Uuid) -> anyhow::Result<()> {
-        trace!("Deleting clip {} for user {}", uuid.to_string(), user.id);
-
-        self.storage
-            .delete_file(&format!("{}.mp4", uuid.to_string()))
-            .await
-            .map_err(|e| {
-                error!("Failed to delete clip file from storage: {}", e);
-            })
-            .ok();
+    /// 从 `offset` 处追加一段数据，返回更新偏移量后的会话。
+    /// 连接中断时已写入的部分会保留，客户端查询偏移量后续传即可。
+    pub async fn append_upload<R>(
+        &self,
+        user_id: i64,
+        id: Uuid,
+        offset: u64,
+        reader: &mut R,
+    ) -> anyhow::Result<UploadSession>
+    where
+        R: AsyncRead + Unpin + ?Sized,
+    {
+        let _guard = self.lock_upload(id)?;
+        let session = self.get_upload(user_id, id).await?;
+        if offset != session.offset {
+            return Err(Error::Conflict(format!(
+                "Upload offset mismatch, expected {}",
+                session.offset
+            ))
+            .into());
+        }
 
-        self.clip_data
-            .delete_clip_with_playlist_items(user.id, uuid)
+        let (_, part) = self.upload_paths(id);
+        let mut file = tokio::fs::OpenOptions::new()
+            .append(true)
+            .open(&part)
             .await?;
-        debug!("Clip {} deleted successfully", uuid.to_string());
-        Ok(())
+        let remaining = session.size - session.offset;
+        // 多读一个字节用来判断是否超出声明的大小
+        let mut limited = reader.take(remaining + 1);
+        let copied = tokio::io::copy(&mut limited, &mut file).await;
+        file.flush().await?;
+        let written = copied?;
+        if written > remaining {
+            file.set_len(session.offset).await?;
+            return Err(Error::BadRequest("Upload exceeds declared size".to_string()).into());
+        }
+        trace!("Upload {} received {} bytes", id, written);
+        Ok(UploadSession {
+            offset: offset + written,
+            ..session
+        })
     }
 
-    pub async fn _get_clip_stream(
+    /// 上传完整后把文件交给常规的切片处理流程
+    pub async fn finalize_upload(
         &self,
-        uuid: Uuid,
-    ) -> anyhow::Result<Box<dyn AsyncRead + Unpin + Send + 'static>> {
-        trace!("Getting clip stream for UUID: {}", uuid);
+        user: &user::Model,
+        id: Uuid,
+    ) -> anyhow::Result<clip::Model> {
+        let _guard = self.lock_upload(id)?;
+        let session = self.get_upload(user.id, id).await?;
+        if session.offset != session.size {
+            return Err(Error::BadRequest(format!(
+                "Upload is incomplete: {} of {} bytes",
+                session.offset, session.size
+            ))
+            .into());
+        }
 
-        let file_name = format!("{}.mp4", uuid);
+        let (manifest, part) = self.upload_paths(id);
+        let file_path = self.tmp_dir.join(format!("{}.{}", id, session.ext));
+        tokio::fs::rename(&part, &file_path).await?;
+        tokio::fs::remove_file(&manifest).await.ok();
+        let content_hash = sha256_file(&file_path).await?;
 
-        self.storage.get_file(&file_name).await.map_err(|e| {
-            error!("Failed to get clip stream for {}: {}", uuid, e);
-            anyhow!("Failed to get clip stream: {}", e)
-        })
+        let req = clip::Model {
+            uuid: id,
+            title: session.title,
+            vup: session.vup,
+            song: session.song,
+            content_hash: Some(content_hash),
+            ..Default::default()
+        };
+        self.create_clip(user, req, file_path).await
     }
 
-    pub async fn get_clip_stream_with_range(
-        &self,
-        uuid: Uuid,
-        range_header: Option<&axum::http::HeaderValue>,
-    ) -> anyhow::Result<(
-        Box<dyn AsyncRead + Unpin + Send + 'static>,
-        u64,
-        Option<(u64, u64)>,
-    )> {
-        trace!("Getting clip stream with range for UUID: {}", uuid);
-
-        let file_name = format!("{}.mp4", uuid);
-
-        let file_size = self
-            .storage
-            .get_file_size(&file_name)
-            .await
-            .map_err(|e| anyhow!("Failed to get file size: {}", e))?;
+    pub async fn cancel_upload(&self, user_id: i64, id: Uuid) -> anyhow::Result<()> {
+        let _guard = self.lock_upload(id)?;
+        self.get_upload(user_id, id).await?;
+        let (manifest, part) = self.upload_paths(id);
+        tokio::fs::remove_file(&part).await.ok();
+        tokio::fs::remove_file(&manifest).await?;
+        debug!("Upload {} cancelled", id);
+        Ok(())
+    }
 
-        let range_info = if let Some(range_val) = range_header {
-            if let Ok(range_str) = range_val.to_str() {
-                parse_range_header(range_str, file_size)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+    fn lock_upload(&self, id: Uuid) -> anyhow::Result<UploadGuard> {
+        let mut active = self.active_uploads.lock().unwrap();
+        if !active.insert(id) {
+            return Err(Error::Conflict("Upload is busy".to_string()).into());
+        }
+        Ok(UploadGuard {
+            id,
+            active: self.active_uploads.clone(),
+        })
+    }
 
-        let stream = if let Some((start, end)) = range_info {
-            self.storage
-                .get_file_range(&file_name, start, end)
-                .await
-                .map_err(|e| anyhow!("Failed to get file range: {}", e))?
-        } else {
-            self.storage
-                .get_file(&file_name)
-                .await
-                .map_err(|e| anyhow!("Failed to get file: {}", e))?
+    /// 清理超过保留时间没有新数据的上传，正在续传的会话跳过
+    async fn cleanup_expired_uploads(&self) {
+        let expiry = Duration::from_secs(self.upload_config.expiry);
+        let Ok(mut entries) = tokio::fs::read_dir(self.upload_dir()).await else {
+            return;
         };
-
-        Ok((stream, file_size, range_info))
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            if path.extension() != Some(OsStr::new("json")) {
+                continue;
+            }
+            let Some(id) = path
+                .file_stem()
+                .and_then(OsStr::to_str)
+                .and_then(|s| s.parse().ok())
+            else {
+                continue;
+            };
+            let Ok(_guard) = self.lock_upload(id) else {
+                continue;
+            };
+            // 清单只在创建时写入，按最近一次追加数据的时间计算
+            let (manifest, part) = self.upload_paths(id);
+            let mut last_modified = None;
+            for file in [&manifest, &part] {
+                if let Ok(modified) = tokio::fs::metadata(file).await.and_then(|m| m.modified()) {
+                    last_modified = last_modified.max(Some(modified));
+                }
+            }
+            let expired = last_modified.is_some_and(|t| t.elapsed().is_ok_and(|e| e > expiry));
+            if expired {
+                debug!("Removing expired upload {}", id);
+                tokio::fs::remove_file(&part).await.ok();
+                tokio::fs::remove_file(&manifest).await.ok();
+            }
+        }
     }
 
-    async fn transcode_and_normalize(
-        &self,
-        input_path: &PathBuf,
-        output_path: &PathBuf,
-    ) -> anyhow::Result<()> {
-        let analysis = Command::new("ffmpeg")
-            .arg("-i")
-            .arg(input_path)
-            .arg("-af")
-            .arg("loudnorm=print_format=json")
-            .arg("-f")
-            .arg("null")
-            .arg("/dev/null")
-            .stdin(Stdio::null())
-            .stderr(Stdio::piped())
-            .stdout(Stdio::null())
-            .output()
+    pub async fn process_clip(&self, job: ProcessJob) -> Result<(), ProcessError> {
+        let ProcessJob {
+            clip_uuid: uuid,
+            input_path: file,
+            trim,
+            effects,
+        } = job;
+        trace!("Processing clip: {}, path: {}", uuid, file.display());
+        let Some(clip) = self
+            .clip_data
+            .get_clip(uuid)
             .await
-            .map_err(|e| anyhow!("Failed to run ffmpeg to analyze loudness: {}", e))?;
-
-        let stderr = String::from_utf8_lossy(&analysis.stderr);
-
-        let re = Regex::new(r"(?s)\{.*?\}").expect("Failed to compile regex");
-        let Some(mat) = re.find(&stderr) else {
-            anyhow::bail!(
-                "Failed to find loudnorm analysis in output, file: {}, output: {}",
-                input_path.display(),
-                stderr
-            );
+            .map_err(ProcessError::Transient)?
+        else {
+            // 切片在处理前已被删除
+            warn!("Clip {} no longer exists, skipping processing", uuid);
+            tokio::fs::remove_file(&file).await.ok();
+            return Ok(());
         };
-        let json_str = mat.as_str();
-        trace!("FFmpeg loudnorm analysis: {}", json_str);
+        let mut active_clip = clip.clone().into_active_model();
+        active_clip.status = Set(clip::Status::Processing);
+        let clip = self
+            .clip_data
+            .update_clip(active_clip)
+            .await
+            .map_err(ProcessError::Transient)?;
+
+        let input_info = media::probe(&file)
+            .await
+            .map_err(|e| {
+                warn!(
+                    "Failed to probe clip {} before processing: {}",
+                    clip.uuid, e
+                );
+            })
+            .unwrap_or_default();
 
-        #[derive(Deserialize, Debug)]
-        struct LoudnessInfo {
-            input_i: String,
-            input_tp: String,
-            input_lra: String,
-            input_thresh: String,
+        // 未指定裁剪或编辑时检测首尾的静音和黑场
+        let mut trim = trim;
+        let mut suggested = None;
+        let edge_mode = self.config.edge_detection.mode;
+        if trim.is_none() && effects.is_none() && edge_mode != EdgeMode::Off {
+            match self.detect_edges(&clip, &file, &input_info).await {
+                Ok(Some(edges)) if edge_mode == EdgeMode::Trim => {
+                    debug!("Trimming clip {} to {:?}", clip.uuid, edges);
+                    trim = Some(Trim {
+                        in_ms: edges.in_ms,
+                        out_ms: edges.out_ms,
+                    });
+                }
+                Ok(edges) => suggested = edges,
+                Err(e) => warn!("Failed to detect edges of clip {}: {}", clip.uuid, e),
+            }
         }
-        let info: LoudnessInfo = match serde_json::from_str(json_str) {
-            Ok(val) => val,
+
+        let volume_db = clip.volume_db + effects.map_or(0.0, |e| e.volume_db);
+        // 处理结果写入单独的文件，成功前保留原始文件以便重试
+        let output_path = self.tmp_dir.join(format!("{}_processed.mp4", clip.uuid));
+        let normalized = match self
+            .transcode_and_normalize(
+                &clip,
+                &file,
+                &output_path,
+                input_info.duration_ms,
+                trim,
+                effects,
+            )
+            .await
+        {
+            Ok(normalized) => normalized,
             Err(e) => {
-                anyhow::bail!("Failed to parse loudnorm analysis JSON: {}", e);
+                error!("Failed to normalize clip {}: {}", clip.uuid, e);
+                tokio::fs::remove_file(&output_path).await.ok();
+                self.set_clip_failed(&clip).await;
+                return Err(ProcessError::Permanent(e));
             }
         };
+        debug!("Clip {} processed successfully", clip.uuid);
 
-        let status = Command::new("ffmpeg")
-            .arg("-loglevel")
-            .arg("error")
-            .arg("-i")
-            .arg(input_path)
-            .arg("-af")
-            .arg(format!(
-                "loudnorm=linear=true:I=-14:TP=0:LRA=50:measured_I={}:measured_TP={}:measured_LRA={}:measured_thresh={}",
-                info.input_i, info.input_tp, info.input_lra, info.input_thresh
-            ))
-            .arg("-ar")
-            .arg("48k")
-            .arg("-vcodec")
-            .arg("copy")
-            .arg(output_path)
-            .status().await.map_err(|e| anyhow!("Failed to run ffmpeg: {}", e))?;
-
-        if !status.success() {
-            anyhow::bail!("FFmpeg command failed with status: {}", status);
-        }
+        self.set_progress(clip.id, clip::ProcessStage::Uploading, 0)
+            .await;
+        if let Err(e) = self
+            .storage
+            .store_file(format!("{}.mp4", clip.uuid), &output_path)
+            .await
+        {
+            error!("Failed to store clip {}: {}", clip.uuid, e);
+            self.set_clip_failed(&clip).await;
+            return Err(ProcessError::Transient(e));
+        }
+        debug!("Clip {} stored successfully", clip.uuid);
+
+        let info = media::probe(&output_path)
+            .await
+            .map_err(|e| {
+                warn!("Failed to probe clip {}: {}", clip.uuid, e);
+            })
+            .unwrap_or_default();
+
+        // 缩略图生成失败不影响切片本身
+        if let Err(e) = self.generate_thumbnails(&clip, &output_path, &info).await {
+            warn!(
+                "Failed to generate thumbnails for clip {}: {}",
+                clip.uuid, e
+            );
+        }
+
+        let mut duplicate_of = clip.duplicate_of;
+        let mut fingerprint = None;
+        if self.config.audio_fingerprint && info.audio_codec.is_some() {
+            match self.match_fingerprint(&clip, &output_path).await {
+                Ok((fp, matched)) => {
+                    fingerprint = Some(fp);
+                    duplicate_of = duplicate_of.or(matched);
+                }
+                Err(e) => warn!("Failed to fingerprint clip {}: {}", clip.uuid, e),
+            }
+        }
+
+        for path in [&file, &output_path] {
+            tokio::fs::remove_file(path)
+                .await
+                .map_err(|e| {
+                    error!("Failed to remove clip file {}: {}", path.display(), e);
+                })
+                .ok();
+        }
+
+        let auto_approved = self
+            .check_auto_approve(&clip, info.duration_ms, &normalized, duplicate_of.is_some())
+            .await;
+        let review = auto_approved.map(|note| clip_review::ActiveModel {
+            clip_id: Set(clip.id),
+            user_id: Set(clip.user_id),
+            decision: Set(clip_review::Decision::AutoApproved),
+            note: Set(Some(note)),
+            created_at: Set(chrono::Utc::now().into()),
+            ..Default::default()
+        });
+
+        let mut active_clip = clip.into_active_model();
+        active_clip.status = Set(match review {
+            Some(_) => clip::Status::Reviewed,
+            None => clip::Status::Reviewing,
+        });
+        active_clip.duration_ms = Set(info.duration_ms);
+        active_clip.width = Set(info.width);
+        active_clip.height = Set(info.height);
+        active_clip.frame_rate = Set(info.frame_rate);
+        active_clip.video_codec = Set(info.video_codec);
+        active_clip.audio_codec = Set(info.audio_codec);
+        active_clip.bit_rate = Set(info.bit_rate);
+        active_clip.file_size = Set(info.file_size);
+        active_clip.volume_db = Set(volume_db);
+        active_clip.stream_profile = Set(self
+            .config
+            .ingest_transcode
+            .then(|| self.stream_config.profile_hash()));
+        let measured = normalized.output;
+        active_clip.loudness = Set(measured.as_ref().map(|m| m.input_i));
+        active_clip.measured_i = Set(measured.as_ref().map(|m| m.input_i));
+        active_clip.measured_tp = Set(measured.as_ref().map(|m| m.input_tp));
+        active_clip.measured_lra = Set(measured.as_ref().map(|m| m.input_lra));
+        active_clip.measured_thresh = Set(measured.as_ref().map(|m| m.input_thresh));
+        active_clip.suggested_in_ms = Set(suggested.map(|e| e.in_ms));
+        active_clip.suggested_out_ms = Set(suggested.map(|e| e.out_ms));
+        active_clip.duplicate_of = Set(duplicate_of);
+        if fingerprint.is_some() {
+            active_clip.fingerprint = Set(fingerprint);
+        }
+        active_clip.process_stage = Set(None);
+        active_clip.process_progress = Set(None);
+        let updated = match review {
+            Some(review) => {
+                self.clip_data
+                    .update_clip_with_review(active_clip, review)
+                    .await
+            }
+            None => self.clip_data.update_clip(active_clip).await,
+        };
+        match updated {
+            Ok(clip) if clip.status == clip::Status::Reviewed => {
+                info!("Clip {} auto-approved", clip.uuid)
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to update clip status: {}", e),
+        }
+
+        Ok(())
+    }
+
+    /// 可信用户的切片满足自动审核规则时返回审核说明
+    /// 开启了自动审核且上传者可信时，切片才可能自动通过
+    async fn may_auto_approve(&self, clip: &clip::Model) -> bool {
+        if !self.config.auto_approve.enabled {
+            return false;
+        }
+        match self.clip_data.get_owner(clip).await {
+            Ok(owner) => owner.is_some_and(|u| u.is_trusted),
+            Err(e) => {
+                warn!("Failed to fetch owner of clip {}: {}", clip.uuid, e);
+                false
+            }
+        }
+    }
+
+    async fn check_auto_approve(
+        &self,
+        clip: &clip::Model,
+        duration_ms: Option<i64>,
+        normalized: &Normalized,
+        duplicate: bool,
+    ) -> Option<String> {
+        let rules = &self.config.auto_approve;
+        if !rules.enabled {
+            return None;
+        }
+        let owner = match self.clip_data.get_owner(clip).await {
+            Ok(owner) => owner?,
+            Err(e) => {
+                warn!("Failed to fetch owner of clip {}: {}", clip.uuid, e);
+                return None;
+            }
+        };
+        let rejected_before = match self.clip_data.list_reviews(clip.id).await {
+            Ok(reviews) => reviews
+                .iter()
+                .any(|(r, _)| r.decision == clip_review::Decision::Rejected),
+            Err(e) => {
+                warn!("Failed to fetch reviews of clip {}: {}", clip.uuid, e);
+                return None;
+            }
+        };
+        auto_approve_note(
+            rules,
+            &owner,
+            rejected_before,
+            duration_ms,
+            normalized,
+            duplicate,
+        )
+        .map_err(|reason| debug!("Clip {} not auto-approved: {}", clip.uuid, reason))
+        .ok()
+    }
+
+    async fn set_clip_failed(&self, clip: &clip::Model) {
+        let mut active_clip = clip.clone().into_active_model();
+        active_clip.status = Set(clip::Status::Failed);
+        self.clip_data
+            .update_clip(active_clip)
+            .await
+            .map_err(|e| {
+                error!("Failed to update clip status to failed: {}", e);
+            })
+            .ok();
+    }
+
+    async fn get_editable_clip(
+        &self,
+        user: &user::Model,
+        uuid: Uuid,
+    ) -> anyhow::Result<Option<clip::Model>> {
+        let clip = match self.clip_data.get_clip(uuid).await? {
+            Some(c) if (c.user_id == user.id || user.is_admin) && c.deleted_at.is_none() => c,
+            _ => return Ok(None),
+        };
+        if matches!(
+            clip.status,
+            clip::Status::Pending | clip::Status::Processing
+        ) {
+            return Err(Error::Conflict(format!("Clip {uuid} is already being processed")).into());
+        }
+        Ok(Some(clip))
+    }
+
+    pub async fn reprocess_clip(
+        &self,
+        user: &user::Model,
+        uuid: Uuid,
+    ) -> anyhow::Result<Option<clip::Model>> {
+        trace!("Reprocessing clip {} for user {}", uuid, user.id);
+        let Some(clip) = self.get_editable_clip(user, uuid).await? else {
+            return Ok(None);
+        };
+        self.requeue_clip(user, clip, None, None).await.map(Some)
+    }
+
+    pub async fn accept_suggested_trim(
+        &self,
+        user: &user::Model,
+        uuid: Uuid,
+    ) -> anyhow::Result<Option<clip::Model>> {
+        trace!(
+            "Accepting suggested trim of clip {} for user {}",
+            uuid, user.id
+        );
+        let Some(clip) = self.get_editable_clip(user, uuid).await? else {
+            return Ok(None);
+        };
+        let (Some(in_ms), Some(out_ms)) = (clip.suggested_in_ms, clip.suggested_out_ms) else {
+            return Err(Error::Conflict("Clip has no suggested trim".to_string()).into());
+        };
+        self.requeue_clip(user, clip, Some(Trim { in_ms, out_ms }), None)
+            .await
+            .map(Some)
+    }
+
+    /// 按 `edit` 重新处理切片，`new_clip` 为 true 时保留原切片，
+    /// 以编辑结果创建一个新切片。时间点相对于当前可以播放的文件。
+    pub async fn edit_clip(
+        &self,
+        user: &user::Model,
+        uuid: Uuid,
+        edit: ClipEdit,
+        new_clip: bool,
+    ) -> anyhow::Result<Option<clip::Model>> {
+        trace!("Editing clip {} for user {}: {:?}", uuid, user.id, edit);
+        let Some(clip) = self.get_editable_clip(user, uuid).await? else {
+            return Ok(None);
+        };
+        let (trim, effects) = edit
+            .resolve(clip.duration_ms)
+            .map_err(|e| Error::BadRequest(e.to_string()))?;
+        if trim.is_none() && effects.is_none() {
+            return Err(Error::BadRequest("Nothing to edit".into()).into());
+        }
+        // 音量调整在之前的调整上累加
+        if effects.is_some_and(|e| (clip.volume_db + e.volume_db).abs() > MAX_VOLUME_ADJUST_DB) {
+            return Err(Error::BadRequest("Volume adjustment is out of range".into()).into());
+        }
+        if !new_clip {
+            return self.requeue_clip(user, clip, trim, effects).await.map(Some);
+        }
+
+        self.check_quota(user, clip.file_size.unwrap_or(0).max(0) as u64)
+            .await?;
+        let (source, original) = self.fetch_source_file(clip.uuid).await?;
+        let new_uuid = Uuid::new_v4();
+        let ext = source
+            .extension()
+            .and_then(OsStr::to_str)
+            .unwrap_or("mp4")
+            .to_string();
+        let file = self.tmp_dir.join(format!("{new_uuid}.{ext}"));
+        // 原始文件还要留给原切片重试，已处理的文件是临时取回的
+        let copied = match original {
+            true => tokio::fs::copy(&source, &file).await.map(|_| ()),
+            false => tokio::fs::rename(&source, &file).await,
+        };
+        if let Err(e) = copied {
+            if !original {
+                tokio::fs::remove_file(&source).await.ok();
+            }
+            return Err(e.into());
+        }
+
+        let clip_active = clip::ActiveModel {
+            uuid: Set(new_uuid),
+            title: Set(clip.title.clone()),
+            vup: Set(clip.vup.clone()),
+            song: Set(clip.song.clone()),
+            vup_id: Set(clip.vup_id),
+            song_id: Set(clip.song_id),
+            upload_time: Set(chrono::Utc::now()),
+            user_id: Set(user.id),
+            file_size: Set(clip.file_size),
+            // 测量值对应的是原切片已存储的文件
+            measured_i: Set(clip.measured_i.filter(|_| !original)),
+            measured_tp: Set(clip.measured_tp.filter(|_| !original)),
+            measured_lra: Set(clip.measured_lra.filter(|_| !original)),
+            measured_thresh: Set(clip.measured_thresh.filter(|_| !original)),
+            volume_db: Set(clip.volume_db),
+            ..Default::default()
+        };
+        let new_clip = match self.clip_data.create_clip(clip_active).await {
+            Ok(c) => c,
+            Err(e) => {
+                tokio::fs::remove_file(&file).await.ok();
+                return Err(e);
+            }
+        };
+        self.queue
+            .push(ProcessJob {
+                clip_uuid: new_clip.uuid,
+                input_path: file,
+                trim,
+                effects,
+            })
+            .await?;
+        debug!(
+            "Clip {} created from {} and enqueued for processing",
+            new_clip.uuid, clip.uuid
+        );
+        Ok(Some(new_clip))
+    }
+
+    /// 优先使用未处理完的原始文件，否则从存储中取回已处理的文件。
+    /// 返回文件路径以及是否为原始文件
+    async fn fetch_source_file(&self, uuid: Uuid) -> anyhow::Result<(PathBuf, bool)> {
+        if let Some(path) = self.find_source_file(uuid).await? {
+            return Ok((path, true));
+        }
+        let path = self.tmp_dir.join(format!("{uuid}.mp4"));
+        self.download_file(&format!("{uuid}.mp4"), &path).await?;
+        Ok((path, false))
+    }
+
+    async fn download_file(&self, name: &str, path: &Path) -> anyhow::Result<()> {
+        let mut reader = self.storage.get_file(name).await?;
+        let mut file = BufWriter::new(File::create(path).await?);
+        let result = async {
+            tokio::io::copy(&mut reader, &mut file).await?;
+            file.flush().await
+        }
+        .await;
+        if let Err(e) = result {
+            tokio::fs::remove_file(path).await.ok();
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    async fn requeue_clip(
+        &self,
+        user: &user::Model,
+        clip: clip::Model,
+        trim: Option<Trim>,
+        effects: Option<Effects>,
+    ) -> anyhow::Result<clip::Model> {
+        // 裁剪或编辑会覆盖已存储的文件，先保留为历史版本
+        if trim.is_some() || effects.is_some() {
+            self.archive_current_file(user, &clip).await?;
+        }
+        let (file, original) = self.fetch_source_file(clip.uuid).await?;
+
+        let mut active_clip = clip.into_active_model();
+        if original {
+            // 测量值对应的是已存储的文件，原始文件需要重新分析
+            active_clip.measured_i = Set(None);
+            active_clip.measured_tp = Set(None);
+            active_clip.measured_lra = Set(None);
+            active_clip.measured_thresh = Set(None);
+        }
+        active_clip.status = Set(clip::Status::Pending);
+        active_clip.process_stage = Set(None);
+        active_clip.process_progress = Set(None);
+        active_clip.suggested_in_ms = Set(None);
+        active_clip.suggested_out_ms = Set(None);
+        let clip = self.clip_data.update_clip(active_clip).await?;
+
+        self.queue
+            .push(ProcessJob {
+                clip_uuid: clip.uuid,
+                input_path: file,
+                trim,
+                effects,
+            })
+            .await?;
+        debug!("Clip {} enqueued for reprocessing", clip.uuid);
+        Ok(clip)
+    }
+
+    /// 用新上传的文件替换切片，原有的播放列表引用不变。
+    /// 当前文件保留为历史版本，新文件处理完成后切片回到待审核状态
+    /// 在接收替换文件之前检查存储配额
+    pub async fn check_replace_quota_before_upload(
+        &self,
+        user: &user::Model,
+        uuid: Uuid,
+        incoming: u64,
+    ) -> anyhow::Result<()> {
+        let clip = self
+            .get_editable_clip(user, uuid)
+            .await?
+            .ok_or(Error::NotFound("Clip not found".to_string()))?;
+        self.check_replace_quota(user, &clip, incoming).await
+    }
+
+    async fn check_replace_quota(
+        &self,
+        user: &user::Model,
+        clip: &clip::Model,
+        incoming: u64,
+    ) -> anyhow::Result<()> {
+        // 替换不增加切片数，只检查存储空间
+        let quota = Quota {
+            max_bytes: self.quota(user).max_bytes,
+            ..Default::default()
+        };
+        if quota.max_bytes.is_none() {
+            return Ok(());
+        }
+        let mut usage = self.quota_usage(user).await?;
+        // 不保留历史版本时原文件的占用会被释放
+        if self.upload_config.max_versions == 0 {
+            usage.bytes = usage
+                .bytes
+                .saturating_sub(clip.file_size.unwrap_or(0).max(0) as u64);
+        }
+        quota
+            .check(&usage, incoming)
+            .map_err(|e| Error::Forbidden(e).into())
+    }
+
+    pub async fn replace_clip_file(
+        &self,
+        user: &user::Model,
+        uuid: Uuid,
+        file: PathBuf,
+        content_hash: String,
+    ) -> anyhow::Result<Option<clip::Model>> {
+        trace!("Replacing file of clip {} for user {}", uuid, user.id);
+        let clip = match self.get_editable_clip(user, uuid).await {
+            Ok(Some(clip)) => clip,
+            result => {
+                tokio::fs::remove_file(&file).await.ok();
+                return result;
+            }
+        };
+
+        let file_size = tokio::fs::metadata(&file).await?.len();
+        let checked = async {
+            self.check_replace_quota(user, &clip, file_size).await?;
+            self.validate_media(&file).await?;
+            self.archive_current_file(user, &clip).await
+        }
+        .await;
+        if let Err(e) = checked {
+            debug!("Rejected replacement of clip {}: {}", uuid, e);
+            tokio::fs::remove_file(&file).await.ok();
+            return Err(e);
+        }
+
+        // 上一次未处理完的原始文件已没有用处
+        if let Some(old) = self.find_source_file(uuid).await? {
+            tokio::fs::remove_file(old).await.ok();
+        }
+        let ext = file.extension().and_then(OsStr::to_str).unwrap_or("mp4");
+        let input_path = self.tmp_dir.join(format!("{uuid}.{ext}"));
+        tokio::fs::rename(&file, &input_path).await?;
+
+        let mut active_clip = clip.into_active_model();
+        active_clip.status = Set(clip::Status::Pending);
+        active_clip.file_size = Set(Some(file_size as i64));
+        active_clip.content_hash = Set(Some(content_hash));
+        active_clip.measured_i = Set(None);
+        active_clip.measured_tp = Set(None);
+        active_clip.measured_lra = Set(None);
+        active_clip.measured_thresh = Set(None);
+        active_clip.volume_db = Set(0.0);
+        active_clip.process_stage = Set(None);
+        active_clip.process_progress = Set(None);
+        active_clip.suggested_in_ms = Set(None);
+        active_clip.suggested_out_ms = Set(None);
+        let clip = self.clip_data.update_clip(active_clip).await?;
+
+        self.queue
+            .push(ProcessJob {
+                clip_uuid: clip.uuid,
+                input_path,
+                trim: None,
+                effects: None,
+            })
+            .await?;
+        debug!(
+            "Clip {} file replaced and enqueued for processing",
+            clip.uuid
+        );
+        Ok(Some(clip))
+    }
+
+    /// 把已存储的文件另存为历史版本，超出保留数量时删除最旧的版本
+    async fn archive_current_file(
+        &self,
+        user: &user::Model,
+        clip: &clip::Model,
+    ) -> anyhow::Result<()> {
+        let max_versions = self.upload_config.max_versions;
+        // 只有处理完成的切片才有已存储的文件
+        if max_versions == 0
+            || !matches!(
+                clip.status,
+                clip::Status::Reviewing | clip::Status::Reviewed | clip::Status::Rejected
+            )
+        {
+            return Ok(());
+        }
+
+        // 存储后端没有复制接口，经临时文件中转
+        let tmp = self.tmp_dir.join(format!("{}_archive.mp4", clip.uuid));
+        self.download_file(&format!("{}.mp4", clip.uuid), &tmp)
+            .await?;
+        let version = clip_version::ActiveModel {
+            clip_id: Set(clip.id),
+            user_id: Set(user.id),
+            duration_ms: Set(clip.duration_ms),
+            file_size: Set(clip.file_size),
+            content_hash: Set(clip.content_hash.clone()),
+            measured_i: Set(clip.measured_i),
+            measured_tp: Set(clip.measured_tp),
+            measured_lra: Set(clip.measured_lra),
+            measured_thresh: Set(clip.measured_thresh),
+            volume_db: Set(clip.volume_db),
+            created_at: Set(chrono::Utc::now().into()),
+            ..Default::default()
+        };
+        let stored = async {
+            let version = self.clip_data.create_version(version).await?;
+            if let Err(e) = self
+                .storage
+                .store_file(version.file_name(clip.uuid), &tmp)
+                .await
+            {
+                self.clip_data.delete_version(version.id).await.ok();
+                return Err(e);
+            }
+            anyhow::Ok(version)
+        }
+        .await;
+        tokio::fs::remove_file(&tmp).await.ok();
+        let version = stored?;
+        debug!("Archived clip {} as version {}", clip.uuid, version.id);
+
+        let versions = self.clip_data.list_versions(clip.id).await?;
+        for old in versions.into_iter().skip(max_versions) {
+            self.delete_version(clip.uuid, &old).await;
+        }
+        Ok(())
+    }
+
+    async fn delete_version(&self, uuid: Uuid, version: &clip_version::Model) {
+        if let Err(e) = self.storage.delete_file(&version.file_name(uuid)).await {
+            warn!(
+                "Failed to delete version {} of clip {}: {}",
+                version.id, uuid, e
+            );
+            return;
+        }
+        self.clip_data
+            .delete_version(version.id)
+            .await
+            .map_err(|e| {
+                error!(
+                    "Failed to delete version {} of clip {}: {}",
+                    version.id, uuid, e
+                );
+            })
+            .ok();
+    }
+
+    async fn delete_clip_versions(&self, uuid: Uuid) {
+        let versions = match self.clip_data.get_clip(uuid).await {
+            Ok(Some(clip)) => self.clip_data.list_versions(clip.id).await,
+            Ok(None) => return,
+            Err(e) => Err(e),
+        };
+        match versions {
+            Ok(versions) => {
+                for version in versions {
+                    self.delete_version(uuid, &version).await;
+                }
+            }
+            Err(e) => error!("Failed to list versions of clip {}: {}", uuid, e),
+        }
+    }
+
+    pub async fn list_clip_versions(
+        &self,
+        user: &user::Model,
+        uuid: Uuid,
+    ) -> anyhow::Result<Option<Vec<clip_version::Model>>> {
+        let clip = match self.clip_data.get_clip(uuid).await? {
+            Some(c) if c.user_id == user.id || user.is_admin => c,
+            _ => return Ok(None),
+        };
+        self.clip_data.list_versions(clip.id).await.map(Some)
+    }
+
+    /// 恢复到历史版本，当前文件同样保留为历史版本，因此回滚本身也可撤销
+    pub async fn rollback_clip(
+        &self,
+        user: &user::Model,
+        uuid: Uuid,
+        version_id: i64,
+    ) -> anyhow::Result<Option<clip::Model>> {
+        trace!(
+            "Rolling back clip {} to version {} for user {}",
+            uuid, version_id, user.id
+        );
+        let Some(clip) = self.get_editable_clip(user, uuid).await? else {
+            return Ok(None);
+        };
+        let Some(version) = self.clip_data.get_version(clip.id, version_id).await? else {
+            return Err(Error::NotFound(format!("Version {version_id} not found")).into());
+        };
+
+        let tmp = self.tmp_dir.join(format!("{uuid}_rollback.mp4"));
+        self.download_file(&version.file_name(uuid), &tmp).await?;
+        let restored = async {
+            self.archive_current_file(user, &clip).await?;
+            self.storage.store_file(format!("{uuid}.mp4"), &tmp).await?;
+            let info = media::probe(&tmp).await.unwrap_or_default();
+            if let Err(e) = self.generate_thumbnails(&clip, &tmp, &info).await {
+                warn!("Failed to generate thumbnails for clip {}: {}", uuid, e);
+            }
+            let mut fingerprint = None;
+            if self.config.audio_fingerprint && info.audio_codec.is_some() {
+                match media::audio_fingerprint(&tmp, FINGERPRINT_MAX_SECS).await {
+                    Ok(fp) => fingerprint = Some(media::fingerprint_to_bytes(&fp)),
+                    Err(e) => warn!("Failed to fingerprint clip {}: {}", uuid, e),
+                }
+            }
+            anyhow::Ok((info, fingerprint))
+        }
+        .await;
+        tokio::fs::remove_file(&tmp).await.ok();
+        let (info, fingerprint) = restored?;
+
+        let mut active_clip = clip.into_active_model();
+        // 恢复的文件同样需要重新审核
+        active_clip.status = Set(clip::Status::Reviewing);
+        active_clip.duration_ms = Set(info.duration_ms.or(version.duration_ms));
+        active_clip.width = Set(info.width);
+        active_clip.height = Set(info.height);
+        active_clip.frame_rate = Set(info.frame_rate);
+        active_clip.video_codec = Set(info.video_codec);
+        active_clip.audio_codec = Set(info.audio_codec);
+        active_clip.bit_rate = Set(info.bit_rate);
+        active_clip.file_size = Set(info.file_size.or(version.file_size));
+        // 不确定历史版本的编码参数，推流时重新编码
+        active_clip.stream_profile = Set(None);
+        active_clip.content_hash = Set(version.content_hash.clone());
+        active_clip.loudness = Set(version.measured_i);
+        active_clip.measured_i = Set(version.measured_i);
+        active_clip.measured_tp = Set(version.measured_tp);
+        active_clip.measured_lra = Set(version.measured_lra);
+        active_clip.measured_thresh = Set(version.measured_thresh);
+        active_clip.volume_db = Set(version.volume_db);
+        active_clip.suggested_in_ms = Set(None);
+        active_clip.suggested_out_ms = Set(None);
+        if fingerprint.is_some() {
+            active_clip.fingerprint = Set(fingerprint);
+        }
+        let clip = self.clip_data.update_clip(active_clip).await?;
+
+        // 已恢复为当前文件，不再作为历史版本保留
+        self.delete_version(uuid, &version).await;
+        debug!("Clip {} rolled back to version {}", uuid, version_id);
+        Ok(Some(clip))
+    }
+
+    async fn find_source_file(&self, uuid: Uuid) -> anyhow::Result<Option<PathBuf>> {
+        let uuid = uuid.to_string();
+        let mut entries = tokio::fs::read_dir(&self.tmp_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.file_stem().is_some_and(|stem| stem == uuid.as_str()) {
+                return Ok(Some(path));
+            }
+        }
+        Ok(None)
+    }
+
+    pub async fn list_clips_by_user(
+        &self,
+        user: &user::Model,
+        filter: &ClipFilter,
+    ) -> anyhow::Result<ClipPage> {
+        trace!("Listing clips for user {}: {:?}", user.id, filter);
+        // 管理员默认可以看到所有切片
+        let user_id = match filter.user_id {
+            Some(id) if id != user.id && !user.is_admin => {
+                return Err(
+                    Error::Forbidden("Cannot list clips of other users".to_string()).into(),
+                );
+            }
+            Some(id) => Some(id),
+            None => (!user.is_admin).then_some(user.id),
+        };
+        self.query_clips(user_id, filter).await
+    }
+
+    /// 所有用户已审核的切片，供选择加入播放列表
+    pub async fn list_reviewed_clips(&self, filter: &ClipFilter) -> anyhow::Result<ClipPage> {
+        trace!("Listing reviewed clips: {:?}", filter);
+        let filter = ClipFilter {
+            statuses: vec![clip::Status::Reviewed],
+            trashed: false,
+            ..filter.clone()
+        };
+        self.query_clips(filter.user_id, &filter).await
+    }
+
+    async fn query_clips(
+        &self,
+        user_id: Option<i64>,
+        filter: &ClipFilter,
+    ) -> anyhow::Result<ClipPage> {
+        if filter
+            .from
+            .zip(filter.to)
+            .is_some_and(|(from, to)| from >= to)
+        {
+            return Err(Error::BadRequest("Invalid date range".to_string()).into());
+        }
+        let mut tag_ids = filter.tags.clone();
+        tag_ids.sort_unstable();
+        tag_ids.dedup();
+        let page = filter.page.max(1);
+        let page_size = match filter.page_size {
+            0 => DEFAULT_PAGE_SIZE,
+            n => n.min(MAX_PAGE_SIZE),
+        };
+        let query = ClipQuery {
+            user_id,
+            tag_ids,
+            search: filter
+                .search
+                .as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string),
+            statuses: filter.statuses.clone(),
+            vup_id: filter.vup_id,
+            song_id: filter.song_id,
+            from: filter.from,
+            to: filter.to,
+            trashed: filter.trashed,
+            sort: filter.sort.column(),
+            order: if filter.ascending {
+                Order::Asc
+            } else {
+                Order::Desc
+            },
+            offset: (page - 1).saturating_mul(page_size),
+            limit: page_size,
+        };
+        let (clips, total) = self.clip_data.list_clips(&query).await.map_err(|e| {
+            error!("Failed to fetch clips: {}", e);
+            e
+        })?;
+        debug!("Fetched {} of {} clips", clips.len(), total);
+        Ok(ClipPage {
+            clips,
+            total,
+            page,
+            page_size,
+        })
+    }
+
+    /// 上传者和管理员可以查看单个切片
+    pub async fn get_clip(&self, user: &user::Model, uuid: Uuid) -> anyhow::Result<clip::Model> {
+        match self.clip_data.get_clip(uuid).await? {
+            Some(c) if c.user_id == user.id || user.is_admin => Ok(c),
+            _ => Err(Error::NotFound("Clip not found".to_string()).into()),
+        }
+    }
+
+    pub async fn get_clip_by_uuid(
+        &self,
+        user: &user::Model,
+        uuid: Uuid,
+    ) -> anyhow::Result<Option<clip::Model>> {
+        trace!("Fetching clip by UUID: {}", uuid.to_string());
+        self.clip_data.get_clip_by_uuid(user.id, uuid).await
+    }
+
+    async fn _get_clip_by_id(&self, id: i64) -> anyhow::Result<clip::Model> {
+        trace!("Fetching clip by ID: {}", id);
+        let clip = self
+            .clip_data
+            ._get_clip_by_id(id)
+            .await
+            .map_err(|e| {
+                error!("Failed to fetch clip by ID {}: {}", id, e);
+                e
+            })?
+            .ok_or_else(|| {
+                error!("Clip not found for user ID: {}", id);
+                anyhow::anyhow!("Clip not found")
+            })?;
+        debug!("Fetched clip: {}", clip.uuid.to_string());
+        Ok(clip)
+    }
+
+    pub async fn update_clip(
+        &self,
+        user: &user::Model,
+        req: clip::Model,
+    ) -> anyhow::Result<Option<clip::Model>> {
+        trace!("Updating clip {} for {}", req.uuid.to_string(), user.id);
+        let clip = self.get_clip_by_uuid(&user, req.uuid).await.map_err(|e| {
+            error!("Failed to verify clip ownership: {}", e);
+            anyhow!("Failed to verify clip ownership: {}", e)
+        })?;
+
+        let clip = match clip {
+            Some(ref c) if (c.status == clip::Status::Reviewed && !user.is_admin) => {
+                trace!("Clip {} is already reviewed, updating details", req.uuid);
+                anyhow::bail!("Clip {} is already reviewed", req.uuid);
+            }
+            Some(c) => c,
+            None => {
+                return Ok(None);
+            }
+        };
+
+        let (vup_id, vup) =
+            catalog::resolve(&self.catalog_data, CatalogKind::Vup, &req.vup).await?;
+        let (song_id, song) =
+            catalog::resolve(&self.catalog_data, CatalogKind::Song, &req.song).await?;
+        let mut active_clip = clip.clone().into_active_model();
+        active_clip.title = Set(req.title);
+        active_clip.vup = Set(vup);
+        active_clip.song = Set(song);
+        active_clip.vup_id = Set(vup_id);
+        active_clip.song_id = Set(song_id);
+        let clip = self
+            .clip_data
+            .update_clip(active_clip)
+            .await
+            .map_err(|e| anyhow!("Failed to update clip: {}", e))?;
+        debug!("Clip updated successfully: {}", clip.uuid.to_string());
+        Ok(Some(clip))
+    }
+
+    pub async fn set_clip_reviewed(
+        &self,
+        user: &user::Model,
+        uuid: Uuid,
+    ) -> anyhow::Result<clip::Model> {
+        self.review_clip(user, uuid, clip_review::Decision::Approved, None)
+            .await
+    }
+
+    /// 审核切片：通过、退回（必须填写原因）或只留下意见
+    pub async fn review_clip(
+        &self,
+        reviewer: &user::Model,
+        uuid: Uuid,
+        decision: clip_review::Decision,
+        note: Option<String>,
+    ) -> anyhow::Result<clip::Model> {
+        trace!("Reviewing clip {} by {}: {:?}", uuid, reviewer.id, decision);
+        let clip = self
+            .clip_data
+            .get_clip(uuid)
+            .await?
+            .filter(|c| c.deleted_at.is_none())
+            .ok_or(Error::NotFound("Clip not found".to_string()))?;
+        let note = normalize_review_note(note)?;
+
+        let status = match decision {
+            clip_review::Decision::Approved if clip.status == clip::Status::Reviewing => {
+                clip::Status::Reviewed
+            }
+            clip_review::Decision::Approved => {
+                return Err(Error::Conflict("Clip is not in reviewing status".to_string()).into());
+            }
+            clip_review::Decision::Rejected if note.is_none() => {
+                return Err(Error::BadRequest("A reason is required to reject".to_string()).into());
+            }
+            // 已通过的切片也可以撤回
+            clip_review::Decision::Rejected
+                if matches!(
+                    clip.status,
+                    clip::Status::Reviewing | clip::Status::Reviewed
+                ) =>
+            {
+                clip::Status::Rejected
+            }
+            clip_review::Decision::Rejected => {
+                return Err(Error::Conflict("Clip has not been processed".to_string()).into());
+            }
+            clip_review::Decision::Comment if note.is_none() => {
+                return Err(Error::BadRequest("Comment must not be empty".to_string()).into());
+            }
+            clip_review::Decision::Comment => clip.status.clone(),
+            clip_review::Decision::Resubmitted | clip_review::Decision::AutoApproved => {
+                return Err(Error::BadRequest("Invalid review decision".to_string()).into());
+            }
+        };
+
+        let review = clip_review::ActiveModel {
+            clip_id: Set(clip.id),
+            user_id: Set(reviewer.id),
+            decision: Set(decision),
+            note: Set(note.clone()),
+            created_at: Set(chrono::Utc::now().into()),
+            ..Default::default()
+        };
+        let mut active_clip = clip.into_active_model();
+        active_clip.status = Set(status);
+        // 评论只记在审核记录里，不覆盖切片上的审核意见
+        if decision != clip_review::Decision::Comment {
+            active_clip.review_note = Set(note);
+        }
+        let clip = self
+            .clip_data
+            .update_clip_with_review(active_clip, review)
+            .await?;
+        debug!("Clip {} reviewed: {:?}", clip.uuid, decision);
+        Ok(clip)
+    }
+
+    /// 上传者修改被退回的切片后重新提交审核
+    pub async fn resubmit_clip(
+        &self,
+        user: &user::Model,
+        uuid: Uuid,
+        note: Option<String>,
+    ) -> anyhow::Result<clip::Model> {
+        let clip = self.get_clip(user, uuid).await?;
+        if clip.status != clip::Status::Rejected || clip.deleted_at.is_some() {
+            return Err(
+                Error::Conflict("Only rejected clips can be resubmitted".to_string()).into(),
+            );
+        }
+        let review = clip_review::ActiveModel {
+            clip_id: Set(clip.id),
+            user_id: Set(user.id),
+            decision: Set(clip_review::Decision::Resubmitted),
+            note: Set(normalize_review_note(note)?),
+            created_at: Set(chrono::Utc::now().into()),
+            ..Default::default()
+        };
+        let mut active_clip = clip.into_active_model();
+        active_clip.status = Set(clip::Status::Reviewing);
+        active_clip.review_note = Set(None);
+        let clip = self
+            .clip_data
+            .update_clip_with_review(active_clip, review)
+            .await?;
+        debug!("Clip {} resubmitted by {}", uuid, user.id);
+        Ok(clip)
+    }
+
+    pub async fn list_clip_reviews(
+        &self,
+        user: &user::Model,
+        uuid: Uuid,
+    ) -> anyhow::Result<Vec<(clip_review::Model, Option<user::Model>)>> {
+        let clip = self.get_clip(user, uuid).await?;
+        self.clip_data.list_reviews(clip.id).await
+    }
+
+    /// 待审核的切片，等待最久的在前
+    pub async fn review_queue(
+        &self,
+        reviewer: &user::Model,
+        page: u64,
+        page_size: u64,
+    ) -> anyhow::Result<ClipPage> {
+        let filter = ClipFilter {
+            statuses: vec![clip::Status::Reviewing],
+            sort: ClipSort::UploadTime,
+            ascending: true,
+            page,
+            page_size,
+            ..Default::default()
+        };
+        self.list_clips_by_user(reviewer, &filter).await
+    }
+
+    /// 移入回收站，保留期为 0 时直接永久删除
+    pub async fn delete_clip(&self, user: &user::Model, uuid: Uuid) -> anyhow::Result<()> {
+        trace!("Deleting clip {} for user {}", uuid.to_string(), user.id);
+        let clip = self
+            .clip_data
+            .get_clip_by_uuid(user.id, uuid)
+            .await?
+            .ok_or(Error::NotFound("Clip not found".to_string()))?;
+        if clip.deleted_at.is_some() {
+            return Ok(());
+        }
+        if self.upload_config.trash.retention == 0 {
+            return self.purge(uuid).await;
+        }
+
+        self.clip_data.trash_clip(clip).await?;
+        debug!("Clip {} moved to trash", uuid.to_string());
+        Ok(())
+    }
+
+    /// 在保留期内从回收站恢复，放回原来的播放列表位置
+    pub async fn restore_clip(
+        &self,
+        user: &user::Model,
+        uuid: Uuid,
+    ) -> anyhow::Result<clip::Model> {
+        let clip = self.get_trashed_clip(user, uuid).await?;
+        if clip
+            .deleted_at
+            .is_some_and(|t| t < self.upload_config.trash.expiry_cutoff())
+        {
+            return Err(Error::Conflict("Clip has expired from the trash".to_string()).into());
+        }
+        let clip = self.clip_data.restore_clip(clip).await?;
+        debug!("Clip {} restored from trash", uuid);
+        Ok(clip)
+    }
+
+    /// 永久删除回收站中的切片
+    pub async fn purge_clip(&self, user: &user::Model, uuid: Uuid) -> anyhow::Result<()> {
+        self.get_trashed_clip(user, uuid).await?;
+        self.purge(uuid).await
+    }
+
+    async fn get_trashed_clip(
+        &self,
+        user: &user::Model,
+        uuid: Uuid,
+    ) -> anyhow::Result<clip::Model> {
+        match self.clip_data.get_clip(uuid).await? {
+            Some(c) if c.user_id != user.id && !user.is_admin => {
+                Err(Error::NotFound("Clip not found".to_string()).into())
+            }
+            Some(c) if c.deleted_at.is_some() => Ok(c),
+            Some(_) => Err(Error::Conflict("Clip is not in the trash".to_string()).into()),
+            None => Err(Error::NotFound("Clip not found".to_string()).into()),
+        }
+    }
+
+    async fn purge(&self, uuid: Uuid) -> anyhow::Result<()> {
+        self.delete_clip_versions(uuid).await;
+        self.delete_clip_files(uuid).await;
+
+        self.clip_data.delete_clip_with_playlist_items(uuid).await?;
+        debug!("Clip {} deleted successfully", uuid.to_string());
+        Ok(())
+    }
+
+    /// 补全记录时长之前就已处理的切片的时长，供播放列表计算时间表
+    pub async fn backfill_durations(self: Arc<Self>) {
+        let clips = match self.clip_data.list_processed_without_duration().await {
+            Ok(clips) => clips,
+            Err(e) => {
+                warn!("Failed to list clips without duration: {}", e);
+                return;
+            }
+        };
+        for clip in clips {
+            let path = self.tmp_dir.join(format!("{}_probe.mp4", clip.uuid));
+            let probed = async {
+                self.download_file(&format!("{}.mp4", clip.uuid), &path)
+                    .await?;
+                media::probe(&path).await
+            }
+            .await;
+            tokio::fs::remove_file(&path).await.ok();
+            let duration_ms = match probed {
+                Ok(info) if info.duration_ms.is_some() => info.duration_ms,
+                Ok(_) => {
+                    warn!("Clip {} has no duration", clip.uuid);
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Failed to probe clip {}: {}", clip.uuid, e);
+                    continue;
+                }
+            };
+            let uuid = clip.uuid;
+            let mut active_clip = clip.into_active_model();
+            active_clip.duration_ms = Set(duration_ms);
+            match self.clip_data.update_clip(active_clip).await {
+                Ok(_) => debug!("Backfilled duration of clip {}", uuid),
+                Err(e) => warn!("Failed to update duration of clip {}: {}", uuid, e),
+            }
+        }
+    }
+
+    /// 定期永久删除超过保留期的回收站切片
+    pub async fn run_trash_purge(self: Arc<Self>) {
+        let trash = &self.upload_config.trash;
+        if trash.retention == 0 {
+            return;
+        }
+        let mut interval = tokio::time::interval(Duration::from_secs(trash.purge_interval.max(1)));
+        loop {
+            interval.tick().await;
+            let clips = match self
+                .clip_data
+                .list_trashed_before(trash.expiry_cutoff())
+                .await
+            {
+                Ok(clips) => clips,
+                Err(e) => {
+                    warn!("Failed to list expired clips in trash: {}", e);
+                    continue;
+                }
+            };
+            for clip in clips {
+                match self.purge(clip.uuid).await {
+                    Ok(()) => info!("Purged clip {} from trash", clip.uuid),
+                    Err(e) => error!("Failed to purge clip {}: {}", clip.uuid, e),
+                }
+            }
+        }
+    }
+
+    async fn delete_clip_files(&self, uuid: Uuid) {
+        self.storage
+            .delete_file(&format!("{}.mp4", uuid.to_string()))
+            .await
+            .map_err(|e| {
+                error!("Failed to delete clip file from storage: {}", e);
+            })
+            .ok();
+
+        for thumbnail in Thumbnail::ALL {
+            self.storage
+                .delete_file(&thumbnail.file_name(uuid))
+                .await
+                .map_err(|e| {
+                    debug!("Failed to delete clip thumbnail from storage: {}", e);
+                })
+                .ok();
+        }
+    }
+
+    pub async fn _get_clip_stream(
+        &self,
+        uuid: Uuid,
+    ) -> anyhow::Result<Box<dyn AsyncRead + Unpin + Send + 'static>> {
+        trace!("Getting clip stream for UUID: {}", uuid);
+
+        let file_name = format!("{}.mp4", uuid);
+
+        self.storage.get_file(&file_name).await.map_err(|e| {
+            error!("Failed to get clip stream for {}: {}", uuid, e);
+            anyhow!("Failed to get clip stream: {}", e)
+        })
+    }
+
+    pub async fn get_clip_stream_with_range(
+        &self,
+        uuid: Uuid,
+        range_header: Option<&axum::http::HeaderValue>,
+    ) -> anyhow::Result<(
+        Box<dyn AsyncRead + Unpin + Send + 'static>,
+        u64,
+        Option<(u64, u64)>,
+    )> {
+        trace!("Getting clip stream with range for UUID: {}", uuid);
+
+        let file_name = format!("{}.mp4", uuid);
+
+        let file_size = self
+            .storage
+            .get_file_size(&file_name)
+            .await
+            .map_err(|e| anyhow!("Failed to get file size: {}", e))?;
+
+        let range_info = if let Some(range_val) = range_header {
+            if let Ok(range_str) = range_val.to_str() {
+                parse_range_header(range_str, file_size)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        let stream = if let Some((start, end)) = range_info {
+            self.storage
+                .get_file_range(&file_name, start, end)
+                .await
+                .map_err(|e| anyhow!("Failed to get file range: {}", e))?
+        } else {
+            self.storage
+                .get_file(&file_name)
+                .await
+                .map_err(|e| anyhow!("Failed to get file: {}", e))?
+        };
+
+        Ok((stream, file_size, range_info))
+    }
+
+    pub async fn get_thumbnail(
+        &self,
+        uuid: Uuid,
+        thumbnail: Thumbnail,
+    ) -> anyhow::Result<(Box<dyn AsyncRead + Unpin + Send + 'static>, u64)> {
+        let file_name = thumbnail.file_name(uuid);
+        let file_size = self.storage.get_file_size(&file_name).await?;
+        let stream = self.storage.get_file(&file_name).await?;
+        Ok((stream, file_size))
+    }
+
+    async fn generate_thumbnails(
+        &self,
+        clip: &clip::Model,
+        path: &Path,
+        info: &media::MediaInfo,
+    ) -> anyhow::Result<()> {
+        let (Some(duration_ms), Some(width), Some(height)) =
+            (info.duration_ms, info.width, info.height)
+        else {
+            debug!(
+                "Clip {} has no video stream, skipping thumbnails",
+                clip.uuid
+            );
+            return Ok(());
+        };
+        if duration_ms <= 0 || width <= 0 || height <= 0 {
+            return Ok(());
+        }
+
+        let poster_path = self.tmp_dir.join(Thumbnail::Poster.file_name(clip.uuid));
+        let sprite_path = self.tmp_dir.join(Thumbnail::Sprite.file_name(clip.uuid));
+        let vtt_path = self.tmp_dir.join(Thumbnail::SpriteVtt.file_name(clip.uuid));
+        let layout = media::SpriteLayout::new(
+            self.config.sprite_columns,
+            self.config.sprite_rows,
+            self.config.sprite_tile_width,
+            width,
+            height,
+        );
+
+        let result = async {
+            // 取 10% 处的画面作为封面，避开片头黑场
+            media::extract_poster(
+                path,
+                &poster_path,
+                (duration_ms / 10).min(5000),
+                self.config.poster_width,
+            )
+            .await?;
+            media::extract_sprite(path, &sprite_path, duration_ms, layout).await?;
+            let vtt = media::sprite_vtt(SPRITE_VTT_IMAGE, duration_ms, layout);
+            tokio::fs::write(&vtt_path, vtt).await?;
+
+            for (thumbnail, file) in [
+                (Thumbnail::Poster, &poster_path),
+                (Thumbnail::Sprite, &sprite_path),
+                (Thumbnail::SpriteVtt, &vtt_path),
+            ] {
+                self.storage
+                    .store_file(thumbnail.file_name(clip.uuid), file)
+                    .await?;
+            }
+            anyhow::Ok(())
+        }
+        .await;
+
+        for file in [&poster_path, &sprite_path, &vtt_path] {
+            tokio::fs::remove_file(file).await.ok();
+        }
+        result
+    }
+
+    /// 计算音频指纹并与已有切片比对，返回指纹和最相似的切片
+    async fn match_fingerprint(
+        &self,
+        clip: &clip::Model,
+        path: &Path,
+    ) -> anyhow::Result<(Vec<u8>, Option<Uuid>)> {
+        let fp = media::audio_fingerprint(path, FINGERPRINT_MAX_SECS).await?;
+        let mut best: Option<(Uuid, f64)> = None;
+        for (uuid, other) in self.clip_data.list_fingerprints().await? {
+            if uuid == clip.uuid {
+                continue;
+            }
+            let similarity =
+                media::fingerprint_similarity(&fp, &media::fingerprint_from_bytes(&other));
+            if similarity >= self.config.fingerprint_threshold
+                && best.is_none_or(|(_, s)| similarity > s)
+            {
+                best = Some((uuid, similarity));
+            }
+        }
+        if let Some((uuid, similarity)) = best {
+            debug!(
+                "Clip {} sounds like {} (similarity {:.3})",
+                clip.uuid, uuid, similarity
+            );
+        }
+        Ok((media::fingerprint_to_bytes(&fp), best.map(|(uuid, _)| uuid)))
+    }
+
+    pub async fn list_duplicate_clips(&self) -> anyhow::Result<Vec<clip::Model>> {
+        trace!("Listing duplicate clips");
+        self.clip_data.list_duplicate_clips().await
+    }
+
+    /// 合并重复切片，保留 `into`，播放列表中的引用原位替换
+    pub async fn merge_clip(&self, from: Uuid, into: Uuid) -> anyhow::Result<Option<clip::Model>> {
+        trace!("Merging clip {} into {}", from, into);
+        if from == into {
+            return Err(Error::BadRequest("Cannot merge a clip into itself".to_string()).into());
+        }
+        if let Some(target) = self.clip_data.get_clip(into).await?
+            && target.deleted_at.is_some()
+        {
+            return Err(
+                Error::Conflict("Cannot merge into a clip in the trash".to_string()).into(),
+            );
+        }
+        // 历史版本记录随源切片一起删除，先取出以便合并成功后清理文件
+        let versions = match self.clip_data.get_clip(from).await? {
+            Some(clip) => self.clip_data.list_versions(clip.id).await?,
+            None => Vec::new(),
+        };
+        let Some(clip) = self.clip_data.merge_clips(from, into).await? else {
+            return Ok(None);
+        };
+        for version in &versions {
+            self.delete_version(from, version).await;
+        }
+        self.delete_clip_files(from).await;
+        debug!("Clip {} merged into {}", from, into);
+        Ok(Some(clip))
+    }
+
+    async fn set_progress(&self, clip_id: i64, stage: clip::ProcessStage, progress: i32) {
+        self.clip_data
+            .update_progress(clip_id, Some(stage), Some(progress))
+            .await
+            .map_err(|e| {
+                warn!("Failed to update progress for clip {}: {}", clip_id, e);
+            })
+            .ok();
+    }
+
+    async fn analyze_loudness(
+        &self,
+        clip: &clip::Model,
+        input_path: &Path,
+        duration_ms: Option<i64>,
+    ) -> anyhow::Result<LoudnormStats> {
+        self.set_progress(clip.id, clip::ProcessStage::Analyzing, 0)
+            .await;
+        let (status, stderr) = media::ffmpeg_with_progress(
+            [
+                OsStr::new("-i"),
+                input_path.as_os_str(),
+                OsStr::new("-af"),
+                OsStr::new("loudnorm=print_format=json"),
+                OsStr::new("-f"),
+                OsStr::new("null"),
+                OsStr::new("/dev/null"),
+            ],
+            duration_ms,
+            |p| self.set_progress(clip.id, clip::ProcessStage::Analyzing, p),
+        )
+        .await
+        .map_err(|e| anyhow!("Failed to run ffmpeg to analyze loudness: {}", e))?;
+
+        if !status.success() {
+            anyhow::bail!(
+                "FFmpeg loudness analysis failed with status: {}, file: {}, output: {}",
+                status,
+                input_path.display(),
+                stderr
+            );
+        }
+
+        let Some(info) = media::parse_loudnorm(&stderr) else {
+            anyhow::bail!(
+                "Failed to find loudnorm analysis in output, file: {}, output: {}",
+                input_path.display(),
+                stderr
+            );
+        };
+        trace!("FFmpeg loudnorm analysis: {:?}", info);
+        Ok(info)
+    }
+
+    async fn detect_edges(
+        &self,
+        clip: &clip::Model,
+        input_path: &Path,
+        info: &media::MediaInfo,
+    ) -> anyhow::Result<Option<media::Edges>> {
+        let Some(duration_ms) = info.duration_ms else {
+            return Ok(None);
+        };
+        let cfg = &self.config.edge_detection;
+        let (has_audio, has_video) = (info.audio_codec.is_some(), info.video_codec.is_some());
+
+        let mut args: Vec<OsString> = vec!["-hide_banner".into(), "-i".into(), input_path.into()];
+        if has_audio {
+            args.push("-af".into());
+            args.push(
+                format!(
+                    "silencedetect=noise={}dB:d={}",
+                    cfg.silence_noise_db, cfg.min_duration
+                )
+                .into(),
+            );
+        }
+        if has_video {
+            args.push("-vf".into());
+            args.push(
+                format!(
+                    "blackdetect=d={}:pix_th={}",
+                    cfg.min_duration, cfg.black_pixel_threshold
+                )
+                .into(),
+            );
+        }
+        args.extend(["-f".into(), "null".into(), "-".into()]);
+
+        self.set_progress(clip.id, clip::ProcessStage::Detecting, 0)
+            .await;
+        let (status, stderr) = media::ffmpeg_with_progress(args, Some(duration_ms), |p| {
+            self.set_progress(clip.id, clip::ProcessStage::Detecting, p)
+        })
+        .await?;
+        if !status.success() {
+            anyhow::bail!("FFmpeg edge detection failed with status: {}", status);
+        }
+        Ok(media::parse_edges(
+            &stderr,
+            duration_ms,
+            has_audio,
+            has_video,
+        ))
+    }
+
+    async fn transcode_and_normalize(
+        &self,
+        clip: &clip::Model,
+        input_path: &Path,
+        output_path: &Path,
+        duration_ms: Option<i64>,
+        trim: Option<Trim>,
+        effects: Option<Effects>,
+    ) -> anyhow::Result<Normalized> {
+        let loudness = &self.config.loudness;
+        // 已有已存储文件的测量值时直接复用，只需按新目标重新处理
+        let (measured, source) = match stored_measurements(clip) {
+            Some(measured) => (Some(measured), None),
+            // 不标准化时只为可能自动通过的切片测量
+            None if loudness.mode == LoudnessMode::None && !self.may_auto_approve(clip).await => {
+                (None, None)
+            }
+            None => {
+                let measured = self.analyze_loudness(clip, input_path, duration_ms).await?;
+                (Some(measured.clone()), Some(measured))
+            }
+        };
+
+        let normalize = match (loudness.mode, &measured) {
+            (LoudnessMode::Gain, Some(m)) => {
+                Some(format!("volume={:.2}dB", loudness.target_i - m.input_i))
+            }
+            (LoudnessMode::TwoPass, Some(m)) => Some(format!(
+                "loudnorm=linear=true:I={}:TP={}:LRA={}:measured_I={}:measured_TP={}:measured_LRA={}:measured_thresh={}:print_format=json",
+                loudness.target_i,
+                loudness.target_tp,
+                loudness.target_lra,
+                m.input_i,
+                m.input_tp,
+                m.input_lra,
+                m.input_thresh
+            )),
+            _ => None,
+        };
+        let output_duration_ms = trim.map(|t| t.out_ms - t.in_ms).or(duration_ms);
+        let mut effects = effects.unwrap_or_default();
+        // 音量调整在响度标准化之后，即相对于目标响度。
+        // 标准化会抵消文件中已有的调整，需要连同之前的调整一起重新应用
+        if normalize.is_some() {
+            effects.volume_db += clip.volume_db;
+        }
+        let filter: Vec<String> = normalize
+            .into_iter()
+            .chain(effects.audio_filters(output_duration_ms))
+            .collect();
+
+        self.set_progress(clip.id, clip::ProcessStage::Normalizing, 0)
+            .await;
+        let mut args: Vec<OsString> = vec!["-y".into(), "-hide_banner".into()];
+        if let Some(trim) = trim {
+            args.extend(["-ss".into(), ms_to_secs(trim.in_ms).into()]);
+        }
+        args.extend(["-i".into(), input_path.into()]);
+        if let Some(trim) = trim {
+            args.extend(["-t".into(), ms_to_secs(trim.out_ms - trim.in_ms).into()]);
+        }
+        if !filter.is_empty() {
+            args.extend(["-af".into(), filter.join(",").into()]);
+        }
+        args.extend(["-ar".into(), loudness.sample_rate.to_string().into()]);
+        if self.config.ingest_transcode {
+            // 只有重新编码时才能对画面淡入淡出
+            let video_filters = effects.video_filters(output_duration_ms);
+            let mut profile = media::stream_profile_args(&self.stream_config);
+            if let Some(vf) = profile.iter().position(|a| a == "-vf") {
+                for f in video_filters {
+                    profile[vf + 1].push(',');
+                    profile[vf + 1].push_str(&f);
+                }
+            }
+            args.extend(profile.into_iter().map(OsString::from));
+        } else {
+            args.extend(["-vcodec".into(), "copy".into()]);
+        }
+        args.push(output_path.into());
+        let (status, stderr) = media::ffmpeg_with_progress(args, output_duration_ms, |p| {
+            self.set_progress(clip.id, clip::ProcessStage::Normalizing, p)
+        })
+        .await?;
 
-        Ok(())
+        if !status.success() {
+            anyhow::bail!(
+                "FFmpeg command failed with status: {}, output: {}",
+                status,
+                stderr
+            );
+        }
+
+        // 记录处理后文件的响度，之后调整目标时可直接复用
+        let output = match loudness.mode {
+            LoudnessMode::TwoPass => media::parse_loudnorm(&stderr).and_then(|info| info.output()),
+            LoudnessMode::Gain => measured.map(|m| m.with_gain(loudness.target_i - m.input_i)),
+            LoudnessMode::None => measured,
+        };
+        Ok(Normalized {
+            source,
+            output: output.map(|m| m.with_gain(effects.volume_db)),
+        })
+    }
+}
+
+async fn sha256_file(path: &Path) -> anyhow::Result<String> {
+    let mut file = File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn ms_to_secs(ms: i64) -> String {
+    format!("{:.3}", ms as f64 / 1000.0)
+}
+
+/// `transcode_and_normalize` 的响度结果
+struct Normalized {
+    // 本次从输入文件测得的原始响度，复用已存储的测量值时为空
+    source: Option<LoudnormStats>,
+    // 处理后文件的响度，写入切片供之后复用
+    output: Option<LoudnormStats>,
+}
+
+/// 可信用户、未被退回过且原始文件满足规则时返回审核说明，否则返回原因
+fn auto_approve_note(
+    rules: &AutoApproveConfig,
+    owner: &user::Model,
+    rejected_before: bool,
+    duration_ms: Option<i64>,
+    normalized: &Normalized,
+    duplicate: bool,
+) -> Result<String, String> {
+    if !owner.is_trusted || owner.is_disabled {
+        return Err("Uploader is not trusted".to_string());
+    }
+    // 被退回过的切片需要审核员确认修改
+    if rejected_before {
+        return Err("Clip was rejected before".to_string());
+    }
+    // 标准化后的响度总是接近目标值，只能检查原始文件
+    let loudness = normalized.source.as_ref().map(|m| m.input_i);
+    rules.check(duration_ms, loudness, duplicate)
+}
+
+fn stored_measurements(clip: &clip::Model) -> Option<LoudnormStats> {
+    Some(LoudnormStats::new(
+        clip.measured_i?,
+        clip.measured_tp?,
+        clip.measured_lra?,
+        clip.measured_thresh?,
+    ))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeMode {
+    Off,
+    /// 记录建议的入点和出点，由上传者确认
+    Suggest,
+    /// 处理时直接裁掉
+    Trim,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct EdgeDetectionConfig {
+    pub mode: EdgeMode,
+    pub silence_noise_db: f64,
+    pub min_duration: f64, // seconds
+    pub black_pixel_threshold: f64,
+}
+
+impl Default for EdgeDetectionConfig {
+    fn default() -> Self {
+        Self {
+            mode: EdgeMode::Off,
+            silence_noise_db: -50.0,
+            min_duration: 0.5,
+            black_pixel_threshold: 0.1,
+        }
+    }
+}
+
+/// 可信用户的切片处理完成后，满足以下条件时直接通过审核
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AutoApproveConfig {
+    pub enabled: bool,
+    pub min_duration: f64,         // seconds
+    pub max_duration: Option<f64>, // seconds
+    // 上传文件标准化前的整体响度，复用已有测量值（如重新处理）时不会自动通过
+    pub min_loudness: f64, // LUFS
+    pub max_loudness: f64, // LUFS
+    pub allow_duplicates: bool,
+}
+
+impl Default for AutoApproveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_duration: 5.0,
+            max_duration: None,
+            min_loudness: -40.0,
+            max_loudness: -5.0,
+            allow_duplicates: false,
+        }
+    }
+}
+
+impl AutoApproveConfig {
+    /// 检查处理结果，通过时返回写入审核记录的说明，否则返回未通过的原因
+    fn check(
+        &self,
+        duration_ms: Option<i64>,
+        loudness: Option<f64>,
+        duplicate: bool,
+    ) -> Result<String, String> {
+        let duration = duration_ms.ok_or("Duration is unknown")? as f64 / 1000.0;
+        if duration < self.min_duration {
+            return Err(format!("Duration {duration:.1}s is too short"));
+        }
+        if self.max_duration.is_some_and(|max| duration > max) {
+            return Err(format!("Duration {duration:.1}s is too long"));
+        }
+        let loudness = loudness.ok_or("Loudness was not measured")?;
+        if !(self.min_loudness..=self.max_loudness).contains(&loudness) {
+            return Err(format!("Loudness {loudness:.1} LUFS is out of range"));
+        }
+        if duplicate && !self.allow_duplicates {
+            return Err("Clip is a suspected duplicate".to_string());
+        }
+        Ok(format!(
+            "Duration {duration:.1}s, loudness {loudness:.1} LUFS"
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoudnessMode {
+    TwoPass,
+    Gain,
+    None,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LoudnessConfig {
+    pub mode: LoudnessMode,
+    pub target_i: f64,   // LUFS
+    pub target_tp: f64,  // dBTP
+    pub target_lra: f64, // LU
+    pub sample_rate: u32,
+}
+
+impl Default for LoudnessConfig {
+    fn default() -> Self {
+        Self {
+            mode: LoudnessMode::TwoPass,
+            target_i: -14.0,
+            target_tp: 0.0,
+            target_lra: 50.0,
+            sample_rate: 48000,
+        }
+    }
+}
+
+/// 参与指纹计算的最大音频时长
+const FINGERPRINT_MAX_SECS: u32 = 120;
+
+/// VTT 中引用雪碧图时使用的相对路径，由接口层补上访问令牌
+pub const SPRITE_VTT_IMAGE: &str = "sprite.jpg";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Thumbnail {
+    Poster,
+    Sprite,
+    SpriteVtt,
+}
+
+impl Thumbnail {
+    const ALL: [Thumbnail; 3] = [Thumbnail::Poster, Thumbnail::Sprite, Thumbnail::SpriteVtt];
+
+    pub fn file_name(&self, uuid: Uuid) -> String {
+        match self {
+            Thumbnail::Poster => format!("{uuid}.jpg"),
+            Thumbnail::Sprite => format!("{uuid}_sprite.jpg"),
+            Thumbnail::SpriteVtt => format!("{uuid}_sprite.vtt"),
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Thumbnail::Poster | Thumbnail::Sprite => "image/jpeg",
+            Thumbnail::SpriteVtt => "text/vtt",
+        }
+    }
+}
+
+/// 从文件名解析切片信息的模板，如 `{vup} - {song}.mp4`，可用 `{title}`、`{vup}`、`{song}`。
+/// 扩展名不参与匹配。
+#[derive(Debug, Clone)]
+pub struct FileNamePattern {
+    regex: Regex,
+}
+
+impl FileNamePattern {
+    pub fn new(pattern: &str) -> anyhow::Result<Self> {
+        let mut expr = String::from("^");
+        let mut rest = file_stem(pattern);
+        let mut seen = HashSet::new();
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..]
+                .find('}')
+                .map(|i| start + i)
+                .ok_or_else(|| anyhow!("Unclosed placeholder in pattern {}", pattern))?;
+            let name = &rest[start + 1..end];
+            if !["title", "vup", "song"].contains(&name) || !seen.insert(name) {
+                anyhow::bail!("Invalid placeholder {{{}}} in pattern {}", name, pattern);
+            }
+            expr.push_str(&regex::escape(&rest[..start]));
+            expr.push_str(&format!("(?P<{name}>.+?)"));
+            rest = &rest[end + 1..];
+        }
+        expr.push_str(&regex::escape(rest));
+        expr.push('$');
+        Ok(Self {
+            regex: Regex::new(&expr)?,
+        })
+    }
+
+    /// 解析出标题、VUP 和歌曲，没有 `{title}` 时用文件名作为标题
+    pub fn parse(&self, file_name: &str) -> Option<clip::Model> {
+        let stem = file_stem(file_name);
+        let caps = self.regex.captures(stem)?;
+        let get = |name| {
+            caps.name(name)
+                .map(|m| m.as_str().trim().to_string())
+                .unwrap_or_default()
+        };
+        let title = match get("title") {
+            t if t.is_empty() => stem.trim().to_string(),
+            t => t,
+        };
+        Some(clip::Model {
+            title,
+            vup: get("vup"),
+            song: get("song"),
+            ..Default::default()
+        })
+    }
+}
+
+fn check_media(info: &media::MediaInfo, config: &ValidationConfig) -> Result<(), String> {
+    let has_video = info.video_codec.is_some();
+    let has_audio = info.audio_codec.is_some();
+    if !has_video && !has_audio {
+        return Err("File has no audio or video stream".to_string());
+    }
+    if config.require_video && !has_video {
+        return Err("File has no video stream".to_string());
+    }
+    if config.require_audio && !has_audio {
+        return Err("File has no audio stream".to_string());
+    }
+
+    let duration = info.duration_ms.unwrap_or(0) as f64 / 1000.0;
+    if duration < config.min_duration {
+        return Err(format!(
+            "Duration {:.1}s is shorter than {:.1}s",
+            duration, config.min_duration
+        ));
+    }
+    if let Some(max) = config.max_duration.filter(|max| duration > *max) {
+        return Err(format!(
+            "Duration {:.1}s is longer than {:.1}s",
+            duration, max
+        ));
+    }
+
+    if let (Some(width), Some(height)) = (info.width, info.height) {
+        let too_wide = config.max_width.is_some_and(|max| width > max);
+        let too_tall = config.max_height.is_some_and(|max| height > max);
+        if too_wide || too_tall {
+            return Err(format!(
+                "Resolution {}x{} exceeds {}x{}",
+                width,
+                height,
+                config.max_width.map_or("-".to_string(), |w| w.to_string()),
+                config.max_height.map_or("-".to_string(), |h| h.to_string()),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 允许上传的视频文件扩展名
+pub const SUPPORTED_EXTENSIONS: [&str; 5] = ["mp4", "mkv", "avi", "mov", "flv"];
+
+const IMPORT_FAILED: &str = "Failed to import from the given URL";
+
+/// 导入只允许访问公网地址，排除回环、私有、链路本地等地址
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a == 0
+                // 100.64.0.0/10 运营商级 NAT
+                || (a == 100 && (64..128).contains(&b))
+                // 198.18.0.0/15 基准测试
+                || (a == 198 && (b == 18 || b == 19))
+                || a >= 240)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(v4) = ip.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(v4));
+            }
+            let first = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // fc00::/7 唯一本地地址
+                || (first & 0xfe00) == 0xfc00
+                // fe80::/10 链路本地地址
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+fn extension_for_content_type(content_type: &str) -> Option<&'static str> {
+    match content_type {
+        "video/mp4" => Some("mp4"),
+        "video/x-matroska" | "video/webm" => Some("mkv"),
+        "video/x-msvideo" => Some("avi"),
+        "video/quicktime" => Some("mov"),
+        "video/x-flv" => Some("flv"),
+        _ => None,
+    }
+}
+
+fn file_stem(file_name: &str) -> &str {
+    match file_name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && !ext.contains(['{', '}', ' ']) => stem,
+        _ => file_name,
+    }
+}
+
+/// 可续传上传的会话信息，与分片数据一起保存在 `tmp_dir/uploads` 中，重启后仍可续传
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UploadSession {
+    pub id: Uuid,
+    pub user_id: i64,
+    pub ext: String,
+    pub size: u64,
+    pub title: String,
+    pub vup: String,
+    pub song: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(skip)]
+    pub offset: u64,
+}
+
+struct UploadGuard {
+    id: Uuid,
+    active: Arc<std::sync::Mutex<HashSet<Uuid>>>,
+}
+
+impl Drop for UploadGuard {
+    fn drop(&mut self) {
+        self.active.lock().unwrap().remove(&self.id);
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct UploadConfig {
+    pub expiry: u64, // seconds，未完成的可续传上传保留时长
+    pub max_batch_files: usize,
+    // 批量上传未提供元数据时用来解析文件名，如 "{vup} - {song}.mp4"
+    pub file_name_pattern: Option<String>,
+    pub import: ImportConfig,
+    pub validation: ValidationConfig,
+    pub quota: QuotaConfig,
+    // 替换文件时保留的历史版本数，0 表示不保留
+    pub max_versions: usize,
+    pub trash: TrashConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TrashConfig {
+    // 删除的切片在回收站中保留的天数，0 表示直接永久删除
+    pub retention: u64,      // days
+    pub purge_interval: u64, // seconds
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self {
+            retention: 30,
+            purge_interval: 3600,
+        }
+    }
+}
+
+impl TrashConfig {
+    /// 早于这个时间移入回收站的切片已过保留期
+    fn expiry_cutoff(&self) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc::now() - chrono::Duration::days(self.retention as i64)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ValidationConfig {
+    pub require_video: bool,
+    pub require_audio: bool,
+    pub min_duration: f64,         // seconds
+    pub max_duration: Option<f64>, // seconds
+    pub max_width: Option<i32>,
+    pub max_height: Option<i32>,
+    // 试解码的时长，用来发现损坏的文件
+    pub decode_check_secs: u32,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        Self {
+            require_video: false,
+            require_audio: false,
+            min_duration: 1.0,
+            max_duration: None,
+            max_width: Some(3840),
+            max_height: Some(2160),
+            decode_check_secs: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ImportConfig {
+    // 允许导入的主机，为空时不允许导入
+    pub allowed_hosts: Vec<String>,
+    // 允许的 Content-Type 前缀
+    pub content_types: Vec<String>,
+    pub timeout: u64, // seconds
+}
+
+impl Default for ImportConfig {
+    fn default() -> Self {
+        Self {
+            allowed_hosts: Vec::new(),
+            content_types: vec!["video/".to_string(), "application/octet-stream".to_string()],
+            timeout: 600,
+        }
+    }
+}
+
+impl Default for UploadConfig {
+    fn default() -> Self {
+        Self {
+            expiry: 86400,
+            max_batch_files: 20,
+            file_name_pattern: None,
+            import: ImportConfig::default(),
+            validation: ValidationConfig::default(),
+            quota: QuotaConfig::default(),
+            max_versions: 5,
+            trash: TrashConfig::default(),
+        }
+    }
+}
+
+const MAX_REVIEW_NOTE_LEN: usize = 1000;
+
+fn normalize_review_note(note: Option<String>) -> anyhow::Result<Option<String>> {
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    if note
+        .as_ref()
+        .is_some_and(|n| n.chars().count() > MAX_REVIEW_NOTE_LEN)
+    {
+        return Err(Error::BadRequest(format!(
+            "Review note must be at most {MAX_REVIEW_NOTE_LEN} characters"
+        ))
+        .into());
+    }
+    Ok(note)
+}
+
+pub const DEFAULT_PAGE_SIZE: u64 = 20;
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+/// 切片列表的筛选条件
+#[derive(Clone, Debug, Default)]
+pub struct ClipFilter {
+    // 需同时包含的标签
+    pub tags: Vec<i64>,
+    pub search: Option<String>,
+    pub statuses: Vec<clip::Status>,
+    // 只有管理员可以查看其他用户的切片
+    pub user_id: Option<i64>,
+    pub vup_id: Option<i64>,
+    pub song_id: Option<i64>,
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    // 查看回收站
+    pub trashed: bool,
+    pub sort: ClipSort,
+    pub ascending: bool,
+    // 从 1 开始
+    pub page: u64,
+    pub page_size: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipSort {
+    #[default]
+    UploadTime,
+    Title,
+    Vup,
+    Song,
+    Duration,
+    FileSize,
+    Status,
+    DeletedAt,
+}
+
+impl ClipSort {
+    fn column(self) -> clip::Column {
+        match self {
+            ClipSort::UploadTime => clip::Column::UploadTime,
+            ClipSort::Title => clip::Column::Title,
+            ClipSort::Vup => clip::Column::Vup,
+            ClipSort::Song => clip::Column::Song,
+            ClipSort::Duration => clip::Column::DurationMs,
+            ClipSort::FileSize => clip::Column::FileSize,
+            ClipSort::Status => clip::Column::Status,
+            ClipSort::DeletedAt => clip::Column::DeletedAt,
+        }
     }
 }
 
+/// 一页切片和符合条件的总数
 #[derive(Clone, Debug)]
+pub struct ClipPage {
+    pub clips: Vec<clip::Model>,
+    pub total: u64,
+    pub page: u64,
+    pub page_size: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ProcessJob {
-    pub clip: clip::Model,
+    pub clip_uuid: Uuid,
     pub input_path: PathBuf,
+    #[serde(default)]
+    pub trim: Option<Trim>,
+    #[serde(default)]
+    pub effects: Option<Effects>,
 }
-pub async fn process_clip(job: ProcessJob, data: Data<Arc<ClipService>>) -> anyhow::Result<()> {
-    debug!("Processing clip: {:?}", job.clip.uuid);
-    data.process_clip(job.clip, job.input_path).await?;
-    Ok(())
+
+/// 裁剪区间，相对于处理输入文件
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Trim {
+    pub in_ms: i64,
+    pub out_ms: i64,
+}
+
+/// 裁剪之后应用的淡入淡出和音量调整
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Effects {
+    pub fade_in_ms: i64,
+    pub fade_out_ms: i64,
+    pub volume_db: f64,
+}
+
+impl Effects {
+    fn audio_filters(&self, duration_ms: Option<i64>) -> Vec<String> {
+        let mut filters = Vec::new();
+        if self.volume_db != 0.0 {
+            filters.push(format!("volume={:.2}dB", self.volume_db));
+        }
+        filters.extend(self.fades("afade", duration_ms));
+        filters
+    }
+
+    fn video_filters(&self, duration_ms: Option<i64>) -> Vec<String> {
+        self.fades("fade", duration_ms)
+    }
+
+    fn fades(&self, filter: &str, duration_ms: Option<i64>) -> Vec<String> {
+        let mut filters = Vec::new();
+        if self.fade_in_ms > 0 {
+            filters.push(format!(
+                "{filter}=t=in:st=0:d={}",
+                ms_to_secs(self.fade_in_ms)
+            ));
+        }
+        if let Some(duration_ms) = duration_ms.filter(|_| self.fade_out_ms > 0) {
+            filters.push(format!(
+                "{filter}=t=out:st={}:d={}",
+                ms_to_secs((duration_ms - self.fade_out_ms).max(0)),
+                ms_to_secs(self.fade_out_ms)
+            ));
+        }
+        filters
+    }
+}
+
+/// 切片编辑请求，时间以毫秒计
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ClipEdit {
+    pub in_ms: Option<i64>,
+    pub out_ms: Option<i64>,
+    pub fade_in_ms: Option<i64>,
+    pub fade_out_ms: Option<i64>,
+    pub volume_db: Option<f64>,
+}
+
+pub const MAX_VOLUME_ADJUST_DB: f64 = 20.0;
+
+impl ClipEdit {
+    /// 校验并转换为裁剪区间和效果，未作修改的部分返回 `None`
+    pub fn resolve(
+        &self,
+        duration_ms: Option<i64>,
+    ) -> Result<(Option<Trim>, Option<Effects>), &'static str> {
+        let in_ms = self.in_ms.unwrap_or(0);
+        let out_ms = match (self.out_ms, duration_ms) {
+            (Some(out), Some(duration)) if out > duration => {
+                return Err("Out point is beyond the end of the clip");
+            }
+            (Some(out), _) => Some(out),
+            (None, duration) => duration,
+        };
+        if in_ms < 0 {
+            return Err("In point must not be negative");
+        }
+        if out_ms.is_some_and(|out| out <= in_ms) {
+            return Err("Out point must be after the in point");
+        }
+
+        let fade_in_ms = self.fade_in_ms.unwrap_or(0);
+        let fade_out_ms = self.fade_out_ms.unwrap_or(0);
+        if fade_in_ms < 0 || fade_out_ms < 0 {
+            return Err("Fade duration must not be negative");
+        }
+        let span = out_ms.map(|out| out - in_ms);
+        if fade_out_ms > 0 && span.is_none() {
+            return Err("Clip duration is unknown, specify an out point to fade out");
+        }
+        if span.is_some_and(|span| fade_in_ms + fade_out_ms > span) {
+            return Err("Fades are longer than the clip");
+        }
+        let volume_db = self.volume_db.unwrap_or(0.0);
+        if !volume_db.is_finite() || volume_db.abs() > MAX_VOLUME_ADJUST_DB {
+            return Err("Volume adjustment is out of range");
+        }
+
+        let trim = match (in_ms, out_ms) {
+            (0, out) if out.is_none() || out == duration_ms => None,
+            (in_ms, Some(out_ms)) => Some(Trim { in_ms, out_ms }),
+            (_, None) => return Err("Clip duration is unknown, specify an out point"),
+        };
+        let effects = Effects {
+            fade_in_ms,
+            fade_out_ms,
+            volume_db,
+        };
+        let effects = (effects != Effects::default()).then_some(effects);
+        Ok((trim, effects))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProcessError {
+    /// 输入文件本身有问题，重试无意义
+    #[error(transparent)]
+    Permanent(anyhow::Error),
+    /// 存储或数据库等暂时性错误，可以重试
+    #[error(transparent)]
+    Transient(anyhow::Error),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ProcessConfig {
+    pub max_retries: usize,
+    pub retry_min_delay: u64, // seconds
+    pub retry_max_delay: u64, // seconds
+    pub poster_width: i32,
+    pub sprite_columns: u32,
+    pub sprite_rows: u32,
+    pub sprite_tile_width: u32,
+    // 入库时按推流参数转码，配合 stream.video_passthrough 使用
+    pub ingest_transcode: bool,
+    // 处理完成后计算音频指纹，查找重新编码过的重复上传
+    pub audio_fingerprint: bool,
+    pub fingerprint_threshold: f64,
+    pub loudness: LoudnessConfig,
+    pub edge_detection: EdgeDetectionConfig,
+    pub auto_approve: AutoApproveConfig,
+}
+
+impl Default for ProcessConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            retry_min_delay: 10,
+            retry_max_delay: 300,
+            poster_width: 480,
+            sprite_columns: 10,
+            sprite_rows: 10,
+            sprite_tile_width: 160,
+            ingest_transcode: false,
+            audio_fingerprint: false,
+            fingerprint_threshold: 0.9,
+            loudness: LoudnessConfig::default(),
+            edge_detection: EdgeDetectionConfig::default(),
+            auto_approve: AutoApproveConfig::default(),
+        }
+    }
+}
+
+pub async fn process_clip(job: ProcessJob, data: Data<Arc<ClipService>>) -> Result<(), JobError> {
+    debug!("Processing clip: {:?}", job.clip_uuid);
+    data.process_clip(job).await.map_err(|e| match e {
+        ProcessError::Permanent(e) => JobError::Abort(Arc::new(e.into())),
+        ProcessError::Transient(e) => JobError::Failed(Arc::new(e.into())),
+    })
 }
 
 fn parse_range_header(range: &str, file_size: u64) -> Option<(u64, u64)> {
@@ -471,3 +2913,194 @@ fn parse_range_header(range: &str, file_size: u64) -> Option<(u64, u64)> {
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_public_ip() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "0.0.0.0",
+            "100.64.0.1",
+            "::1",
+            "::",
+            "fe80::1",
+            "fd00::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in ["8.8.8.8", "1.1.1.1", "2606:4700::1111"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[test]
+    fn test_auto_approve_uses_source_loudness() {
+        let rules = AutoApproveConfig::default();
+        let owner = user::Model {
+            is_trusted: true,
+            ..Default::default()
+        };
+        let normalized = |source: Option<f64>| Normalized {
+            source: source.map(|i| LoudnormStats::new(i, -2.0, 5.0, i - 10.0)),
+            // 标准化后总是接近目标响度
+            output: Some(LoudnormStats::new(-14.0, -1.0, 5.0, -24.0)),
+        };
+        let decide = |owner: &user::Model, rejected: bool, source: Option<f64>| {
+            auto_approve_note(
+                &rules,
+                owner,
+                rejected,
+                Some(60_000),
+                &normalized(source),
+                false,
+            )
+        };
+
+        assert!(decide(&owner, false, Some(-18.0)).is_ok());
+        // 原始文件过轻
+        assert!(decide(&owner, false, Some(-55.0)).is_err());
+        // 复用已存储的测量值时不知道原始响度
+        assert!(decide(&owner, false, None).is_err());
+        assert!(decide(&owner, true, Some(-18.0)).is_err());
+        assert!(decide(&user::Model::default(), false, Some(-18.0)).is_err());
+    }
+
+    #[test]
+    fn test_auto_approve_check() {
+        let rules = AutoApproveConfig {
+            max_duration: Some(600.0),
+            ..Default::default()
+        };
+        assert!(rules.check(Some(180_000), Some(-14.0), false).is_ok());
+        assert!(rules.check(Some(1_000), Some(-14.0), false).is_err());
+        assert!(rules.check(Some(900_000), Some(-14.0), false).is_err());
+        assert!(rules.check(None, Some(-14.0), false).is_err());
+        assert!(rules.check(Some(180_000), Some(-50.0), false).is_err());
+        assert!(rules.check(Some(180_000), None, false).is_err());
+        assert!(rules.check(Some(180_000), Some(-14.0), true).is_err());
+    }
+
+    #[test]
+    fn test_file_name_pattern() {
+        let pattern = FileNamePattern::new("{vup} - {song}.mp4").unwrap();
+        let clip = pattern.parse("Alice - Song A (live).mkv").unwrap();
+        assert_eq!(clip.vup, "Alice");
+        assert_eq!(clip.song, "Song A (live)");
+        assert_eq!(clip.title, "Alice - Song A (live)");
+        assert!(pattern.parse("no separator.mp4").is_none());
+
+        let pattern = FileNamePattern::new("[{vup}] {title}").unwrap();
+        let clip = pattern.parse("[Bob] 2025.07.01 birthday.mp4").unwrap();
+        assert_eq!(clip.vup, "Bob");
+        assert_eq!(clip.title, "2025.07.01 birthday");
+
+        assert!(FileNamePattern::new("{vup} - {unknown}").is_err());
+        assert!(FileNamePattern::new("{vup} - {vup}").is_err());
+    }
+
+    #[test]
+    fn test_clip_edit_resolve() {
+        let edit = ClipEdit {
+            in_ms: Some(1_000),
+            fade_out_ms: Some(500),
+            ..Default::default()
+        };
+        let (trim, effects) = edit.resolve(Some(10_000)).unwrap();
+        let trim = trim.unwrap();
+        assert_eq!((trim.in_ms, trim.out_ms), (1_000, 10_000));
+        let effects = effects.unwrap();
+        assert_eq!(
+            effects.audio_filters(Some(9_000)),
+            ["afade=t=out:st=8.500:d=0.500"]
+        );
+
+        let (trim, effects) = ClipEdit::default().resolve(Some(10_000)).unwrap();
+        assert!(trim.is_none() && effects.is_none());
+
+        let volume = ClipEdit {
+            volume_db: Some(-3.0),
+            ..Default::default()
+        };
+        let (trim, effects) = volume.resolve(None).unwrap();
+        assert!(trim.is_none());
+        assert_eq!(effects.unwrap().audio_filters(None), ["volume=-3.00dB"]);
+
+        for bad in [
+            ClipEdit {
+                out_ms: Some(11_000),
+                ..Default::default()
+            },
+            ClipEdit {
+                in_ms: Some(5_000),
+                out_ms: Some(5_000),
+                ..Default::default()
+            },
+            ClipEdit {
+                fade_in_ms: Some(6_000),
+                fade_out_ms: Some(6_000),
+                ..Default::default()
+            },
+            ClipEdit {
+                volume_db: Some(40.0),
+                ..Default::default()
+            },
+        ] {
+            assert!(bad.resolve(Some(10_000)).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn test_check_media() {
+        let config = ValidationConfig {
+            max_duration: Some(600.0),
+            ..Default::default()
+        };
+        let info = media::MediaInfo {
+            duration_ms: Some(180_000),
+            width: Some(1920),
+            height: Some(1080),
+            video_codec: Some("h264".to_string()),
+            audio_codec: Some("aac".to_string()),
+            ..Default::default()
+        };
+        assert!(check_media(&info, &config).is_ok());
+
+        let silent = media::MediaInfo {
+            audio_codec: None,
+            ..info.clone()
+        };
+        assert!(check_media(&silent, &config).is_ok());
+        let require_audio = ValidationConfig {
+            require_audio: true,
+            ..config.clone()
+        };
+        assert!(check_media(&silent, &require_audio).is_err());
+
+        let empty = media::MediaInfo {
+            duration_ms: Some(180_000),
+            ..Default::default()
+        };
+        assert!(check_media(&empty, &config).is_err());
+
+        let long = media::MediaInfo {
+            duration_ms: Some(3_600_000),
+            ..info.clone()
+        };
+        assert!(check_media(&long, &config).is_err());
+
+        let huge = media::MediaInfo {
+            width: Some(7680),
+            height: Some(4320),
+            ..info
+        };
+        assert!(check_media(&huge, &config).is_err());
+    }
+}
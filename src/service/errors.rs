@@ -11,6 +11,8 @@ pub enum Error {
     BadRequest(String),
     #[error("Forbidden: {0}")]
     Forbidden(String),
+    #[error("Conflict: {0}")]
+    Conflict(String),
     #[error("InternalServerError: {0}")]
     InternalServerError(String),
     #[error("unknown error")]
@@ -0,0 +1,271 @@
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use serde::Deserialize;
+use tokio::fs::File;
+use tracing::{debug, error, info, trace, warn};
+
+use crate::core::entity::{clip, user};
+use crate::service::clip::{FileNamePattern, SUPPORTED_EXTENSIONS};
+use crate::service::{ClipService, UserService};
+
+const PROCESSED_DIR: &str = "processed";
+const FAILED_DIR: &str = "failed";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct InboxConfig {
+    // 为空时不启用
+    pub path: Option<String>,
+    // 根目录下的文件归属的用户 (mid)，为空时忽略根目录下的文件
+    pub owner_mid: Option<i64>,
+    // 以 mid 命名的子目录作为对应用户的收件箱
+    pub per_user: bool,
+    pub interval: u64, // seconds
+    // 文件修改后至少等待这么久才导入，避免读到未写完的文件
+    pub settle: u64, // seconds
+}
+
+impl Default for InboxConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            owner_mid: None,
+            per_user: false,
+            interval: 10,
+            settle: 5,
+        }
+    }
+}
+
+/// 与视频同名的 JSON 文件，如 `a.mp4` 对应 `a.json`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Sidecar {
+    title: String,
+    vup: String,
+    song: String,
+}
+
+/// 定时扫描收件箱目录，把放入的视频导入为切片，
+/// 处理后移入 `processed`，失败的移入 `failed` 并附上错误原因。
+pub struct InboxService {
+    clip_svc: Arc<ClipService>,
+    user_svc: Arc<UserService>,
+    config: InboxConfig,
+    pattern: Option<FileNamePattern>,
+}
+
+impl InboxService {
+    pub fn new(
+        clip_svc: Arc<ClipService>,
+        user_svc: Arc<UserService>,
+        config: InboxConfig,
+        pattern: Option<FileNamePattern>,
+    ) -> Self {
+        Self {
+            clip_svc,
+            user_svc,
+            config,
+            pattern,
+        }
+    }
+
+    pub async fn run(self: Arc<Self>) {
+        let Some(root) = self.config.path.clone().map(PathBuf::from) else {
+            return;
+        };
+        info!("Watching inbox directory {}", root.display());
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval.max(1)));
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.scan(&root).await {
+                warn!("Failed to scan inbox {}: {}", root.display(), e);
+            }
+        }
+    }
+
+    async fn scan(&self, root: &Path) -> anyhow::Result<()> {
+        if let Some(mid) = self.config.owner_mid {
+            self.scan_inbox(root, mid).await?;
+        }
+        if !self.config.per_user {
+            return Ok(());
+        }
+
+        let mut entries = tokio::fs::read_dir(root).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if !entry.file_type().await?.is_dir() {
+                continue;
+            }
+            let Some(mid) = entry.file_name().to_str().and_then(|s| s.parse().ok()) else {
+                continue;
+            };
+            if let Err(e) = self.scan_inbox(&entry.path(), mid).await {
+                warn!("Failed to scan inbox of user {}: {}", mid, e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn scan_inbox(&self, dir: &Path, mid: i64) -> anyhow::Result<()> {
+        trace!("Scanning inbox {} for user {}", dir.display(), mid);
+        let mut user = None;
+        let mut entries = tokio::fs::read_dir(dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            let metadata = entry.metadata().await?;
+            if !metadata.is_file() || !self.is_candidate(&path) {
+                continue;
+            }
+            let settled = metadata
+                .modified()
+                .is_ok_and(|t| t.elapsed().is_ok_and(|e| e.as_secs() >= self.config.settle));
+            if !settled {
+                continue;
+            }
+
+            // 目录中有文件时才查询用户
+            let user = match user {
+                Some(ref u) => u,
+                None => user.insert(self.get_user(mid).await?),
+            };
+            match self.import(user, &path).await {
+                Ok(clip) => {
+                    info!("Imported {} as clip {}", path.display(), clip.uuid);
+                    self.archive(dir, &path, PROCESSED_DIR, None).await;
+                }
+                Err(e) => {
+                    error!("Failed to import {}: {}", path.display(), e);
+                    self.archive(dir, &path, FAILED_DIR, Some(&e)).await;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn is_candidate(&self, path: &Path) -> bool {
+        let Some(name) = path.file_name().and_then(OsStr::to_str) else {
+            return false;
+        };
+        // 跳过隐藏文件和附带的 JSON，其余文件无论格式都会被处理
+        !name.starts_with('.') && path.extension() != Some(OsStr::new("json"))
+    }
+
+    async fn get_user(&self, mid: i64) -> anyhow::Result<user::Model> {
+        let user = self
+            .user_svc
+            .get_user_by_mid(mid)
+            .await?
+            .with_context(|| format!("User {mid} not found"))?;
+        self.user_svc.check_user_permissions(&user).await?;
+        Ok(user)
+    }
+
+    async fn import(&self, user: &user::Model, path: &Path) -> anyhow::Result<clip::Model> {
+        let ext = path
+            .extension()
+            .and_then(OsStr::to_str)
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+        if !SUPPORTED_EXTENSIONS.contains(&ext.as_str()) {
+            anyhow::bail!("Unsupported file type: {}", ext);
+        }
+        let file_name = path.file_name().and_then(OsStr::to_str).unwrap_or_default();
+
+        let req = match tokio::fs::read(path.with_extension("json")).await {
+            Ok(json) => {
+                let sidecar: Sidecar =
+                    serde_json::from_slice(&json).context("Invalid sidecar JSON")?;
+                let title = match sidecar.title.trim() {
+                    "" => file_stem(path),
+                    title => title.to_string(),
+                };
+                clip::Model {
+                    title,
+                    vup: sidecar.vup,
+                    song: sidecar.song,
+                    ..Default::default()
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => self
+                .pattern
+                .as_ref()
+                .and_then(|p| p.parse(file_name))
+                .unwrap_or_else(|| clip::Model {
+                    title: file_stem(path),
+                    ..Default::default()
+                }),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut file = File::open(path).await?;
+        let (uuid, tmp_path, content_hash) =
+            self.clip_svc.save_clip_to_tmp(&mut file, &ext).await?;
+        let req = clip::Model {
+            uuid,
+            upload_time: chrono::Utc::now(),
+            content_hash: Some(content_hash),
+            ..req
+        };
+        self.clip_svc.create_clip(user, req, tmp_path).await
+    }
+
+    /// 把文件和附带的 JSON 移到子目录，失败时另存错误原因
+    async fn archive(&self, dir: &Path, path: &Path, sub: &str, err: Option<&anyhow::Error>) {
+        let target_dir = dir.join(sub);
+        let result = async {
+            tokio::fs::create_dir_all(&target_dir).await?;
+            let target = unique_path(&target_dir, path).await;
+            tokio::fs::rename(path, &target).await?;
+            let sidecar = path.with_extension("json");
+            if tokio::fs::try_exists(&sidecar).await.unwrap_or(false) {
+                tokio::fs::rename(&sidecar, target.with_extension("json")).await?;
+            }
+            if let Some(err) = err {
+                let mut reason = target.clone().into_os_string();
+                reason.push(".error.txt");
+                tokio::fs::write(reason, format!("{err:#}\n")).await?;
+            }
+            debug!("Moved {} to {}", path.display(), target.display());
+            anyhow::Ok(())
+        }
+        .await;
+        if let Err(e) = result {
+            // 移动失败会导致下次扫描重复导入，只能记录下来
+            error!(
+                "Failed to move {} to {}: {}",
+                path.display(),
+                target_dir.display(),
+                e
+            );
+        }
+    }
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .and_then(OsStr::to_str)
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+/// 目标已存在时在文件名后加上时间戳
+async fn unique_path(dir: &Path, path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default();
+    let target = dir.join(name);
+    if !tokio::fs::try_exists(&target).await.unwrap_or(false) {
+        return target;
+    }
+    let stamp = chrono::Utc::now().format("%Y%m%d%H%M%S");
+    let ext = path
+        .extension()
+        .and_then(OsStr::to_str)
+        .map(|e| format!(".{e}"))
+        .unwrap_or_default();
+    dir.join(format!("{}-{stamp}{ext}", file_stem(path)))
+}
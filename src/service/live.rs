@@ -3,6 +3,7 @@ use std::sync::atomic::{AtomicBool, Ordering};
 
 use anyhow::anyhow;
 use bilive::wbi::WBI;
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use tokio::sync::Mutex;
 use tokio::task::JoinHandle;
@@ -12,6 +13,13 @@ use crate::core::storage::Storage;
 use crate::core::streamer::{RtmpStreamer, RtmpStreamerConfig};
 use crate::service::{ClipService, PlaylistService, UserService};
 
+#[derive(Debug, Clone)]
+pub struct NowPlaying {
+    pub playlist_id: i64,
+    pub position: i64,
+    pub started_at: DateTime<Utc>,
+}
+
 pub struct LiveService {
     user_svc: Arc<UserService>,
     #[allow(dead_code)]
@@ -19,6 +27,7 @@ pub struct LiveService {
     playlist_svc: Arc<PlaylistService>,
     storage: Arc<Storage>,
     tasks: DashMap<String, (JoinHandle<()>, Arc<RtmpStreamer>, Arc<AtomicBool>)>,
+    now_playing: Arc<DashMap<i64, NowPlaying>>,
     config: RtmpStreamerConfig,
     wbi: Arc<Mutex<WBI>>,
 }
@@ -39,6 +48,7 @@ impl LiveService {
             playlist_svc,
             storage,
             tasks,
+            now_playing: Arc::new(DashMap::new()),
             config,
             wbi,
         }
@@ -81,17 +91,20 @@ impl LiveService {
         let rtmp_url = format!("{}{}", live_info.rtmp.addr, live_info.rtmp.code);
         let config = self.config.clone();
 
-        let streamer = Arc::new(RtmpStreamer::new(config, &rtmp_url)?);
+        let streamer = Arc::new(RtmpStreamer::new(config.clone(), &rtmp_url)?);
         streamer.start().await?;
         let storage = self.storage.clone();
         let playlist_svc = self.playlist_svc.clone();
 
         let streamer_clone = streamer.clone();
+        let now_playing = self.now_playing.clone();
         let user_id = user.id;
         let stopped = Arc::new(AtomicBool::new(false));
         let stopped_clone = stopped.clone();
         let task = tokio::spawn(async move {
             loop {
+                // 一轮下来没有播放任何切片时稍后再试，避免空转查询
+                let mut played = false;
                 let playlists = playlist_svc
                     .get_user_active_playlist(user_id)
                     .await
@@ -151,6 +164,13 @@ impl LiveService {
                             let clip_uuid = clip.uuid.clone();
                             let clip_title = clip.title.clone();
                             let _clip_vup = clip.vup.clone();
+                            let compatible = config.accepts_video(
+                                clip.video_codec.as_deref(),
+                                clip.width,
+                                clip.height,
+                                clip.frame_rate,
+                                clip.stream_profile.as_deref(),
+                            );
                             match storage.get_file(&format!("{}.mp4", clip_uuid)).await {
                                 Ok(file) => {
                                     streamer_clone
@@ -160,8 +180,16 @@ impl LiveService {
                                             tracing::error!("Failed to update title: {}", e);
                                         })
                                         .ok();
+                                    now_playing.insert(
+                                        user_id,
+                                        NowPlaying {
+                                            playlist_id: playlist.id,
+                                            position: index,
+                                            started_at: Utc::now(),
+                                        },
+                                    );
                                     streamer_clone
-                                        .push(file)
+                                        .push(file, compatible)
                                         .await
                                         .map_err(|e| {
                                             tracing::error!(
@@ -170,15 +198,27 @@ impl LiveService {
                                             );
                                         })
                                         .ok();
+                                    now_playing.remove(&user_id);
+                                    played = true;
                                 }
                                 Err(e) => {
                                     tracing::warn!("Failed to get file from storage: {}", e);
                                     tokio::time::sleep(std::time::Duration::from_secs(3)).await;
                                 }
                             }
+                        } else {
+                            tracing::debug!(
+                                "Skipping position {} of playlist {}: no playable clip",
+                                index,
+                                playlist.id
+                            );
                         }
                     }
                 }
+                if !played {
+                    tracing::warn!("Nothing to play for user {}, retrying later", user_id);
+                    tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                }
             }
         });
         self.tasks
@@ -195,6 +235,7 @@ impl LiveService {
                 .ok();
             stopped.store(true, Ordering::SeqCst);
         }
+        self.now_playing.remove(&user.id);
         let session = self.user_svc.get_session_and_refresh(user).await?;
         let live = bilive::live::Live::new(session, self.wbi.clone());
         let room_info = self
@@ -207,6 +248,13 @@ impl LiveService {
         Ok(())
     }
 
+    pub fn now_playing(&self, playlist_id: i64) -> Option<NowPlaying> {
+        self.now_playing
+            .iter()
+            .find(|n| n.playlist_id == playlist_id)
+            .map(|n| n.clone())
+    }
+
     pub async fn get_room_info(
         &self,
         user: &user::Model,
@@ -1,9 +1,16 @@
+pub(crate) mod catalog;
 pub(crate) mod clip;
-mod errors;
+pub(crate) mod errors;
+pub use catalog::CatalogService;
 pub use clip::ClipService;
 pub(crate) mod playlist;
+pub(crate) mod quota;
+pub(crate) mod tag;
 pub use playlist::PlaylistService;
+pub use tag::TagService;
 pub(crate) mod user;
 pub use user::UserService;
+pub(crate) mod inbox;
+pub use inbox::InboxService;
 mod live;
-pub use live::LiveService;
+pub use live::{LiveService, NowPlaying};
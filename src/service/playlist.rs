@@ -1,11 +1,46 @@
+use chrono::{DateTime, TimeDelta, Utc};
 use sea_orm::prelude::*;
 use sea_orm::{ActiveValue, IntoActiveModel, Set};
 use uuid::Uuid;
 
-use crate::core::entity::{clip, playlist, playlist_item};
+use crate::core::entity::{clip, playlist, playlist_item, playlist_share, user};
 use crate::data::PlaylistData;
+use crate::service::NowPlaying;
 use crate::service::errors::Error;
 
+pub struct ScheduledItem {
+    pub item: playlist_item::Model,
+    pub clip: clip::Model,
+    /// 相对播放列表开头的偏移，之前有切片时长未知时为空
+    pub start_offset_ms: Option<i64>,
+    /// 预计开始播放的时间，只在直播时为之后的项计算
+    pub eta: Option<DateTime<Utc>>,
+    /// 直播时是否会跳过，见 [`is_playable`]
+    pub skipped: bool,
+}
+
+pub struct PlaylistSchedule {
+    pub items: Vec<ScheduledItem>,
+    pub total_duration_ms: Option<i64>,
+    pub now_playing: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Access {
+    Viewer,
+    Editor,
+    Owner,
+}
+
+impl From<playlist_share::Role> for Access {
+    fn from(role: playlist_share::Role) -> Self {
+        match role {
+            playlist_share::Role::Viewer => Access::Viewer,
+            playlist_share::Role::Editor => Access::Editor,
+        }
+    }
+}
+
 pub struct PlaylistService {
     playlist_data: PlaylistData,
 }
@@ -28,21 +63,100 @@ impl PlaylistService {
         self.playlist_data.get_user_playlists(user_id).await
     }
 
+    pub async fn get_shared_playlists(
+        &self,
+        user_id: i64,
+    ) -> anyhow::Result<Vec<(playlist::Model, playlist_share::Role)>> {
+        let shares = self.playlist_data.get_shared_playlists(user_id).await?;
+        Ok(shares
+            .into_iter()
+            .filter_map(|(share, playlist)| playlist.map(|p| (p, share.role)))
+            .collect())
+    }
+
     pub async fn get_playlist(&self, user_id: i64, id: i64) -> anyhow::Result<playlist::Model> {
+        self.get_playlist_with_access(user_id, id, Access::Owner)
+            .await
+    }
+
+    pub async fn get_playlist_with_access(
+        &self,
+        user_id: i64,
+        id: i64,
+        required: Access,
+    ) -> anyhow::Result<playlist::Model> {
         let playlist = self
             .playlist_data
-            .get_playlist(user_id, id)
+            .get_playlist_by_id(id)
             .await?
             .ok_or(Error::NotFound("Playlist not found".to_string()))?;
+
+        let access = if playlist.user_id == user_id {
+            Access::Owner
+        } else {
+            self.playlist_data
+                .get_share(id, user_id)
+                .await?
+                .map(|share| Access::from(share.role))
+                .ok_or(Error::NotFound("Playlist not found".to_string()))?
+        };
+        if access < required {
+            return Err(Error::Forbidden("Insufficient playlist permissions".to_string()).into());
+        }
         Ok(playlist)
     }
 
+    pub async fn list_shares(
+        &self,
+        user_id: i64,
+        playlist_id: i64,
+    ) -> anyhow::Result<Vec<(playlist_share::Model, user::Model)>> {
+        self.get_playlist(user_id, playlist_id).await?;
+        let shares = self.playlist_data.list_shares(playlist_id).await?;
+        Ok(shares
+            .into_iter()
+            .filter_map(|(share, user)| user.map(|u| (share, u)))
+            .collect())
+    }
+
+    pub async fn share_playlist(
+        &self,
+        user_id: i64,
+        playlist_id: i64,
+        target_user_id: i64,
+        role: playlist_share::Role,
+    ) -> anyhow::Result<playlist_share::Model> {
+        let playlist = self.get_playlist(user_id, playlist_id).await?;
+        if playlist.user_id == target_user_id {
+            return Err(
+                Error::BadRequest("Cannot share a playlist with its owner".to_string()).into(),
+            );
+        }
+        self.playlist_data
+            .save_share(playlist_id, target_user_id, role)
+            .await
+    }
+
+    pub async fn unshare_playlist(
+        &self,
+        user_id: i64,
+        playlist_id: i64,
+        target_user_id: i64,
+    ) -> anyhow::Result<()> {
+        self.get_playlist(user_id, playlist_id).await?;
+        self.playlist_data
+            .delete_share(playlist_id, target_user_id)
+            .await
+    }
+
     pub async fn update_playlist(
         &self,
         user_id: i64,
         playlist: playlist::Model,
     ) -> anyhow::Result<playlist::Model> {
-        let existing_playlist = self.get_playlist(user_id, playlist.id).await?;
+        let existing_playlist = self
+            .get_playlist_with_access(user_id, playlist.id, Access::Editor)
+            .await?;
         let mut playlist_model = existing_playlist.into_active_model();
         let now: DateTimeWithTimeZone = chrono::Utc::now().into();
         playlist_model.name = Set(playlist.name);
@@ -91,7 +205,8 @@ impl PlaylistService {
         playlist_id: i64,
         clip_uuid: Uuid,
     ) -> anyhow::Result<playlist_item::Model> {
-        self.get_playlist(user_id, playlist_id).await?;
+        self.get_playlist_with_access(user_id, playlist_id, Access::Viewer)
+            .await?;
         let item = self
             .playlist_data
             .get_playlist_item_by_clip_uuid(playlist_id, clip_uuid)
@@ -105,7 +220,8 @@ impl PlaylistService {
         user_id: i64,
         playlist_id: i64,
     ) -> anyhow::Result<Vec<(playlist_item::Model, clip::Model)>> {
-        self.get_playlist(user_id, playlist_id).await?;
+        self.get_playlist_with_access(user_id, playlist_id, Access::Viewer)
+            .await?;
         let items = self
             .playlist_data
             .get_playlist_items_with_clips(playlist_id)
@@ -120,6 +236,21 @@ impl PlaylistService {
         Ok(resp)
     }
 
+    pub async fn get_playlist_schedule(
+        &self,
+        user_id: i64,
+        playlist_id: i64,
+        now_playing: Option<NowPlaying>,
+    ) -> anyhow::Result<PlaylistSchedule> {
+        let playlist = self
+            .get_playlist_with_access(user_id, playlist_id, Access::Viewer)
+            .await?;
+        let items = self
+            .get_playlist_item_by_playlist_id(user_id, playlist_id)
+            .await?;
+        Ok(build_schedule(items, playlist.user_id, now_playing))
+    }
+
     pub async fn get_playlist_item_count(&self, playlist_id: i64) -> anyhow::Result<i64> {
         self.playlist_data
             .get_playlist_item_count(playlist_id)
@@ -136,9 +267,12 @@ impl PlaylistService {
         if !playlist.is_active {
             return Ok(None);
         }
-        self.playlist_data
+        let clip = self
+            .playlist_data
             .get_clip_by_position(playlist_id, position)
-            .await
+            .await?
+            .filter(|c| is_playable(c, playlist.user_id));
+        Ok(clip)
     }
 
     pub async fn add_to_playlist(
@@ -147,7 +281,20 @@ impl PlaylistService {
         playlist_id: i64,
         clip_uuid: Uuid,
     ) -> anyhow::Result<()> {
-        self.get_playlist(user_id, playlist_id).await?;
+        let playlist = self
+            .get_playlist_with_access(user_id, playlist_id, Access::Editor)
+            .await?;
+
+        // 列表所有者的切片或已审核的切片才能加入，协作者也不能加入自己未审核的切片
+        let clip = self
+            .playlist_data
+            .get_clip(clip_uuid)
+            .await?
+            .filter(|c| c.deleted_at.is_none())
+            .ok_or(Error::NotFound("Clip not found".to_string()))?;
+        if clip.user_id != playlist.user_id && clip.status != clip::Status::Reviewed {
+            return Err(Error::Forbidden("Clip is not reviewed".to_string()).into());
+        }
 
         let existing = self
             .playlist_data
@@ -177,7 +324,8 @@ impl PlaylistService {
         playlist_id: i64,
         clip_uuid: Uuid,
     ) -> anyhow::Result<()> {
-        self.get_playlist(user_id, playlist_id).await?;
+        self.get_playlist_with_access(user_id, playlist_id, Access::Editor)
+            .await?;
         self.playlist_data
             .remove_playlist_item_and_reorder(playlist_id, clip_uuid)
             .await
@@ -190,9 +338,151 @@ impl PlaylistService {
         item_id: i64,
         new_position: i64,
     ) -> anyhow::Result<()> {
-        self.get_playlist(user_id, playlist_id).await?;
+        self.get_playlist_with_access(user_id, playlist_id, Access::Editor)
+            .await?;
         self.playlist_data
             .reorder_playlist_item(playlist_id, item_id, new_position)
             .await
     }
 }
+
+/// 直播时能否播放：需要已有处理完成的文件，其他用户的切片替换文件或被退回后
+/// 不再是已审核状态，审核通过前跳过
+fn is_playable(clip: &clip::Model, owner_id: i64) -> bool {
+    // 处理完成时才会记录时长，重新处理期间仍保留之前的文件
+    let stored = clip.duration_ms.is_some()
+        || !matches!(
+            clip.status,
+            clip::Status::Pending | clip::Status::Processing | clip::Status::Failed
+        );
+    stored && (clip.user_id == owner_id || clip.status == clip::Status::Reviewed)
+}
+
+fn build_schedule(
+    items: Vec<(playlist_item::Model, clip::Model)>,
+    owner_id: i64,
+    now_playing: Option<NowPlaying>,
+) -> PlaylistSchedule {
+    let mut offset = Some(0i64);
+    let mut scheduled = Vec::with_capacity(items.len());
+    for (item, clip) in items {
+        // 跳过的切片不占用播放时间
+        let skipped = !is_playable(&clip, owner_id);
+        let start_offset_ms = offset.filter(|_| !skipped);
+        if !skipped {
+            offset = offset.zip(clip.duration_ms).map(|(o, d)| o + d);
+        }
+        scheduled.push(ScheduledItem {
+            item,
+            clip,
+            start_offset_ms,
+            eta: None,
+            skipped,
+        });
+    }
+
+    if let Some(ref playing) = now_playing {
+        let current_offset = scheduled
+            .iter()
+            .find(|s| s.item.position == playing.position)
+            .and_then(|s| s.start_offset_ms);
+        if let Some(current_offset) = current_offset {
+            for s in scheduled
+                .iter_mut()
+                .filter(|s| s.item.position > playing.position)
+            {
+                s.eta = s
+                    .start_offset_ms
+                    .map(|o| playing.started_at + TimeDelta::milliseconds(o - current_offset));
+            }
+        }
+    }
+
+    PlaylistSchedule {
+        items: scheduled,
+        total_duration_ms: offset,
+        now_playing: now_playing.map(|n| n.position),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(position: i64, duration_ms: Option<i64>) -> (playlist_item::Model, clip::Model) {
+        let item = playlist_item::Model {
+            id: position,
+            playlist_id: 1,
+            clip_uuid: Uuid::new_v4(),
+            position,
+            created_at: Utc::now().into(),
+        };
+        let clip = clip::Model {
+            uuid: item.clip_uuid,
+            duration_ms,
+            status: clip::Status::Reviewed,
+            ..Default::default()
+        };
+        (item, clip)
+    }
+
+    #[test]
+    fn test_build_schedule() {
+        let started_at = Utc::now();
+        let schedule = build_schedule(
+            vec![
+                item(0, Some(1000)),
+                item(1, Some(2000)),
+                item(2, Some(3000)),
+            ],
+            0,
+            Some(NowPlaying {
+                playlist_id: 1,
+                position: 1,
+                started_at,
+            }),
+        );
+        let offsets: Vec<_> = schedule.items.iter().map(|s| s.start_offset_ms).collect();
+        assert_eq!(offsets, vec![Some(0), Some(1000), Some(3000)]);
+        assert_eq!(schedule.total_duration_ms, Some(6000));
+        assert_eq!(schedule.now_playing, Some(1));
+        assert_eq!(schedule.items[0].eta, None);
+        assert_eq!(schedule.items[1].eta, None);
+        assert_eq!(
+            schedule.items[2].eta,
+            Some(started_at + TimeDelta::milliseconds(2000))
+        );
+    }
+
+    #[test]
+    fn test_build_schedule_unknown_duration() {
+        let schedule = build_schedule(vec![item(0, None), item(1, Some(2000))], 0, None);
+        let offsets: Vec<_> = schedule.items.iter().map(|s| s.start_offset_ms).collect();
+        assert_eq!(offsets, vec![Some(0), None]);
+        assert_eq!(schedule.total_duration_ms, None);
+    }
+
+    #[test]
+    fn test_build_schedule_skips_unplayable() {
+        let mut unprocessed = item(1, None);
+        unprocessed.1.status = clip::Status::Pending;
+        let mut rejected = item(2, Some(2000));
+        rejected.1.user_id = 2;
+        rejected.1.status = clip::Status::Rejected;
+        let schedule = build_schedule(
+            vec![
+                item(0, Some(1000)),
+                unprocessed,
+                rejected,
+                item(3, Some(3000)),
+            ],
+            1,
+            None,
+        );
+        let offsets: Vec<_> = schedule.items.iter().map(|s| s.start_offset_ms).collect();
+        assert_eq!(offsets, vec![Some(0), None, None, Some(1000)]);
+        let skipped: Vec<_> = schedule.items.iter().map(|s| s.skipped).collect();
+        assert_eq!(skipped, vec![false, true, true, false]);
+        assert_eq!(schedule.total_duration_ms, Some(4000));
+    }
+}
@@ -0,0 +1,136 @@
+use bytesize::ByteSize;
+use serde::{Deserialize, Serialize};
+
+use crate::core::entity::user;
+
+/// 一组配额上限，为空表示不限
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Quota {
+    pub max_bytes: Option<ByteSize>,
+    pub max_clips: Option<u64>,
+    pub max_daily_uploads: Option<u64>, // 最近 24 小时
+}
+
+/// 按角色配置的配额，管理员和主播未配置时使用默认配额
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct QuotaConfig {
+    pub default: Quota,
+    pub streamer: Option<Quota>,
+    pub admin: Option<Quota>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    pub bytes: u64,
+    pub clips: u64,
+    pub daily_uploads: u64,
+}
+
+impl QuotaConfig {
+    /// 角色配额叠加用户单独设置的配额
+    pub fn resolve(&self, user: &user::Model) -> Quota {
+        let role = match (user.is_admin, user.can_stream) {
+            (true, _) if self.admin.is_some() => self.admin.as_ref(),
+            (_, true) if self.streamer.is_some() => self.streamer.as_ref(),
+            _ => None,
+        };
+        let base = role.unwrap_or(&self.default);
+        let apply = |value: Option<i64>, base: Option<u64>| match value {
+            Some(v) if v < 0 => None,
+            Some(v) => Some(v as u64),
+            None => base,
+        };
+        Quota {
+            max_bytes: apply(user.quota_bytes, base.max_bytes.map(|b| b.as_u64())).map(ByteSize),
+            max_clips: apply(user.quota_clips, base.max_clips),
+            max_daily_uploads: apply(user.quota_daily_uploads, base.max_daily_uploads),
+        }
+    }
+}
+
+impl Quota {
+    /// 检查再上传 `incoming` 字节的一个切片是否超出配额
+    pub fn check(&self, usage: &QuotaUsage, incoming: u64) -> Result<(), String> {
+        if let Some(max) = self.max_clips.filter(|max| usage.clips >= *max) {
+            return Err(format!(
+                "Clip quota exceeded: {} of {} clips",
+                usage.clips, max
+            ));
+        }
+        if let Some(max) = self
+            .max_daily_uploads
+            .filter(|max| usage.daily_uploads >= *max)
+        {
+            return Err(format!(
+                "Daily upload quota exceeded: {} of {} uploads in the last 24 hours",
+                usage.daily_uploads, max
+            ));
+        }
+        if let Some(max) = self
+            .max_bytes
+            .filter(|max| usage.bytes + incoming > max.as_u64())
+        {
+            return Err(format!(
+                "Storage quota exceeded: {} used, {} incoming, {} allowed",
+                ByteSize(usage.bytes),
+                ByteSize(incoming),
+                max
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_and_check() {
+        let config = QuotaConfig {
+            default: Quota {
+                max_bytes: Some(ByteSize::gib(1)),
+                max_clips: Some(10),
+                max_daily_uploads: None,
+            },
+            streamer: Some(Quota {
+                max_clips: Some(100),
+                ..Default::default()
+            }),
+            admin: None,
+        };
+        let user = user::Model {
+            can_stream: false,
+            ..Default::default()
+        };
+        let quota = config.resolve(&user);
+        assert_eq!(quota.max_clips, Some(10));
+
+        let streamer = user::Model {
+            can_stream: true,
+            quota_daily_uploads: Some(5),
+            ..user.clone()
+        };
+        let quota = config.resolve(&streamer);
+        assert_eq!(quota.max_clips, Some(100));
+        assert_eq!(quota.max_bytes, None);
+        assert_eq!(quota.max_daily_uploads, Some(5));
+
+        let unlimited = user::Model {
+            quota_clips: Some(-1),
+            ..user
+        };
+        let quota = config.resolve(&unlimited);
+        assert_eq!(quota.max_clips, None);
+
+        let usage = QuotaUsage {
+            bytes: ByteSize::mib(900).as_u64(),
+            clips: 3,
+            daily_uploads: 0,
+        };
+        assert!(quota.check(&usage, ByteSize::mib(100).as_u64()).is_ok());
+        assert!(quota.check(&usage, ByteSize::mib(200).as_u64()).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use sea_orm::{IntoActiveModel, Set};
+use tracing::{debug, trace};
+use uuid::Uuid;
+
+use crate::core::entity::{clip, tag, user};
+use crate::data::{ClipData, TagData};
+use crate::service::errors::Error;
+
+const MAX_TAG_NAME_LEN: usize = 64;
+const MAX_CATEGORY_LEN: usize = 32;
+
+pub struct TagService {
+    tag_data: TagData,
+    clip_data: ClipData,
+}
+
+impl TagService {
+    pub fn new(tag_data: TagData, clip_data: ClipData) -> Self {
+        Self {
+            tag_data,
+            clip_data,
+        }
+    }
+
+    pub async fn list_tags(&self) -> anyhow::Result<Vec<tag::Model>> {
+        self.tag_data.list_tags().await
+    }
+
+    pub async fn create_tag(
+        &self,
+        name: &str,
+        category: Option<&str>,
+    ) -> anyhow::Result<tag::Model> {
+        let (name, category) = normalize(name, category)?;
+        trace!("Creating tag {} ({:?})", name, category);
+        if self.tag_data.find_tag_by_name(&name).await?.is_some() {
+            return Err(Error::Conflict(format!("Tag {name} already exists")).into());
+        }
+        let tag = tag::ActiveModel {
+            name: Set(name),
+            category: Set(category),
+            created_at: Set(chrono::Utc::now().into()),
+            ..Default::default()
+        };
+        self.tag_data.create_tag(tag).await
+    }
+
+    pub async fn update_tag(
+        &self,
+        id: i64,
+        name: &str,
+        category: Option<&str>,
+    ) -> anyhow::Result<tag::Model> {
+        let (name, category) = normalize(name, category)?;
+        let tag = self
+            .tag_data
+            .get_tag(id)
+            .await?
+            .ok_or(Error::NotFound("Tag not found".to_string()))?;
+        let existing = self.tag_data.find_tag_by_name(&name).await?;
+        if existing.is_some_and(|other| other.id != id) {
+            return Err(Error::Conflict(format!("Tag {name} already exists")).into());
+        }
+        let mut active = tag.into_active_model();
+        active.name = Set(name);
+        active.category = Set(category);
+        self.tag_data.update_tag(active).await
+    }
+
+    pub async fn delete_tag(&self, id: i64) -> anyhow::Result<()> {
+        trace!("Deleting tag {}", id);
+        self.tag_data
+            .get_tag(id)
+            .await?
+            .ok_or(Error::NotFound("Tag not found".to_string()))?;
+        self.tag_data.delete_tag(id).await
+    }
+
+    /// 替换切片的全部标签，只有上传者和管理员可以修改
+    pub async fn set_clip_tags(
+        &self,
+        user: &user::Model,
+        uuid: Uuid,
+        tag_ids: &[i64],
+    ) -> anyhow::Result<(clip::Model, Vec<tag::Model>)> {
+        let clip = match self.clip_data.get_clip(uuid).await? {
+            Some(c) if c.user_id == user.id || user.is_admin => c,
+            _ => return Err(Error::NotFound("Clip not found".to_string()).into()),
+        };
+        let mut tag_ids = tag_ids.to_vec();
+        tag_ids.sort_unstable();
+        tag_ids.dedup();
+        let tags = self.tag_data.find_tags(&tag_ids).await?;
+        if tags.len() != tag_ids.len() {
+            return Err(Error::BadRequest("Unknown tag".to_string()).into());
+        }
+        self.tag_data.set_clip_tags(clip.id, &tag_ids).await?;
+        debug!("Set tags of clip {} to {:?}", uuid, tag_ids);
+        Ok((clip, tags))
+    }
+
+    /// 按切片 id 分组的标签
+    pub async fn tags_of_clips(
+        &self,
+        clips: &[clip::Model],
+    ) -> anyhow::Result<HashMap<i64, Vec<tag::Model>>> {
+        let ids: Vec<i64> = clips.iter().map(|c| c.id).collect();
+        let mut map: HashMap<i64, Vec<tag::Model>> = HashMap::new();
+        for (clip_id, tag) in self.tag_data.list_clip_tags(&ids).await? {
+            map.entry(clip_id).or_default().push(tag);
+        }
+        Ok(map)
+    }
+}
+
+fn normalize(name: &str, category: Option<&str>) -> anyhow::Result<(String, Option<String>)> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_TAG_NAME_LEN {
+        return Err(Error::BadRequest(format!(
+            "Tag name must be 1 to {MAX_TAG_NAME_LEN} characters"
+        ))
+        .into());
+    }
+    let category = category.map(str::trim).filter(|c| !c.is_empty());
+    if category.is_some_and(|c| c.chars().count() > MAX_CATEGORY_LEN) {
+        return Err(Error::BadRequest(format!(
+            "Tag category must be at most {MAX_CATEGORY_LEN} characters"
+        ))
+        .into());
+    }
+    Ok((name.to_string(), category.map(str::to_string)))
+}
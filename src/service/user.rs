@@ -136,9 +136,22 @@ impl UserService {
         is_admin: Option<bool>,
         can_stream: Option<bool>,
         is_disabled: Option<bool>,
+        is_trusted: Option<bool>,
     ) -> anyhow::Result<user::Model> {
         self.user_data
-            .update_user_permissions(user_id, is_admin, can_stream, is_disabled)
+            .update_user_permissions(user_id, is_admin, can_stream, is_disabled, is_trusted)
+            .await
+    }
+
+    pub async fn update_user_quota(
+        &self,
+        user_id: i64,
+        quota_bytes: Option<i64>,
+        quota_clips: Option<i64>,
+        quota_daily_uploads: Option<i64>,
+    ) -> anyhow::Result<user::Model> {
+        self.user_data
+            .update_user_quota(user_id, quota_bytes, quota_clips, quota_daily_uploads)
             .await
     }
 
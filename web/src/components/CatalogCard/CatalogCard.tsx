@@ -0,0 +1,201 @@
+import React, { useState, useEffect } from 'react';
+import { message, Modal, Select } from 'antd';
+import axios from 'axios';
+import { useApi } from '../../context/AppContext';
+import type { CatalogEntry, CatalogKind } from '../../types';
+import ContentCard from '../ContentCard/ContentCard';
+import './CatalogCard.css';
+
+interface CatalogCardProps {
+  kind: CatalogKind;
+  title: string;
+}
+
+interface EntryForm {
+  name: string;
+  image_url: string;
+  link: string;
+  aliases: string;
+}
+
+// vup 或歌曲目录的管理卡片，可编辑条目和合并重复条目
+const CatalogCard: React.FC<CatalogCardProps> = ({ kind, title }) => {
+  const [entries, setEntries] = useState<CatalogEntry[]>([]);
+  const [loading, setLoading] = useState(true);
+  const [editing, setEditing] = useState<CatalogEntry | null>(null);
+  const [form, setForm] = useState<EntryForm>({ name: '', image_url: '', link: '', aliases: '' });
+  const [merging, setMerging] = useState<CatalogEntry | null>(null);
+  const [mergeInto, setMergeInto] = useState<number | null>(null);
+  const api = useApi();
+  const imageLabel = kind === 'vup' ? '头像链接' : '封面链接';
+
+  useEffect(() => {
+    loadEntries();
+  }, [kind]);
+
+  const loadEntries = async () => {
+    try {
+      setLoading(true);
+      setEntries(await api.listCatalog(kind));
+    } catch (error) {
+      console.error(`获取${title}失败:`, error);
+      message.error(`获取${title}失败`);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  const errorMessage = (error: unknown, fallback: string) =>
+    axios.isAxiosError(error) && typeof error.response?.data === 'string' && error.response.data
+      ? error.response.data
+      : fallback;
+
+  const openEdit = (entry: CatalogEntry) => {
+    setEditing(entry);
+    setForm({
+      name: entry.name,
+      image_url: entry.image_url ?? '',
+      link: entry.link ?? '',
+      aliases: entry.aliases.filter(alias => alias !== entry.name).join('\n'),
+    });
+  };
+
+  const handleSave = async () => {
+    if (!editing) return;
+    try {
+      const updated = await api.updateCatalogEntry(kind, editing.id, {
+        name: form.name,
+        image_url: form.image_url || null,
+        link: form.link || null,
+        aliases: form.aliases.split('\n').map(alias => alias.trim()).filter(Boolean),
+      });
+      setEntries(prev => prev.map(e => (e.id === updated.id ? { ...updated, clip_count: e.clip_count } : e)));
+      setEditing(null);
+      message.success('已保存');
+    } catch (error) {
+      console.error('保存失败:', error);
+      message.error(errorMessage(error, '保存失败'));
+    }
+  };
+
+  const handleMerge = async () => {
+    if (!merging || mergeInto == null) return;
+    try {
+      const merged = await api.mergeCatalogEntry(kind, merging.id, mergeInto);
+      setEntries(prev => prev.filter(e => e.id !== merging.id).map(e => (e.id === merged.id ? merged : e)));
+      setMerging(null);
+      setMergeInto(null);
+      message.success('已合并');
+    } catch (error) {
+      console.error('合并失败:', error);
+      message.error(errorMessage(error, '合并失败'));
+    }
+  };
+
+  return (
+    <ContentCard title={title} loading={loading}>
+      <div className="catalog-list">
+        {entries.length === 0 && <p>暂无条目</p>}
+        {entries.map(entry => (
+          <div key={entry.id} className="catalog-item">
+            <div className="catalog-info">
+              {entry.image_url && <img className="catalog-image" src={entry.image_url} alt="" />}
+              <div>
+                <div>
+                  {entry.link ? (
+                    <a href={entry.link} target="_blank" rel="noreferrer">{entry.name}</a>
+                  ) : (
+                    entry.name
+                  )}
+                  <span className="catalog-count">{entry.clip_count} 个切片</span>
+                </div>
+                {entry.aliases.length > 1 && (
+                  <div className="catalog-aliases">
+                    别名：{entry.aliases.filter(alias => alias !== entry.name).join('、')}
+                  </div>
+                )}
+              </div>
+            </div>
+            <span>
+              <button className="action-btn" onClick={() => openEdit(entry)}>编辑</button>
+              <button className="action-btn" onClick={() => setMerging(entry)}>合并到...</button>
+            </span>
+          </div>
+        ))}
+      </div>
+
+      <Modal
+        title={`编辑：${editing?.name ?? ''}`}
+        open={editing !== null}
+        onOk={handleSave}
+        onCancel={() => setEditing(null)}
+        okText="保存"
+        cancelText="取消"
+      >
+        <div className="catalog-field">
+          <label>正式名称</label>
+          <input
+            className="form-input"
+            value={form.name}
+            onChange={e => setForm({ ...form, name: e.target.value })}
+          />
+        </div>
+        <div className="catalog-field">
+          <label>{imageLabel}</label>
+          <input
+            className="form-input"
+            value={form.image_url}
+            onChange={e => setForm({ ...form, image_url: e.target.value })}
+            placeholder="https://"
+          />
+        </div>
+        <div className="catalog-field">
+          <label>外部链接</label>
+          <input
+            className="form-input"
+            value={form.link}
+            onChange={e => setForm({ ...form, link: e.target.value })}
+            placeholder="https://"
+          />
+        </div>
+        <div className="catalog-field">
+          <label>别名（每行一个）</label>
+          <textarea
+            className="form-input"
+            rows={4}
+            value={form.aliases}
+            onChange={e => setForm({ ...form, aliases: e.target.value })}
+          />
+        </div>
+      </Modal>
+
+      <Modal
+        title={`合并：${merging?.name ?? ''}`}
+        open={merging !== null}
+        onOk={handleMerge}
+        onCancel={() => {
+          setMerging(null);
+          setMergeInto(null);
+        }}
+        okText="合并"
+        cancelText="取消"
+        okButtonProps={{ disabled: mergeInto == null }}
+      >
+        <p>合并后该条目的别名和切片都会转到目标条目，该条目将被删除。</p>
+        <Select
+          className="catalog-merge-select"
+          showSearch
+          placeholder="选择目标条目"
+          value={mergeInto ?? undefined}
+          onChange={value => setMergeInto(value)}
+          optionFilterProp="label"
+          options={entries
+            .filter(e => e.id !== merging?.id)
+            .map(e => ({ value: e.id, label: e.name }))}
+        />
+      </Modal>
+    </ContentCard>
+  );
+};
+
+export default CatalogCard;
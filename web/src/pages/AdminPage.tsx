@@ -1,9 +1,11 @@
 import React, { useState, useEffect } from 'react';
-import { message } from 'antd';
+import { message, Modal, InputNumber, Popconfirm } from 'antd';
+import { useNavigate } from 'react-router-dom';
 import { useAuth, useApi } from '../context/AppContext';
-import type { User, UpdateUserPermissionsRequest } from '../types';
+import type { User, Tag, Clip, UpdateUserPermissionsRequest, UpdateUserQuotaRequest } from '../types';
 import PageContainer from '../components/PageContainer/PageContainer';
 import ContentCard from '../components/ContentCard/ContentCard';
+import CatalogCard from '../components/CatalogCard/CatalogCard';
 import './AdminPage.css';
 
 const AdminPage: React.FC = () => {
@@ -11,13 +13,105 @@ const AdminPage: React.FC = () => {
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
   const [updating, setUpdating] = useState<Set<number>>(new Set());
+  const [quotaUser, setQuotaUser] = useState<User | null>(null);
+  const [quotaForm, setQuotaForm] = useState<UpdateUserQuotaRequest>({
+    quota_bytes: null,
+    quota_clips: null,
+    quota_daily_uploads: null,
+  });
+  const [tags, setTags] = useState<Tag[]>([]);
+  const [queue, setQueue] = useState<Clip[]>([]);
+  const [queueTotal, setQueueTotal] = useState(0);
   const { currentUser } = useAuth();
   const api = useApi();
+  const navigate = useNavigate();
 
   useEffect(() => {
     loadUsers();
+    loadTags();
+    loadQueue();
   }, []);
 
+  const loadQueue = async () => {
+    try {
+      const data = await api.getReviewQueue();
+      setQueue(data.items);
+      setQueueTotal(data.total);
+    } catch (error) {
+      console.error('获取审核队列失败:', error);
+      message.error('获取审核队列失败');
+    }
+  };
+
+  const handleApprove = async (clip: Clip) => {
+    try {
+      await api.reviewClip(clip.uuid, 'approved');
+      message.success('已通过审核');
+      loadQueue();
+    } catch (error) {
+      console.error('审核失败:', error);
+      message.error('审核失败');
+    }
+  };
+
+  const handleReject = async (clip: Clip) => {
+    const reason = window.prompt('退回原因');
+    if (reason == null) return;
+    if (!reason.trim()) {
+      message.warning('请填写退回原因');
+      return;
+    }
+    try {
+      await api.reviewClip(clip.uuid, 'rejected', reason);
+      message.success('已退回');
+      loadQueue();
+    } catch (error) {
+      console.error('退回失败:', error);
+      message.error('退回失败');
+    }
+  };
+
+  // 等待时间按上传时间计算
+  const formatAge = (timestamp: number) => {
+    const hours = Math.floor((Date.now() / 1000 - timestamp) / 3600);
+    return hours < 24 ? `${hours} 小时` : `${Math.floor(hours / 24)} 天`;
+  };
+
+  const loadTags = async () => {
+    try {
+      setTags(await api.listTags());
+    } catch (error) {
+      console.error('获取标签失败:', error);
+      message.error('获取标签失败');
+    }
+  };
+
+  const handleRenameTag = async (tag: Tag) => {
+    const name = window.prompt('标签名称', tag.name);
+    if (name == null) return;
+    const category = window.prompt('分类（可留空）', tag.category ?? '');
+    if (category == null) return;
+    try {
+      const updated = await api.updateTag(tag.id, { name, category: category || null });
+      setTags(prev => prev.map(t => (t.id === updated.id ? updated : t)));
+      message.success('标签已更新');
+    } catch (error) {
+      console.error('更新标签失败:', error);
+      message.error('更新标签失败');
+    }
+  };
+
+  const handleDeleteTag = async (tag: Tag) => {
+    try {
+      await api.deleteTag(tag.id);
+      setTags(prev => prev.filter(t => t.id !== tag.id));
+      message.success('标签已删除');
+    } catch (error) {
+      console.error('删除标签失败:', error);
+      message.error('删除标签失败');
+    }
+  };
+
   const loadUsers = async () => {
     try {
       setLoading(true);
@@ -41,7 +135,7 @@ const AdminPage: React.FC = () => {
     setError(null);
   };
 
-  const handlePermissionToggle = async (user: User, permission: 'is_admin' | 'can_stream' | 'is_disabled') => {
+  const handlePermissionToggle = async (user: User, permission: 'is_admin' | 'can_stream' | 'is_trusted' | 'is_disabled') => {
     // 防止用户修改自己的管理员权限或禁用自己
     if (currentUser && user.id === currentUser.id && user.is_admin) {
       if (permission === 'is_admin' || permission === 'is_disabled') {
@@ -79,12 +173,48 @@ const AdminPage: React.FC = () => {
     }
   };
 
+  const GIB = 1024 * 1024 * 1024;
+
+  const openQuotaModal = (user: User) => {
+    setQuotaUser(user);
+    setQuotaForm({
+      quota_bytes: user.quota_bytes ?? null,
+      quota_clips: user.quota_clips ?? null,
+      quota_daily_uploads: user.quota_daily_uploads ?? null,
+    });
+  };
+
+  const handleSaveQuota = async () => {
+    if (!quotaUser) return;
+    try {
+      const updated = await api.updateUserQuota(quotaUser.id, quotaForm);
+      setUsers(prev => prev.map(u => (u.id === updated.id ? updated : u)));
+      message.success('配额更新成功');
+      setQuotaUser(null);
+    } catch (error) {
+      console.error('更新配额失败:', error);
+      message.error('更新配额失败');
+    }
+  };
+
+  const formatQuota = (user: User) => {
+    const format = (value: number | null | undefined, unit: (v: number) => string) =>
+      value == null ? '默认' : value < 0 ? '不限' : unit(value);
+    return [
+      format(user.quota_bytes, v => `${(v / GIB).toFixed(1)} GiB`),
+      format(user.quota_clips, v => `${v} 个`),
+      format(user.quota_daily_uploads, v => `${v} 次/天`),
+    ].join(' / ');
+  };
+
   const getPermissionName = (permission: string) => {
     switch (permission) {
       case 'is_admin':
         return '管理员权限';
       case 'can_stream':
         return '开播权限';
+      case 'is_trusted':
+        return '免审状态';
       case 'is_disabled':
         return '禁用状态';
       default:
@@ -141,9 +271,10 @@ const AdminPage: React.FC = () => {
                     <th>状态</th>
                     <th>管理员</th>
                     <th>开播权限</th>
+                    <th>免审</th>
                     <th>禁用状态</th>
+                    <th>配额（空间/切片/每日上传）</th>
                     <th>注册时间</th>
-                    <th>最后更新</th>
                   </tr>
                 </thead>
                 <tbody>
@@ -183,6 +314,17 @@ const AdminPage: React.FC = () => {
                           <span className="toggle-slider"></span>
                         </label>
                       </td>
+                      <td>
+                        <label className="toggle-switch">
+                          <input
+                            type="checkbox"
+                            checked={user.is_trusted}
+                            disabled={updating.has(user.id)}
+                            onChange={() => handlePermissionToggle(user, 'is_trusted')}
+                          />
+                          <span className="toggle-slider"></span>
+                        </label>
+                      </td>
                       <td>
                         <label className="toggle-switch">
                           <input
@@ -197,6 +339,11 @@ const AdminPage: React.FC = () => {
                           <span className="toggle-slider"></span>
                         </label>
                       </td>
+                      <td>
+                        <button className="action-btn" onClick={() => openQuotaModal(user)}>
+                          {formatQuota(user)}
+                        </button>
+                      </td>
                       <td>{formatDate(user.created_at)}</td>
                     </tr>
                   ))}
@@ -212,6 +359,92 @@ const AdminPage: React.FC = () => {
           </div>
         )}
       </ContentCard>
+
+      <ContentCard title={`审核队列（${queueTotal}）`}>
+        <div className="tag-list">
+          {queue.length === 0 && <p>暂无待审核的切片</p>}
+          {queue.map(clip => (
+            <div key={clip.uuid} className="tag-item">
+              <span>
+                <span className="tag-category">{formatAge(clip.upload_time)}</span>
+                {clip.title}
+                {clip.vup && ` - ${clip.vup}`}
+              </span>
+              <span>
+                <button className="action-btn" onClick={() => navigate(`/clip/edit/${clip.uuid}`)}>查看</button>
+                <button className="action-btn" onClick={() => handleApprove(clip)}>通过</button>
+                <button className="action-btn danger" onClick={() => handleReject(clip)}>退回</button>
+              </span>
+            </div>
+          ))}
+        </div>
+      </ContentCard>
+
+      <ContentCard title="标签管理">
+        <div className="tag-list">
+          {tags.length === 0 && <p>暂无标签</p>}
+          {tags.map(tag => (
+            <div key={tag.id} className="tag-item">
+              <span>
+                {tag.category && <span className="tag-category">{tag.category}</span>}
+                {tag.name}
+              </span>
+              <span>
+                <button className="action-btn" onClick={() => handleRenameTag(tag)}>编辑</button>
+                <Popconfirm
+                  title="删除这个标签？切片上的该标签会一并移除"
+                  onConfirm={() => handleDeleteTag(tag)}
+                  okText="是"
+                  cancelText="否"
+                >
+                  <button className="action-btn danger">删除</button>
+                </Popconfirm>
+              </span>
+            </div>
+          ))}
+        </div>
+      </ContentCard>
+
+      <CatalogCard kind="vup" title="VUP 目录" />
+      <CatalogCard kind="song" title="歌曲目录" />
+
+      <Modal
+        title={`设置配额：${quotaUser?.uname ?? ''}`}
+        open={quotaUser !== null}
+        onOk={handleSaveQuota}
+        onCancel={() => setQuotaUser(null)}
+        okText="保存"
+        cancelText="取消"
+      >
+        <p>留空使用角色默认配额，填 -1 表示不限。</p>
+        <div className="quota-field">
+          <label>存储空间（GiB）</label>
+          <InputNumber
+            value={quotaForm.quota_bytes == null ? null : quotaForm.quota_bytes < 0 ? -1 : quotaForm.quota_bytes / GIB}
+            min={-1}
+            step={1}
+            onChange={v => setQuotaForm(f => ({ ...f, quota_bytes: v == null ? null : v < 0 ? -1 : Math.round(v * GIB) }))}
+          />
+        </div>
+        <div className="quota-field">
+          <label>切片数</label>
+          <InputNumber
+            value={quotaForm.quota_clips}
+            min={-1}
+            precision={0}
+            onChange={v => setQuotaForm(f => ({ ...f, quota_clips: v }))}
+          />
+        </div>
+        <div className="quota-field">
+          <label>24 小时内上传次数</label>
+          <InputNumber
+            value={quotaForm.quota_daily_uploads}
+            min={-1}
+            precision={0}
+            onChange={v => setQuotaForm(f => ({ ...f, quota_daily_uploads: v }))}
+          />
+        </div>
+      </Modal>
     </PageContainer>
   );
 };
@@ -1,10 +1,11 @@
 import React, { useEffect, useState, useCallback, useRef } from 'react';
-import { message, Modal, Table, Popconfirm } from 'antd';
+import { message, Modal, Table, Popconfirm, Select } from 'antd';
 import {useApi, useAuth} from '../context/AppContext';
 import { useNavigate } from 'react-router-dom';
-import type { Clip, Playlist } from '../types';
-import { EditOutlined, CheckCircleOutlined, PlaySquareOutlined, DeleteOutlined, EyeOutlined } from '@ant-design/icons';
-import type { ColumnsType } from 'antd/es/table';
+import type { Clip, ClipListQuery, ClipSort, Playlist, Tag, User } from '../types';
+import { EditOutlined, CheckCircleOutlined, CloseCircleOutlined, SendOutlined, PlaySquareOutlined, DeleteOutlined, EyeOutlined, ReloadOutlined, ScissorOutlined, MergeCellsOutlined, RollbackOutlined } from '@ant-design/icons';
+import type { ColumnsType, TablePaginationConfig } from 'antd/es/table';
+import type { SorterResult } from 'antd/es/table/interface';
 import PageContainer from '../components/PageContainer/PageContainer';
 import ContentCard from '../components/ContentCard/ContentCard';
 import './ClipsPage.css';
@@ -21,16 +22,57 @@ const ClipsPage: React.FC = () => {
   const [previewVideoUrl, setPreviewVideoUrl] = useState<string>('');
   const [loadingPlaylists, setLoadingPlaylists] = useState(false);
   const [loadingPreview, setLoadingPreview] = useState(false);
+  const [tags, setTags] = useState<Tag[]>([]);
+  const [tagFilter, setTagFilter] = useState<number[]>([]);
+  const [search, setSearch] = useState('');
+  const [statusFilter, setStatusFilter] = useState<string[]>([]);
+  const [ownerFilter, setOwnerFilter] = useState<number | undefined>(undefined);
+  const [dateFrom, setDateFrom] = useState('');
+  const [dateTo, setDateTo] = useState('');
+  const [sort, setSort] = useState<{ field: ClipSort; order: 'asc' | 'desc' }>({ field: 'upload_time', order: 'desc' });
+  const [page, setPage] = useState(1);
+  const [pageSize, setPageSize] = useState(10);
+  const [total, setTotal] = useState(0);
+  const [users, setUsers] = useState<User[]>([]);
+  const [trashed, setTrashed] = useState(false);
+  const [rejectingClip, setRejectingClip] = useState<Clip | null>(null);
+  const [rejectReason, setRejectReason] = useState('');
   const videoRef = useRef<HTMLVideoElement>(null);
   const api = useApi();
   const navigate = useNavigate();
   const { isAdmin, canStream } = useAuth();
 
+  // 日期输入按本地时区的整天计算，结束日期包含当天
+  const toTimestamp = (date: string, endOfDay = false) => {
+    if (!date) return undefined;
+    const time = new Date(`${date}T00:00:00`).getTime() / 1000;
+    return endOfDay ? time + 24 * 60 * 60 : time;
+  };
+
+  const clipQuery = (): ClipListQuery => ({
+    tags: tagFilter,
+    q: search.trim(),
+    status: statusFilter,
+    user_id: ownerFilter,
+    from: toTimestamp(dateFrom),
+    to: toTimestamp(dateTo, true),
+    trashed: trashed || undefined,
+    sort: sort.field,
+    order: sort.order,
+    page,
+    page_size: pageSize,
+  });
+
+  const loadClips = async () => {
+    const data = await api.listClips(clipQuery());
+    setClips(data.items);
+    setTotal(data.total);
+  };
+
   const fetchClips = async () => {
     try {
       setLoading(true);
-      const data = await api.listClips();
-      setClips(data);
+      await loadClips();
     } catch (error) {
       console.error('获取切片失败:', error);
       message.error('获取切片失败');
@@ -54,7 +96,70 @@ const ClipsPage: React.FC = () => {
 
   useEffect(() => {
     fetchClips();
-  }, []);
+  }, [tagFilter, statusFilter, ownerFilter, dateFrom, dateTo, trashed, sort, page, pageSize]);
+
+  // 搜索输入停顿后再请求
+  useEffect(() => {
+    const timer = setTimeout(() => {
+      if (page === 1) {
+        fetchClips();
+      } else {
+        setPage(1);
+      }
+    }, 300);
+    return () => clearTimeout(timer);
+  }, [search]);
+
+  useEffect(() => {
+    api.listTags().then(setTags).catch(error => {
+      console.error('获取标签失败:', error);
+    });
+    if (isAdmin) {
+      api.getAllUsers().then(setUsers).catch(error => {
+        console.error('获取用户失败:', error);
+      });
+    }
+  }, [isAdmin]);
+
+  // 筛选条件变化后回到第一页
+  const updateFilter = <T,>(setter: (value: T) => void) => (value: T) => {
+    setter(value);
+    setPage(1);
+  };
+
+  const handleTableChange = (
+    pagination: TablePaginationConfig,
+    _filters: unknown,
+    sorter: SorterResult<Clip> | SorterResult<Clip>[],
+  ) => {
+    const current = Array.isArray(sorter) ? sorter[0] : sorter;
+    const field = current?.order ? (current.columnKey as ClipSort) : 'upload_time';
+    const order = current?.order === 'ascend' ? 'asc' : 'desc';
+    if (field !== sort.field || order !== sort.order) {
+      setSort({ field, order });
+      setPage(1);
+    } else {
+      setPage(pagination.current ?? 1);
+    }
+    setPageSize(pagination.pageSize ?? pageSize);
+  };
+
+  const sortOrderOf = (field: ClipSort) =>
+    sort.field === field ? (sort.order === 'asc' ? 'ascend' as const : 'descend' as const) : null;
+
+  // 有切片正在处理时定时刷新进度
+  const hasActiveClips = clips.some(c => c.status === 'pending' || c.status === 'processing');
+  useEffect(() => {
+    if (!hasActiveClips) return;
+    const timer = setInterval(async () => {
+      try {
+        await loadClips();
+      } catch (error) {
+        console.error('刷新切片进度失败:', error);
+      }
+    }, 3000);
+    return () => clearInterval(timer);
+  }, [hasActiveClips, tagFilter, search, statusFilter, ownerFilter, dateFrom, dateTo, trashed, sort, page, pageSize]);
 
   // Handle review clip action
   const handleReviewClip = async (uuid: string) => {
@@ -68,6 +173,68 @@ const ClipsPage: React.FC = () => {
     }
   };
 
+  const handleRejectClip = async () => {
+    if (!rejectingClip) return;
+    if (!rejectReason.trim()) {
+      message.warning('请填写退回原因');
+      return;
+    }
+    try {
+      await api.reviewClip(rejectingClip.uuid, 'rejected', rejectReason);
+      message.success('已退回');
+      setRejectingClip(null);
+      setRejectReason('');
+      fetchClips();
+    } catch (error) {
+      console.error('退回失败:', error);
+      message.error('退回失败');
+    }
+  };
+
+  const handleResubmitClip = async (uuid: string) => {
+    try {
+      await api.resubmitClip(uuid);
+      message.success('已重新提交审核');
+      fetchClips();
+    } catch (error) {
+      console.error('重新提交失败:', error);
+      message.error('重新提交失败');
+    }
+  };
+
+  const handleReprocessClip = async (uuid: string) => {
+    try {
+      await api.reprocessClip(uuid);
+      message.success('已重新加入处理队列');
+      fetchClips();
+    } catch (error) {
+      console.error('重新处理失败:', error);
+      message.error('重新处理失败');
+    }
+  };
+
+  const handleAcceptTrim = async (uuid: string) => {
+    try {
+      await api.acceptSuggestedTrim(uuid);
+      message.success('已按建议裁剪并重新处理');
+      fetchClips();
+    } catch (error) {
+      console.error('应用裁剪建议失败:', error);
+      message.error('应用裁剪建议失败');
+    }
+  };
+
+  const handleMergeClip = async (uuid: string, into: string) => {
+    try {
+      await api.mergeClip(uuid, into);
+      message.success('已合并到原切片');
+      fetchClips();
+    } catch (error) {
+      console.error('合并失败:', error);
+      message.error('合并失败');
+    }
+  };
+
   // Handle add to playlist action
   const handleAddToPlaylistClick = (uuid: string) => {
     setCurrentClipUuid(uuid);
@@ -76,10 +243,10 @@ const ClipsPage: React.FC = () => {
   };
 
   // Handle delete clip action
-  const handleDeleteClip = async (uuid: string) => {
+  const handleDeleteClip = async (uuid: string, permanent = false) => {
     try {
-      await api.deleteClip(uuid);
-      message.success('删除成功');
+      await api.deleteClip(uuid, permanent);
+      message.success(permanent ? '已永久删除' : '已移到回收站');
       fetchClips();
     } catch (error) {
       console.error('删除失败:', error);
@@ -87,6 +254,23 @@ const ClipsPage: React.FC = () => {
     }
   };
 
+  const handleRestoreClip = async (uuid: string) => {
+    try {
+      await api.restoreClip(uuid);
+      message.success('已恢复，并放回原来的播放列表位置');
+      fetchClips();
+    } catch (error) {
+      console.error('恢复失败:', error);
+      message.error('恢复失败');
+    }
+  };
+
+  const toggleTrash = () => {
+    setTrashed(!trashed);
+    setSort({ field: trashed ? 'upload_time' : 'deleted_at', order: 'desc' });
+    setPage(1);
+  };
+
   // Handle preview clip action (admin only)
   const handlePreviewClip = async (uuid: string, title: string) => {
     setPreviewClipTitle(title);
@@ -159,16 +343,34 @@ const ClipsPage: React.FC = () => {
     };
   }, [isModalVisible, handleEnterKeyPress]);
 
-  const getStatusTag = (status: string) => {
+  const stageLabels: Record<string, string> = {
+    detecting: '检测首尾中',
+    analyzing: '分析中',
+    normalizing: '标准化中',
+    uploading: '上传中',
+  };
+
+  const getStatusTag = (status: string, record: Clip) => {
     switch (status) {
       case 'processing':
-        return <span className="status-tag status-processing">处理中</span>;
+        return (
+          <span className="status-tag status-processing">
+            {record.process_stage ? stageLabels[record.process_stage] : '处理中'}
+            {record.process_progress != null && ` ${record.process_progress}%`}
+          </span>
+        );
       case 'failed':
         return <span className="status-tag status-failed">失败</span>;
       case 'reviewing':
         return <span className="status-tag status-reviewing">审核中</span>;
       case 'reviewed':
         return <span className="status-tag status-reviewed">已审核</span>;
+      case 'rejected':
+        return (
+          <span className="status-tag status-failed" title={record.review_note ?? undefined}>
+            已退回
+          </span>
+        );
       default:
         return <span className="status-tag">{status}</span>;
     }
@@ -178,44 +380,131 @@ const ClipsPage: React.FC = () => {
     return new Date(timestamp * 1000).toLocaleString();
   };
 
+  const formatDuration = (ms?: number | null) => {
+    if (ms == null) return '-';
+    const seconds = Math.round(ms / 1000);
+    return `${Math.floor(seconds / 60)}:${String(seconds % 60).padStart(2, '0')}`;
+  };
+
   const columns: ColumnsType<Clip> = [
+    {
+      title: '',
+      key: 'poster',
+      width: 96,
+      render: (record: Clip) => (
+        <img
+          className="clip-poster"
+          src={api.getClipPosterUrl(record.uuid)}
+          alt=""
+          loading="lazy"
+          onError={(e) => { e.currentTarget.style.visibility = 'hidden'; }}
+        />
+      ),
+    },
     {
       title: '标题',
       dataIndex: 'title',
       key: 'title',
-      width: '25%',
+      width: '20%',
+      sorter: true,
+      sortOrder: sortOrderOf('title'),
+      render: (text: string, record: Clip) => (
+        <>
+          {text}
+          {record.duplicate_of && <span className="status-tag status-failed clip-duplicate">疑似重复</span>}
+        </>
+      ),
     },
     {
       title: 'VUP',
       dataIndex: 'vup',
       key: 'vup',
       width: '15%',
+      sorter: true,
+      sortOrder: sortOrderOf('vup'),
     },
     {
       title: '歌曲',
       dataIndex: 'song',
       key: 'song',
       width: '15%',
+      sorter: true,
+      sortOrder: sortOrderOf('song'),
+    },
+    {
+      title: '标签',
+      key: 'tags',
+      width: '10%',
+      render: (record: Clip) => (
+        <div className="clip-tags">
+          {record.tags?.map(tag => (
+            <span key={tag.id} className="status-tag clip-tag">{tag.name}</span>
+          ))}
+        </div>
+      ),
+    },
+    {
+      title: '时长',
+      dataIndex: 'duration_ms',
+      key: 'duration',
+      width: '5%',
+      sorter: true,
+      sortOrder: sortOrderOf('duration'),
+      render: (ms?: number | null) => formatDuration(ms),
     },
     {
       title: '上传时间',
       dataIndex: 'upload_time',
       key: 'upload_time',
       width: '15%',
+      sorter: true,
+      sortOrder: sortOrderOf('upload_time'),
       render: (text: number) => formatDate(text),
     },
+    ...(trashed ? [{
+      title: '删除时间',
+      dataIndex: 'deleted_at',
+      key: 'deleted_at',
+      width: '15%',
+      sorter: true,
+      sortOrder: sortOrderOf('deleted_at'),
+      render: (text: number) => formatDate(text),
+    }] : []),
     {
       title: '状态',
       dataIndex: 'status',
       key: 'status',
       width: '10%',
-      render: (text: string) => getStatusTag(text),
+      sorter: true,
+      sortOrder: sortOrderOf('status'),
+      render: (text: string, record: Clip) => getStatusTag(text, record),
     },
     {
       title: '操作',
       key: 'action',
       width: '20%',
-      render: (record: Clip) => (
+      render: (record: Clip) => trashed ? (
+        <div className="action-buttons">
+          <button
+            className="action-btn"
+            onClick={() => handleRestoreClip(record.uuid)}
+          >
+            <RollbackOutlined />
+            恢复
+          </button>
+          <Popconfirm
+            title="永久删除后无法恢复，确定吗？"
+            onConfirm={() => handleDeleteClip(record.uuid, true)}
+            okText="是"
+            cancelText="否"
+          >
+            <button className="action-btn danger">
+              <DeleteOutlined />
+              永久删除
+            </button>
+          </Popconfirm>
+        </div>
+      ) : (
         <div className="action-buttons">
           {(record.status!="reviewed" || isAdmin ) && (
           <button
@@ -235,6 +524,63 @@ const ClipsPage: React.FC = () => {
               通过审核
             </button>
           )}
+          {isAdmin && (record.status === 'reviewing' || record.status === 'reviewed') && (
+            <button
+              className="action-btn danger"
+              onClick={() => setRejectingClip(record)}
+            >
+              <CloseCircleOutlined />
+              退回
+            </button>
+          )}
+          {record.status === 'rejected' && (
+            <Popconfirm
+              title={`退回原因：${record.review_note ?? '无'}。已修改完成并重新提交审核？`}
+              onConfirm={() => handleResubmitClip(record.uuid)}
+              okText="是"
+              cancelText="否"
+            >
+              <button className="action-btn">
+                <SendOutlined />
+                重新提交
+              </button>
+            </Popconfirm>
+          )}
+          {record.status === 'failed' && (
+            <button
+              className="action-btn"
+              onClick={() => handleReprocessClip(record.uuid)}
+            >
+              <ReloadOutlined />
+              重新处理
+            </button>
+          )}
+          {record.suggested_in_ms != null && record.suggested_out_ms != null && (
+            <Popconfirm
+              title={`裁剪为 ${formatDuration(record.suggested_in_ms)} - ${formatDuration(record.suggested_out_ms)}？`}
+              onConfirm={() => handleAcceptTrim(record.uuid)}
+              okText="是"
+              cancelText="否"
+            >
+              <button className="action-btn">
+                <ScissorOutlined />
+                裁剪首尾
+              </button>
+            </Popconfirm>
+          )}
+          {isAdmin && record.duplicate_of && (
+            <Popconfirm
+              title="合并到原切片？播放列表中的引用会替换为原切片，本切片将被删除"
+              onConfirm={() => handleMergeClip(record.uuid, record.duplicate_of!)}
+              okText="是"
+              cancelText="否"
+            >
+              <button className="action-btn">
+                <MergeCellsOutlined />
+                合并重复
+              </button>
+            </Popconfirm>
+          )}
           { (canStream && record.status === 'reviewed') && (
           <button
             className="action-btn"
@@ -245,7 +591,7 @@ const ClipsPage: React.FC = () => {
           </button>
               )}
           <Popconfirm
-            title="确定删除这个切片吗？"
+            title="移到回收站？切片会从播放列表中移除，恢复时放回原处"
             onConfirm={() => handleDeleteClip(record.uuid)}
             okText="是"
             cancelText="否"
@@ -270,27 +616,125 @@ const ClipsPage: React.FC = () => {
   ];
 
   const refreshButton = (
-    <button
-      className="action-btn"
-      onClick={() => fetchClips()}
-    >
-      刷新
-    </button>
+    <div className="clips-toolbar">
+      <input
+        className="clips-search"
+        placeholder="搜索标题、VUP、歌曲"
+        value={search}
+        onChange={e => setSearch(e.target.value)}
+      />
+      <Select
+        mode="multiple"
+        allowClear
+        className="clips-status-filter"
+        placeholder="状态"
+        value={statusFilter}
+        onChange={updateFilter(setStatusFilter)}
+        options={[
+          { value: 'pending', label: '等待处理' },
+          { value: 'processing', label: '处理中' },
+          { value: 'failed', label: '失败' },
+          { value: 'reviewing', label: '审核中' },
+          { value: 'reviewed', label: '已审核' },
+          { value: 'rejected', label: '已退回' },
+        ]}
+      />
+      {isAdmin && (
+        <Select
+          allowClear
+          showSearch
+          className="clips-owner-filter"
+          placeholder="上传者"
+          value={ownerFilter}
+          onChange={updateFilter(setOwnerFilter)}
+          options={users.map(user => ({ value: user.id, label: user.uname }))}
+          optionFilterProp="label"
+        />
+      )}
+      <input
+        type="date"
+        className="clips-date"
+        title="上传时间起"
+        value={dateFrom}
+        onChange={e => updateFilter(setDateFrom)(e.target.value)}
+      />
+      <span>至</span>
+      <input
+        type="date"
+        className="clips-date"
+        title="上传时间止"
+        value={dateTo}
+        onChange={e => updateFilter(setDateTo)(e.target.value)}
+      />
+      <Select
+        mode="multiple"
+        allowClear
+        className="clips-tag-filter"
+        placeholder="按标签筛选"
+        value={tagFilter}
+        onChange={updateFilter(setTagFilter)}
+        options={tags.map(tag => ({
+          value: tag.id,
+          label: tag.category ? `${tag.category}: ${tag.name}` : tag.name,
+        }))}
+        optionFilterProp="label"
+      />
+      <button
+        className="action-btn"
+        onClick={toggleTrash}
+      >
+        <DeleteOutlined />
+        {trashed ? '返回切片列表' : '回收站'}
+      </button>
+      <button
+        className="action-btn"
+        onClick={() => fetchClips()}
+      >
+        刷新
+      </button>
+    </div>
   );
 
   return (
-    <PageContainer title="我的切片" extra={refreshButton}>
+    <PageContainer title={trashed ? '回收站' : '我的切片'} extra={refreshButton}>
       <ContentCard>
         <div className="clips-table-container">
           <Table
             columns={columns}
             dataSource={clips.map(clip => ({ ...clip, key: clip.uuid }))}
             loading={loading}
-            pagination={{ pageSize: 10 }}
+            onChange={handleTableChange}
+            pagination={{
+              current: page,
+              pageSize,
+              total,
+              showSizeChanger: true,
+              showTotal: count => `共 ${count} 个切片`,
+            }}
           />
         </div>
       </ContentCard>
 
+      <Modal
+        title={`退回切片 - ${rejectingClip?.title ?? ''}`}
+        open={rejectingClip !== null}
+        onOk={handleRejectClip}
+        onCancel={() => {
+          setRejectingClip(null);
+          setRejectReason('');
+        }}
+        okText="退回"
+        cancelText="取消"
+      >
+        <textarea
+          className="reject-reason"
+          rows={4}
+          placeholder="请填写退回原因，上传者修改后可重新提交"
+          value={rejectReason}
+          onChange={e => setRejectReason(e.target.value)}
+        />
+      </Modal>
+
       {/* 添加到播放列表的模态框 */}
       <Modal
         title="添加到播放列表"
@@ -1,8 +1,9 @@
-import React, { useEffect, useState, useCallback } from 'react';
-import { Form, message } from 'antd';
+import React, { useEffect, useState, useCallback, useRef } from 'react';
+import { Form, InputNumber, Checkbox, Popconfirm, Progress, Select, message } from 'antd';
+import axios from 'axios';
 import { useApi } from '../context/AppContext';
 import { useNavigate, useParams } from 'react-router-dom';
-import type {ClipRequest} from '../types';
+import type {Clip, ClipRequest, ClipEditRequest, ClipVersion, ClipReview, ReviewDecision, Tag, CatalogEntry} from '../types';
 import PageContainer from '../components/PageContainer/PageContainer';
 import ContentCard from '../components/ContentCard/ContentCard';
 import './EditClipPage.css';
@@ -14,6 +15,17 @@ const EditClipPage: React.FC = () => {
   const api = useApi();
   const navigate = useNavigate();
   const { uuid } = useParams<{ uuid: string }>();
+  const [clip, setClip] = useState<Clip | null>(null);
+  const [edit, setEdit] = useState<ClipEditRequest>({});
+  const [editing, setEditing] = useState(false);
+  const videoRef = useRef<HTMLVideoElement>(null);
+  const [versions, setVersions] = useState<ClipVersion[]>([]);
+  const [reviews, setReviews] = useState<ClipReview[]>([]);
+  const [resubmitNote, setResubmitNote] = useState('');
+  const [replaceProgress, setReplaceProgress] = useState<number | null>(null);
+  const [allTags, setAllTags] = useState<Tag[]>([]);
+  const [vups, setVups] = useState<CatalogEntry[]>([]);
+  const [songs, setSongs] = useState<CatalogEntry[]>([]);
 
   useEffect(() => {
     const fetchClip = async () => {
@@ -21,14 +33,30 @@ const EditClipPage: React.FC = () => {
 
       try {
         setLoading(true);
-        const clips = await api.listClips();
-        const clip = clips.find(c => c.uuid === uuid);
+        const [clip, tags] = await Promise.all([
+          api.getClip(uuid).catch(() => null),
+          api.listTags(),
+        ]);
+        setAllTags(tags);
+        // 目录只用于输入提示，获取失败不影响编辑
+        api.listCatalog('vup').then(setVups).catch(() => {});
+        api.listCatalog('song').then(setSongs).catch(() => {});
 
         if (clip) {
+          setClip(clip);
+          setEdit({});
+          api.listClipVersions(clip.uuid).then(setVersions).catch(error => {
+            console.error('获取历史版本失败:', error);
+          });
+          api.listClipReviews(clip.uuid).then(setReviews).catch(error => {
+            console.error('获取审核记录失败:', error);
+          });
           form.setFieldsValue({
             title: clip.title,
             vup: clip.vup,
             song: clip.song,
+            // 已有标签用 id，新输入的标签用名称，保存时创建
+            tags: clip.tags?.map(tag => String(tag.id)) ?? [],
           });
         } else {
           message.error('找不到指定的切片');
@@ -59,6 +87,13 @@ const EditClipPage: React.FC = () => {
       };
 
       await api.updateClip(uuid, data);
+
+      const tagIds: number[] = [];
+      for (const value of (values.tags ?? []) as string[]) {
+        const existing = allTags.find(tag => String(tag.id) === value || tag.name === value.trim());
+        tagIds.push(existing ? existing.id : (await api.createTag({ name: value.trim() })).id);
+      }
+      await api.setClipTags(uuid, tagIds);
       message.success('更新成功');
       navigate('/clips');
     } catch (error) {
@@ -69,6 +104,72 @@ const EditClipPage: React.FC = () => {
     }
   };
 
+  // 时间在界面上以秒显示，提交时转换为毫秒
+  const toSeconds = (ms?: number) => (ms == null ? null : ms / 1000);
+  const toMs = (seconds: number | null) => (seconds == null ? undefined : Math.round(seconds * 1000));
+
+  const markPoint = (key: 'in_ms' | 'out_ms') => {
+    if (!videoRef.current) return;
+    const ms = Math.round(videoRef.current.currentTime * 1000);
+    setEdit(e => ({ ...e, [key]: ms }));
+  };
+
+  const handleEdit = async () => {
+    if (!uuid) return;
+    try {
+      setEditing(true);
+      const result = await api.editClip(uuid, edit);
+      message.success(edit.new_clip ? '已创建新切片，正在处理' : '已加入处理队列');
+      navigate(edit.new_clip ? `/clip/edit/${result.uuid}` : '/clips');
+    } catch (error) {
+      console.error('剪辑失败:', error);
+      const reason = errorReason(error);
+      message.error(reason ? `剪辑失败：${reason}` : '剪辑失败');
+    } finally {
+      setEditing(false);
+    }
+  };
+
+  const errorReason = (error: unknown) =>
+    axios.isAxiosError(error) && typeof error.response?.data === 'string' ? error.response.data : '';
+
+  const handleReplaceFile = async (e: React.ChangeEvent<HTMLInputElement>) => {
+    const file = e.target.files?.[0];
+    e.target.value = '';
+    if (!uuid || !file) return;
+    try {
+      setReplaceProgress(0);
+      await api.replaceClipFile(uuid, file, setReplaceProgress);
+      message.success('文件已替换，处理完成后需要重新审核');
+      navigate('/clips');
+    } catch (error) {
+      console.error('替换文件失败:', error);
+      const reason = errorReason(error);
+      message.error(reason ? `替换文件失败：${reason}` : '替换文件失败');
+    } finally {
+      setReplaceProgress(null);
+    }
+  };
+
+  const handleRollback = async (version: ClipVersion) => {
+    if (!uuid) return;
+    try {
+      await api.rollbackClip(uuid, version.id);
+      message.success('已恢复到历史版本，需要重新审核');
+      navigate('/clips');
+    } catch (error) {
+      console.error('恢复版本失败:', error);
+      const reason = errorReason(error);
+      message.error(reason ? `恢复版本失败：${reason}` : '恢复版本失败');
+    }
+  };
+
+  const formatDuration = (ms?: number | null) => {
+    if (ms == null) return '-';
+    const seconds = Math.round(ms / 1000);
+    return `${Math.floor(seconds / 60)}:${String(seconds % 60).padStart(2, '0')}`;
+  };
+
   const handleCancel = () => {
     navigate('/clips');
   };
@@ -80,6 +181,10 @@ const EditClipPage: React.FC = () => {
       if (e.target instanceof HTMLTextAreaElement) {
         return;
       }
+      // 剪辑表单单独提交
+      if (e.target instanceof Element && e.target.closest('.clip-edit-card')) {
+        return;
+      }
       e.preventDefault();
       handleSubmit();
     }
@@ -102,6 +207,28 @@ const EditClipPage: React.FC = () => {
     </button>
   );
 
+  const decisionLabels: Record<ReviewDecision, string> = {
+    approved: '通过',
+    rejected: '退回',
+    comment: '意见',
+    resubmitted: '重新提交',
+    auto_approved: '自动通过',
+  };
+
+  const handleResubmit = async () => {
+    if (!clip) return;
+    try {
+      const updated = await api.resubmitClip(clip.uuid, resubmitNote || undefined);
+      setClip(updated);
+      setResubmitNote('');
+      setReviews(await api.listClipReviews(clip.uuid));
+      message.success('已重新提交审核');
+    } catch (error) {
+      console.error('重新提交失败:', error);
+      message.error('重新提交失败');
+    }
+  };
+
   return (
     <PageContainer title="编辑切片" extra={backButton}>
       <ContentCard loading={loading}>
@@ -130,6 +257,7 @@ const EditClipPage: React.FC = () => {
                 <Form.Item name="vup">
                   <input
                     className="form-input"
+                    list="vup-options"
                     placeholder="请输入VUP名称（可选）"
                   />
                 </Form.Item>
@@ -140,10 +268,32 @@ const EditClipPage: React.FC = () => {
                 <Form.Item name="song">
                   <input
                     className="form-input"
+                    list="song-options"
                     placeholder="请输入歌曲名称（可选）"
                   />
                 </Form.Item>
               </div>
+              <datalist id="vup-options">
+                {vups.map(v => <option key={v.id} value={v.name} />)}
+              </datalist>
+              <datalist id="song-options">
+                {songs.map(s => <option key={s.id} value={s.name} />)}
+              </datalist>
+
+              <div className="form-group">
+                <label className="form-label">标签</label>
+                <Form.Item name="tags">
+                  <Select
+                    mode="tags"
+                    placeholder="选择或输入新标签"
+                    options={allTags.map(tag => ({
+                      value: String(tag.id),
+                      label: tag.category ? `${tag.category}: ${tag.name}` : tag.name,
+                    }))}
+                    optionFilterProp="label"
+                  />
+                </Form.Item>
+              </div>
 
               <div className="form-actions">
                 <button
@@ -165,6 +315,177 @@ const EditClipPage: React.FC = () => {
           </div>
         )}
       </ContentCard>
+
+      {clip && (clip.status === 'reviewing' || clip.status === 'reviewed' || clip.status === 'failed') && (
+      <ContentCard title="剪辑">
+        <div className="edit-form-card clip-edit-card">
+          {clip.status !== 'failed' && (
+            <video
+              ref={videoRef}
+              className="edit-preview"
+              src={api.getClipPreviewUrl(clip.uuid)}
+              controls
+              preload="metadata"
+            />
+          )}
+
+          <div className="form-group edit-points">
+            <div>
+              <label className="form-label">入点（秒）</label>
+              <InputNumber
+                min={0}
+                step={0.1}
+                value={toSeconds(edit.in_ms)}
+                onChange={v => setEdit(e => ({ ...e, in_ms: toMs(v) }))}
+              />
+              <button type="button" className="form-btn secondary" onClick={() => markPoint('in_ms')}>
+                设为当前位置
+              </button>
+            </div>
+            <div>
+              <label className="form-label">出点（秒）</label>
+              <InputNumber
+                min={0}
+                step={0.1}
+                value={toSeconds(edit.out_ms)}
+                onChange={v => setEdit(e => ({ ...e, out_ms: toMs(v) }))}
+              />
+              <button type="button" className="form-btn secondary" onClick={() => markPoint('out_ms')}>
+                设为当前位置
+              </button>
+            </div>
+          </div>
+
+          <div className="form-group edit-points">
+            <div>
+              <label className="form-label">淡入（秒）</label>
+              <InputNumber
+                min={0}
+                step={0.5}
+                value={toSeconds(edit.fade_in_ms)}
+                onChange={v => setEdit(e => ({ ...e, fade_in_ms: toMs(v) }))}
+              />
+            </div>
+            <div>
+              <label className="form-label">淡出（秒）</label>
+              <InputNumber
+                min={0}
+                step={0.5}
+                value={toSeconds(edit.fade_out_ms)}
+                onChange={v => setEdit(e => ({ ...e, fade_out_ms: toMs(v) }))}
+              />
+            </div>
+            <div>
+              <label className="form-label">
+                音量调整（dB{clip?.volume_db ? `，当前已调整 ${clip.volume_db.toFixed(1)}` : ''}）
+              </label>
+              <InputNumber
+                min={-20 - (clip?.volume_db ?? 0)}
+                max={20 - (clip?.volume_db ?? 0)}
+                step={1}
+                value={edit.volume_db ?? null}
+                onChange={v => setEdit(e => ({ ...e, volume_db: v ?? undefined }))}
+              />
+            </div>
+          </div>
+
+          <div className="form-group">
+            <Checkbox
+              checked={!!edit.new_clip}
+              onChange={e => setEdit(prev => ({ ...prev, new_clip: e.target.checked }))}
+            >
+              保留原切片，另存为新切片
+            </Checkbox>
+          </div>
+
+          <div className="form-actions">
+            <button
+              type="button"
+              className="form-btn primary"
+              disabled={editing}
+              onClick={handleEdit}
+            >
+              {editing ? '提交中...' : '应用剪辑'}
+            </button>
+          </div>
+        </div>
+      </ContentCard>
+      )}
+
+      {clip && (clip.status === 'rejected' || reviews.length > 0) && (
+      <ContentCard title="审核">
+        <div className="edit-form-card clip-edit-card">
+          {clip.status === 'rejected' && (
+            <div className="form-group">
+              <label className="form-label">退回原因</label>
+              <div className="review-reason">{clip.review_note}</div>
+              <textarea
+                className="form-input"
+                rows={2}
+                placeholder="修改说明（可选）"
+                value={resubmitNote}
+                onChange={e => setResubmitNote(e.target.value)}
+              />
+              <button type="button" className="form-btn primary" onClick={handleResubmit}>
+                重新提交审核
+              </button>
+              <div className="form-hint">修改信息、剪辑或替换文件后再重新提交</div>
+            </div>
+          )}
+
+          <div className="form-group">
+            <label className="form-label">审核记录</label>
+            {reviews.map(review => (
+              <div key={review.id} className="review-item">
+                <span className={`review-decision review-${review.decision}`}>
+                  {decisionLabels[review.decision]}
+                </span>
+                <span>{review.user_name ?? `用户 ${review.user_id}`}</span>
+                <span>{new Date(review.created_at * 1000).toLocaleString()}</span>
+                {review.note && <div className="review-note">{review.note}</div>}
+              </div>
+            ))}
+          </div>
+        </div>
+      </ContentCard>
+      )}
+
+      {clip && clip.status !== 'pending' && clip.status !== 'processing' && (
+      <ContentCard title="文件与历史版本">
+        <div className="edit-form-card clip-edit-card">
+          <div className="form-group">
+            <label className="form-label">替换文件</label>
+            <input
+              type="file"
+              accept="video/*,audio/*"
+              disabled={replaceProgress !== null}
+              onChange={handleReplaceFile}
+            />
+            {replaceProgress !== null && <Progress percent={replaceProgress} size="small" />}
+            <div className="form-hint">播放列表中的引用保持不变，当前文件会保留为历史版本</div>
+          </div>
+
+          <div className="form-group">
+            <label className="form-label">历史版本</label>
+            {versions.length === 0 && <div className="form-hint">暂无历史版本</div>}
+            {versions.map(version => (
+              <div key={version.id} className="version-item">
+                <span>{new Date(version.created_at * 1000).toLocaleString()}</span>
+                <span>{formatDuration(version.duration_ms)}</span>
+                <Popconfirm
+                  title="恢复到这个版本？当前文件会保留为历史版本"
+                  onConfirm={() => handleRollback(version)}
+                  okText="是"
+                  cancelText="否"
+                >
+                  <button type="button" className="form-btn secondary">恢复</button>
+                </Popconfirm>
+              </div>
+            ))}
+          </div>
+        </div>
+      </ContentCard>
+      )}
     </PageContainer>
   );
 };
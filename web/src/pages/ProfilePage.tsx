@@ -22,14 +22,16 @@ const ProfilePage: React.FC = () => {
   const fetchUserStats = async () => {
     try {
       setLoading(true);
-      const [clips, playlists] = await Promise.all([
-        api.listClips(),
+      // 只需要总数，每次取一条即可
+      const [clips, reviewed, playlists] = await Promise.all([
+        api.listClips({ page_size: 1 }),
+        api.listClips({ status: ['reviewed'], page_size: 1 }),
         (currentUser?.can_stream)?api.listPlaylists():Promise.resolve([])
       ]);
 
       setStats({
-        totalClips: clips.length,
-        reviewedClips: clips.filter(clip => clip.status === 'reviewed').length,
+        totalClips: clips.total,
+        reviewedClips: reviewed.total,
         totalPlaylists: playlists.length
       });
     } catch (error) {
@@ -44,6 +46,14 @@ const ProfilePage: React.FC = () => {
     message.success('已退出登录');
   };
 
+  const formatBytes = (bytes: number) => {
+    if (bytes === 0) return '0 B';
+    const k = 1024;
+    const sizes = ['B', 'KB', 'MB', 'GB', 'TB'];
+    const i = Math.min(Math.floor(Math.log(bytes) / Math.log(k)), sizes.length - 1);
+    return parseFloat((bytes / Math.pow(k, i)).toFixed(1)) + ' ' + sizes[i];
+  };
+
   // const formatDate = (dateString: string) => {
   //   return new Date(dateString).toLocaleString('zh-CN');
   // };
@@ -159,6 +169,32 @@ const ProfilePage: React.FC = () => {
               </div>
             </div>
 
+            {/* 配额信息 */}
+            {currentUser.quota && (
+            <div className="profile-section">
+              <h3 className="section-title">💾 上传配额</h3>
+              <div className="stats-grid">
+                <div className="stat-card">
+                  <div className="stat-number">
+                    {formatBytes(currentUser.quota.usage.bytes)} / {currentUser.quota.max_bytes != null ? formatBytes(currentUser.quota.max_bytes) : '不限'}
+                  </div>
+                  <div className="stat-label">存储空间</div>
+                </div>
+                <div className="stat-card">
+                  <div className="stat-number">
+                    {currentUser.quota.usage.clips} / {currentUser.quota.max_clips ?? '不限'}
+                  </div>
+                  <div className="stat-label">切片数</div>
+                </div>
+                <div className="stat-card">
+                  <div className="stat-number">
+                    {currentUser.quota.usage.daily_uploads} / {currentUser.quota.max_daily_uploads ?? '不限'}
+                  </div>
+                  <div className="stat-label">24 小时内上传</div>
+                </div>
+              </div>
+            </div>)}
+
             {/* 操作按钮 */}
             <div className="profile-actions">
               <button
@@ -1,8 +1,9 @@
 import React, { useState, useRef, useCallback, useEffect } from 'react';
 import { Form, message } from 'antd';
+import axios from 'axios';
 import { useApi } from '../context/AppContext';
 import { useNavigate } from 'react-router-dom';
-import type { ClipRequest, ServerConfig } from '../types';
+import type { ClipRequest, ServerConfig, CatalogEntry } from '../types';
 import PageContainer from '../components/PageContainer/PageContainer';
 import ContentCard from '../components/ContentCard/ContentCard';
 import './UploadPage.css';
@@ -14,12 +15,17 @@ const UploadPage: React.FC = () => {
   const [uploadProgress, setUploadProgress] = useState(0);
   const [dragOver, setDragOver] = useState(false);
   const [serverConfig, setServerConfig] = useState<ServerConfig | null>(null);
+  const [vups, setVups] = useState<CatalogEntry[]>([]);
+  const [songs, setSongs] = useState<CatalogEntry[]>([]);
   const fileInputRef = useRef<HTMLInputElement>(null);
   const api = useApi();
   const navigate = useNavigate();
 
   useEffect(() => {
     fetchServerConfig();
+    // 目录只用于输入提示，获取失败不影响上传
+    api.listCatalog('vup').then(setVups).catch(() => {});
+    api.listCatalog('song').then(setSongs).catch(() => {});
   }, []);
 
   const fetchServerConfig = async () => {
@@ -97,16 +103,24 @@ const UploadPage: React.FC = () => {
         song: values.song || '',
       };
 
-      await api.uploadClip(file, metadata, (progress: number) => {
+      const clip = await api.uploadClipResumable(file, metadata, (progress: number) => {
         setUploadProgress(progress);
       });
 
       setUploadProgress(100);
-      message.success('上传成功');
+      if (clip.duplicate_of) {
+        message.warning('上传成功，但该文件与已有切片重复，可能会被管理员合并');
+      } else {
+        message.success('上传成功');
+      }
       navigate('/clips');
     } catch (error) {
       console.error('上传失败:', error);
-      message.error('上传失败');
+      // 文件校验未通过时服务器会返回具体原因
+      const reason = axios.isAxiosError(error) && error.response?.status && error.response.status < 500
+        ? error.response.data
+        : null;
+      message.error(typeof reason === 'string' && reason ? `上传失败：${reason}` : '上传失败');
     } finally {
       setUploading(false);
     }
@@ -214,6 +228,7 @@ const UploadPage: React.FC = () => {
                 <Form.Item name="vup">
                   <input
                     className="form-input"
+                    list="vup-options"
                     placeholder="请输入VUP名称（可选）"
                   />
                 </Form.Item>
@@ -224,10 +239,17 @@ const UploadPage: React.FC = () => {
                 <Form.Item name="song">
                   <input
                     className="form-input"
+                    list="song-options"
                     placeholder="请输入歌曲名称（可选）"
                   />
                 </Form.Item>
               </div>
+              <datalist id="vup-options">
+                {vups.map(v => <option key={v.id} value={v.name} />)}
+              </datalist>
+              <datalist id="song-options">
+                {songs.map(s => <option key={s.id} value={s.name} />)}
+              </datalist>
             </Form>
           </div>
 
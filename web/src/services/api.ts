@@ -4,6 +4,17 @@ import type {
     LoginInfo,
     Clip,
     ClipRequest,
+    ClipListQuery,
+    ClipListResponse,
+    ClipEditRequest,
+    ClipVersion,
+    ClipReview,
+    ReviewDecision,
+    Tag,
+    TagRequest,
+    CatalogKind,
+    CatalogEntry,
+    CatalogRequest,
     Playlist,
     PlaylistRequest,
     PlaylistItem,
@@ -14,7 +25,9 @@ import type {
     RoomInfo,
     ServerConfig,
     User,
-    UpdateUserPermissionsRequest
+    UpdateUserPermissionsRequest,
+    UpdateUserQuotaRequest,
+    UploadSession
 } from '../types';
 
 class ApiService {
@@ -89,8 +102,58 @@ class ApiService {
   }
 
   // 切片��关API
-  async listClips(): Promise<Clip[]> {
-    const response = await this.api.get('/clips');
+  async listClips(query: ClipListQuery = {}): Promise<ClipListResponse> {
+    const params = {
+      ...query,
+      tags: query.tags?.length ? query.tags.join(',') : undefined,
+      status: query.status?.length ? query.status.join(',') : undefined,
+      q: query.q || undefined,
+    };
+    const response = await this.api.get('/clips', { params });
+    return response.data;
+  }
+
+  async getClip(uuid: string): Promise<Clip> {
+    const response = await this.api.get(`/clip/${uuid}`);
+    return response.data;
+  }
+
+  async setClipTags(uuid: string, tags: number[]): Promise<Clip> {
+    const response = await this.api.post(`/clip/${uuid}/tags`, { tags });
+    return response.data;
+  }
+
+  async listTags(): Promise<Tag[]> {
+    const response = await this.api.get('/tags');
+    return response.data;
+  }
+
+  async createTag(tag: TagRequest): Promise<Tag> {
+    const response = await this.api.post('/tags', tag);
+    return response.data;
+  }
+
+  async updateTag(id: number, tag: TagRequest): Promise<Tag> {
+    const response = await this.api.post(`/admin/tags/${id}`, tag);
+    return response.data;
+  }
+
+  async deleteTag(id: number): Promise<void> {
+    await this.api.delete(`/admin/tags/${id}`);
+  }
+
+  async listCatalog(kind: CatalogKind): Promise<CatalogEntry[]> {
+    const response = await this.api.get(`/${kind}s`);
+    return response.data;
+  }
+
+  async updateCatalogEntry(kind: CatalogKind, id: number, entry: CatalogRequest): Promise<CatalogEntry> {
+    const response = await this.api.post(`/admin/${kind}s/${id}`, entry);
+    return response.data;
+  }
+
+  async mergeCatalogEntry(kind: CatalogKind, id: number, into: number): Promise<CatalogEntry> {
+    const response = await this.api.post(`/admin/${kind}s/${id}/merge`, { into });
     return response.data;
   }
 
@@ -115,6 +178,63 @@ class ApiService {
     return response.data;
   }
 
+  // 分片续传上传，网络中断后从服务器记录的偏移量继续
+  async uploadClipResumable(file: File, metadata: ClipRequest, onProgress?: (progress: number) => void): Promise<Clip> {
+    const CHUNK_SIZE = 8 * 1024 * 1024;
+    const MAX_RETRIES = 5;
+    // 记住上传会话，刷新页面后选择同一文件可以继续上传
+    const key = `upload:${file.name}:${file.size}:${file.lastModified}`;
+
+    let session: UploadSession | null = null;
+    const savedId = localStorage.getItem(key);
+    if (savedId) {
+      try {
+        session = (await this.api.get(`/uploads/${savedId}`)).data;
+      } catch {
+        localStorage.removeItem(key);
+      }
+    }
+    if (!session) {
+      const response = await this.api.post('/uploads', {
+        file_name: file.name,
+        size: file.size,
+        metadata,
+      });
+      session = response.data as UploadSession;
+      localStorage.setItem(key, session.id);
+    }
+
+    let offset = session.offset;
+    let retries = 0;
+    while (offset < file.size) {
+      const chunk = file.slice(offset, Math.min(offset + CHUNK_SIZE, file.size));
+      try {
+        const response = await this.api.patch(`/uploads/${session.id}`, chunk, {
+          headers: {
+            'Content-Type': 'application/offset+octet-stream',
+            'Upload-Offset': String(offset),
+          },
+          timeout: 0,
+          onUploadProgress: (progressEvent) => {
+            onProgress?.(Math.round(((offset + progressEvent.loaded) * 100) / file.size));
+          },
+        });
+        offset = response.data.offset;
+        retries = 0;
+      } catch (error) {
+        if (++retries > MAX_RETRIES) throw error;
+        await new Promise((resolve) => setTimeout(resolve, 1000 * retries));
+        // 以服务器实际收到的数据为准
+        offset = (await this.api.get(`/uploads/${session.id}`)).data.offset;
+      }
+      onProgress?.(Math.round((offset * 100) / file.size));
+    }
+
+    const response = await this.api.post(`/uploads/${session.id}/finalize`, null, { timeout: 0 });
+    localStorage.removeItem(key);
+    return response.data;
+  }
+
   async updateClip(uuid: string, data: ClipRequest): Promise<Clip> {
     const response = await this.api.post(`/clip/${uuid}`, data);
     return response.data;
@@ -125,8 +245,88 @@ class ApiService {
     return response.data;
   }
 
-  async deleteClip(uuid: string): Promise<void> {
-    await this.api.delete(`/clip/${uuid}`);
+  async reviewClip(uuid: string, decision: ReviewDecision, note?: string): Promise<Clip> {
+    const response = await this.api.post(`/clip/${uuid}/review`, { decision, note });
+    return response.data;
+  }
+
+  async resubmitClip(uuid: string, note?: string): Promise<Clip> {
+    const response = await this.api.post(`/clip/${uuid}/resubmit`, { note });
+    return response.data;
+  }
+
+  async listClipReviews(uuid: string): Promise<ClipReview[]> {
+    const response = await this.api.get(`/clip/${uuid}/reviews`);
+    return response.data;
+  }
+
+  async getReviewQueue(page = 1, pageSize = 20): Promise<ClipListResponse> {
+    const response = await this.api.get('/admin/reviews/queue', {
+      params: { page, page_size: pageSize },
+    });
+    return response.data;
+  }
+
+  async reprocessClip(uuid: string): Promise<Clip> {
+    const response = await this.api.post(`/clip/${uuid}/reprocess`);
+    return response.data;
+  }
+
+  async editClip(uuid: string, edit: ClipEditRequest): Promise<Clip> {
+    const response = await this.api.post(`/clip/${uuid}/edit`, edit);
+    return response.data;
+  }
+
+  async replaceClipFile(uuid: string, file: File, onProgress?: (progress: number) => void): Promise<Clip> {
+    const formData = new FormData();
+    formData.append('file', file);
+
+    const response = await this.api.post(`/clip/${uuid}/file`, formData, {
+      headers: {
+        'Content-Type': 'multipart/form-data'
+      },
+      onUploadProgress: (progressEvent) => {
+        if (progressEvent.total && onProgress) {
+          onProgress(Math.round((progressEvent.loaded * 100) / progressEvent.total));
+        }
+      }
+    });
+    return response.data;
+  }
+
+  async listClipVersions(uuid: string): Promise<ClipVersion[]> {
+    const response = await this.api.get(`/clip/${uuid}/versions`);
+    return response.data;
+  }
+
+  async rollbackClip(uuid: string, versionId: number): Promise<Clip> {
+    const response = await this.api.post(`/clip/${uuid}/versions/${versionId}/rollback`);
+    return response.data;
+  }
+
+  async acceptSuggestedTrim(uuid: string): Promise<Clip> {
+    const response = await this.api.post(`/clip/${uuid}/trim/accept`);
+    return response.data;
+  }
+
+  async listDuplicateClips(): Promise<Clip[]> {
+    const response = await this.api.get('/admin/clips/duplicates');
+    return response.data;
+  }
+
+  async mergeClip(uuid: string, into: string): Promise<Clip> {
+    const response = await this.api.post(`/admin/clips/${uuid}/merge`, { into });
+    return response.data;
+  }
+
+  // 默认移入回收站，permanent 为 true 时永久删除回收站中的切片
+  async deleteClip(uuid: string, permanent = false): Promise<void> {
+    await this.api.delete(`/clip/${uuid}`, { params: { permanent: permanent || undefined } });
+  }
+
+  async restoreClip(uuid: string): Promise<Clip> {
+    const response = await this.api.post(`/clip/${uuid}/restore`);
+    return response.data;
   }
 
   // 获取视频预览URL（仅admin可用，支持分段加载）
@@ -135,6 +335,14 @@ class ApiService {
     return `${this.api.defaults.baseURL}/clip/${uuid}/preview?token=${this.token}`;
   }
 
+  getClipPosterUrl(uuid: string): string {
+    return `${this.api.defaults.baseURL}/clip/${uuid}/poster.jpg?token=${this.token}`;
+  }
+
+  getClipSpriteVttUrl(uuid: string): string {
+    return `${this.api.defaults.baseURL}/clip/${uuid}/sprite.vtt?token=${this.token}`;
+  }
+
   // 废弃的方法，保留以避免破坏现有代码
   async getClipPreviewBlob(uuid: string): Promise<string> {
     // 现在直接返回支持Range的URL而不是blob
@@ -181,7 +389,7 @@ class ApiService {
 
   async getPlaylistItems(id: number): Promise<PlaylistItem[]> {
     const response = await this.api.get(`/playlists/${id}/items`);
-    return response.data;
+    return response.data.items;
   }
 
   async addClipToPlaylist(data: PlaylistItemRequest): Promise<void> {
@@ -230,6 +438,11 @@ class ApiService {
     const response = await this.api.post(`/admin/users/${userId}/permissions`, permissions);
     return response.data;
   }
+
+  async updateUserQuota(userId: number, quota: UpdateUserQuotaRequest): Promise<User> {
+    const response = await this.api.post(`/admin/users/${userId}/quota`, quota);
+    return response.data;
+  }
 }
 
 export default ApiService;
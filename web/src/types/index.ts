@@ -28,12 +28,38 @@ export interface User {
   is_admin: boolean;
   can_stream: boolean;
   is_disabled: boolean;
+  // 可信用户的切片满足规则时自动通过审核
+  is_trusted: boolean;
   created_at: string;
+  // 用户单独的配额，null 表示使用角色配额，负数表示不限
+  quota_bytes?: number | null;
+  quota_clips?: number | null;
+  quota_daily_uploads?: number | null;
+  // 仅 /user/me 返回
+  quota?: Quota;
+}
+
+export interface Quota {
+  max_bytes: number | null;
+  max_clips: number | null;
+  max_daily_uploads: number | null;
+  usage: {
+    bytes: number;
+    clips: number;
+    daily_uploads: number;
+  };
+}
+
+export interface UpdateUserQuotaRequest {
+  quota_bytes: number | null;
+  quota_clips: number | null;
+  quota_daily_uploads: number | null;
 }
 
 export interface UpdateUserPermissionsRequest {
   is_admin?: boolean;
   can_stream?: boolean;
+  is_trusted?: boolean;
   is_disabled?: boolean;
 }
 
@@ -43,8 +69,107 @@ export interface Clip {
   title: string;
   vup: string;
   song: string;
+  vup_id?: number | null;
+  song_id?: number | null;
   upload_time: number;
   status: string;
+  process_stage?: 'detecting' | 'analyzing' | 'normalizing' | 'uploading' | null;
+  process_progress?: number | null;
+  duration_ms?: number | null;
+  width?: number | null;
+  height?: number | null;
+  frame_rate?: number | null;
+  video_codec?: string | null;
+  audio_codec?: string | null;
+  bit_rate?: number | null;
+  file_size?: number | null;
+  loudness?: number | null;
+  // 之前编辑累计的音量调整
+  volume_db?: number;
+  suggested_in_ms?: number | null;
+  suggested_out_ms?: number | null;
+  duplicate_of?: string | null;
+  // 最近一次审核的意见，退回时为原因
+  review_note?: string | null;
+  // 移入回收站的时间，Unix 时间戳（秒）
+  deleted_at?: number | null;
+  // 仅切片列表返回
+  tags?: Tag[];
+}
+
+export type ReviewDecision = 'approved' | 'rejected' | 'comment' | 'resubmitted' | 'auto_approved';
+
+export interface ClipReview {
+  id: number;
+  user_id: number;
+  user_name?: string | null;
+  decision: ReviewDecision;
+  note?: string | null;
+  created_at: number;
+}
+
+export type ClipSort = 'upload_time' | 'title' | 'vup' | 'song' | 'duration' | 'file_size' | 'status' | 'deleted_at';
+
+export interface ClipListQuery {
+  tags?: number[];
+  q?: string;
+  status?: string[];
+  user_id?: number;
+  vup_id?: number;
+  song_id?: number;
+  // Unix 时间戳（秒）
+  from?: number;
+  to?: number;
+  // 查看回收站
+  trashed?: boolean;
+  sort?: ClipSort;
+  order?: 'asc' | 'desc';
+  page?: number;
+  page_size?: number;
+}
+
+export interface ClipListResponse {
+  items: Clip[];
+  total: number;
+  page: number;
+  page_size: number;
+}
+
+export interface Tag {
+  id: number;
+  name: string;
+  category?: string | null;
+}
+
+export interface TagRequest {
+  name: string;
+  category?: string | null;
+}
+
+// vup 和歌曲目录
+export type CatalogKind = 'vup' | 'song';
+
+export interface CatalogEntry {
+  id: number;
+  name: string;
+  // vup 的头像或歌曲的封面
+  image_url?: string | null;
+  link?: string | null;
+  aliases: string[];
+  clip_count: number;
+}
+
+export interface CatalogRequest {
+  name: string;
+  image_url?: string | null;
+  link?: string | null;
+  aliases: string[];
+}
+
+export interface UploadSession {
+  id: string;
+  offset: number;
+  size: number;
 }
 
 export interface ClipRequest {
@@ -53,6 +178,24 @@ export interface ClipRequest {
   song: string;
 }
 
+export interface ClipVersion {
+  id: number;
+  user_id: number;
+  duration_ms?: number | null;
+  file_size?: number | null;
+  created_at: number;
+}
+
+export interface ClipEditRequest {
+  in_ms?: number;
+  out_ms?: number;
+  fade_in_ms?: number;
+  fade_out_ms?: number;
+  volume_db?: number;
+  // 为 true 时保留原切片，以编辑结果创建新切片
+  new_clip?: boolean;
+}
+
 // 播放列表相关类型
 export interface Playlist {
   id: number;
@@ -77,6 +220,11 @@ export interface PlaylistItem {
   position: number;
   clip_title: string;
   clip_vup: string;
+  duration_ms?: number;
+  start_offset_ms?: number;
+  eta?: number;
+  // 直播时会跳过，不计入时间表
+  skipped: boolean;
 }
 
 export interface PlaylistItemRequest {